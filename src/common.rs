@@ -12,14 +12,79 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 
+static MESSAGES_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Output formats for commands producing data other tools may want to consume.
+///
+#[derive(Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+    Yaml,
+}
+
+/// Send all messages from the 'infoln!' family of macros to stderr, leaving
+/// stdout for machine-readable output.
+///
+pub fn set_messages_to_stderr() {
+    MESSAGES_TO_STDERR.store(true, Ordering::Relaxed);
+}
+
+pub fn print_message(msg: String) {
+    if MESSAGES_TO_STDERR.load(Ordering::Relaxed) {
+        eprintln!("{}", msg);
+    } else {
+        println!("{}", msg);
+    }
+}
+
+/// Obtain the next page's URL from a response's 'Link' header, if any.
+///
+pub fn get_next_page(response: &reqwest::Response) -> Option<reqwest::Url> {
+    let link = response
+        .headers()
+        .get(reqwest::header::LINK)?
+        .to_str()
+        .ok()?;
+    let re = regex::Regex::new(r#"<([^>]+)>\s*;\s*rel="?next"?"#).unwrap();
+    let next = re.captures(link)?.get(1)?.as_str();
+    response.url().join(next).ok()
+}
+
+/// Print 'value' in the provided machine-readable 'format'.
+///
+pub fn print_structured<T: serde::Serialize>(format: &OutputFormat, value: &T) -> Result<(), ()> {
+    let res = match format {
+        OutputFormat::Json => serde_json::to_string_pretty(&value).map_err(|e| e.to_string()),
+        OutputFormat::Yaml => serde_yaml::to_string(&value).map_err(|e| e.to_string()),
+        OutputFormat::Table => {
+            log::error!("table output is not a structured format");
+            return Err(());
+        }
+    };
+    match res {
+        Ok(v) => {
+            println!("{}", v.trim_end());
+            Ok(())
+        }
+        Err(err) => {
+            log::error!("Unable to serialize output: {}", err);
+            Err(())
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! warnln {
     ($($t:tt)*) => {{
         extern crate colored;
         use colored::*;
-        println!("\u{26A0}\u{fe0f}  {}", format!($($t)*).magenta().bold());
+        $crate::common::print_message(format!("\u{26A0}\u{fe0f}  {}", format!($($t)*).magenta().bold()));
         log::warn!($($t)*);
     }};
 }
@@ -29,7 +94,7 @@ macro_rules! infoln {
     ($($t:tt)*) => {{
         extern crate colored;
         use colored::*;
-        println!("\u{2139}\u{fe0f}  {}", format!($($t)*).cyan().bold());
+        $crate::common::print_message(format!("\u{2139}\u{fe0f}  {}", format!($($t)*).cyan().bold()));
         log::info!($($t)*);
     }};
 }
@@ -39,7 +104,7 @@ macro_rules! boomln {
     ($($t:tt)*) => {{
         extern crate colored;
         use colored::*;
-        println!("\u{1f4a5} {}", format!($($t)*).red().bold());
+        $crate::common::print_message(format!("\u{1f4a5} {}", format!($($t)*).red().bold()));
         log::error!($($t)*);
     }};
 }
@@ -49,7 +114,7 @@ macro_rules! errorln {
     ($($t:tt)*) => {{
         extern crate colored;
         use colored::*;
-        println!("\u{274c}  {}", format!($($t)*).red().bold());
+        $crate::common::print_message(format!("\u{274c}  {}", format!($($t)*).red().bold()));
         log::error!($($t)*);
     }};
 }
@@ -59,7 +124,7 @@ macro_rules! successln {
     ($($t:tt)*) => {{
         extern crate colored;
         use colored::*;
-        println!("\u{2705} {}", format!($($t)*).green().bold());
+        $crate::common::print_message(format!("\u{2705} {}", format!($($t)*).green().bold()));
         log::info!($($t)*);
     }};
 }
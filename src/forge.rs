@@ -0,0 +1,171 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::ws::config::{WSForgeKind, WSGitHubConfig, WSWorkflowConfig};
+
+use self::errors::{ForgeError, ForgeResult};
+
+pub mod errors;
+mod gitea;
+mod github;
+mod gitlab;
+pub mod graphql;
+mod retry;
+
+pub use github::GitHubClient;
+
+/// A pull request to be opened, from branch 'head' onto branch 'base'.
+///
+pub struct PullRequest {
+    pub title: String,
+    pub body: String,
+    pub head: String,
+    pub base: String,
+}
+
+/// A pull request, once opened.
+///
+pub struct PullRequestInfo {
+    pub number: u64,
+    pub url: String,
+}
+
+/// The state of a CI pipeline run.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PipelineStatus {
+    Queued,
+    InProgress,
+    Completed,
+    Unknown,
+}
+
+/// A single CI pipeline run for a tag (e.g., a GitHub Actions workflow run).
+///
+#[derive(Clone)]
+pub struct PipelineRun {
+    pub id: u64,
+    /// name of the pipeline, or workflow, being run
+    pub name: String,
+    /// tag or branch the pipeline ran for
+    pub tag: String,
+    pub status: PipelineStatus,
+    pub success: bool,
+    /// attempt number of the run, if the forge tracks re-runs
+    pub attempt: Option<u64>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub url: Option<String>,
+}
+
+/// A single job of a CI pipeline run.
+///
+pub struct PipelineJob {
+    pub id: u64,
+    pub name: String,
+    pub status: String,
+    pub conclusion: Option<String>,
+    pub url: Option<String>,
+    pub steps: Vec<PipelineStep>,
+}
+
+/// A single step of a CI pipeline job.
+///
+pub struct PipelineStep {
+    pub name: String,
+    pub conclusion: Option<String>,
+}
+
+/// A release for a tag, as it exists on the forge.
+///
+pub struct ForgeRelease {
+    /// forge-specific identifier, used to update the release
+    pub id: String,
+    pub name: Option<String>,
+    pub body: Option<String>,
+    /// whether the release is a pre-release, on forges supporting those
+    pub prerelease: Option<bool>,
+    pub url: String,
+}
+
+/// A release to be created, or updated, for tag 'tag'.
+///
+pub struct ReleaseRequest {
+    pub tag: String,
+    pub name: String,
+    pub body: String,
+    pub prerelease: bool,
+}
+
+impl ForgeRelease {
+    /// Whether this release already matches 'req', and needs no update.
+    ///
+    pub fn matches(self: &Self, req: &ReleaseRequest) -> bool {
+        self.name.as_ref() == Some(&req.name)
+            && self.body.as_ref() == Some(&req.body)
+            && self.prerelease.is_none_or(|p| p == req.prerelease)
+    }
+}
+
+/// A forge hosting a repository, on which pull requests are opened, CI
+/// pipelines are run for release tags, and releases are published.
+///
+#[async_trait::async_trait]
+pub trait Forge: Send + Sync {
+    /// Open pull request 'pr'.
+    async fn create_pull_request(self: &Self, pr: &PullRequest) -> ForgeResult<PullRequestInfo>;
+
+    /// Obtain the latest run of each of 'workflows' for tag 'tag', ignoring
+    /// cancelled runs. Workflows without runs are left out.
+    async fn get_pipeline_runs(
+        self: &Self,
+        tag: &String,
+        workflows: &Vec<WSWorkflowConfig>,
+    ) -> ForgeResult<Vec<PipelineRun>>;
+
+    /// Obtain the jobs of pipeline run 'run_id'. Not all forges expose these.
+    async fn get_pipeline_jobs(self: &Self, _run_id: u64) -> ForgeResult<Vec<PipelineJob>> {
+        Err(ForgeError::UnsupportedError)
+    }
+
+    /// Obtain the full log of pipeline job 'job_id'. Not all forges expose
+    /// these.
+    async fn get_job_log(self: &Self, _job_id: u64) -> ForgeResult<String> {
+        Err(ForgeError::UnsupportedError)
+    }
+
+    /// Obtain the release for tag 'tag', if it exists.
+    async fn get_release(self: &Self, tag: &String) -> ForgeResult<Option<ForgeRelease>>;
+
+    /// Create release 'req', returning its URL.
+    async fn create_release(self: &Self, req: &ReleaseRequest) -> ForgeResult<String>;
+
+    /// Update the existing 'release' to match 'req', returning its URL.
+    async fn update_release(
+        self: &Self,
+        release: &ForgeRelease,
+        req: &ReleaseRequest,
+    ) -> ForgeResult<String>;
+}
+
+/// Obtain the forge described by 'cfg', authenticating with 'token'.
+///
+pub fn from_config(cfg: &WSGitHubConfig, token: &String) -> Box<dyn Forge> {
+    match cfg.kind {
+        WSForgeKind::GitHub => Box::new(github::GitHubForge::new(&cfg, &token)),
+        WSForgeKind::GitLab => Box::new(gitlab::GitLabForge::new(&cfg, &token)),
+        WSForgeKind::Gitea => Box::new(gitea::GiteaForge::new(&cfg, &token)),
+    }
+}
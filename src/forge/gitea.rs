@@ -0,0 +1,76 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::ws::config::{WSGitHubConfig, WSWorkflowConfig};
+
+use super::{
+    errors::ForgeResult, github::GitHubForge, Forge, ForgeRelease, PipelineRun, PullRequest,
+    PullRequestInfo, ReleaseRequest,
+};
+
+const GITEA_API_BASE: &str = "https://gitea.com/api/v1";
+
+/// A repository on Gitea, or Forgejo. Its API for pull requests, releases,
+/// and Actions workflow runs is compatible with GitHub's, albeit under a
+/// different base URL.
+///
+pub struct GiteaForge {
+    github: GitHubForge,
+}
+
+impl GiteaForge {
+    pub fn new(cfg: &WSGitHubConfig, token: &String) -> GiteaForge {
+        let base = match &cfg.api_base {
+            Some(v) => v.trim_end_matches('/'),
+            None => GITEA_API_BASE,
+        };
+        GiteaForge {
+            github: GitHubForge::with_url(
+                format!("{}/repos/{}/{}", base, cfg.org, cfg.repo),
+                token.clone(),
+            ),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Forge for GiteaForge {
+    async fn create_pull_request(self: &Self, pr: &PullRequest) -> ForgeResult<PullRequestInfo> {
+        self.github.create_pull_request(&pr).await
+    }
+
+    async fn get_pipeline_runs(
+        self: &Self,
+        tag: &String,
+        workflows: &Vec<WSWorkflowConfig>,
+    ) -> ForgeResult<Vec<PipelineRun>> {
+        self.github.get_pipeline_runs(&tag, &workflows).await
+    }
+
+    async fn get_release(self: &Self, tag: &String) -> ForgeResult<Option<ForgeRelease>> {
+        self.github.get_release(&tag).await
+    }
+
+    async fn create_release(self: &Self, req: &ReleaseRequest) -> ForgeResult<String> {
+        self.github.create_release(&req).await
+    }
+
+    async fn update_release(
+        self: &Self,
+        release: &ForgeRelease,
+        req: &ReleaseRequest,
+    ) -> ForgeResult<String> {
+        self.github.update_release(&release, &req).await
+    }
+}
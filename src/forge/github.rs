@@ -0,0 +1,406 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::ws::config::{WSGitHubConfig, WSWorkflowConfig};
+
+mod client;
+
+pub use client::GitHubClient;
+
+use super::{
+    errors::{ForgeError, ForgeResult},
+    Forge, ForgeRelease, PipelineJob, PipelineRun, PipelineStatus, PipelineStep, PullRequest,
+    PullRequestInfo, ReleaseRequest,
+};
+
+// ----
+// raw requests to, and responses from, GitHub
+// ----
+
+#[derive(serde::Serialize)]
+struct GitHubPullRequestRequest<'a> {
+    title: &'a String,
+    head: &'a String,
+    base: &'a String,
+    body: &'a String,
+}
+
+#[derive(serde::Deserialize)]
+struct GitHubPullRequestResponse {
+    html_url: String,
+    number: u64,
+}
+
+#[derive(serde::Deserialize)]
+struct GitHubRunResult {
+    #[allow(dead_code)]
+    total_count: u64,
+    workflow_runs: Vec<GitHubWorkflowResult>,
+}
+
+/// A workflow run, as obtained from GitHub. Fields not provided by all
+/// GitHub compatible forges (e.g., Gitea) are optional.
+///
+#[derive(serde::Deserialize)]
+struct GitHubWorkflowResult {
+    id: u64,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    workflow_id: Option<u64>,
+    head_branch: Option<String>,
+
+    status: Option<String>,
+    conclusion: Option<String>,
+
+    #[serde(default)]
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default, alias = "completed_at")]
+    updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default, alias = "started_at")]
+    run_started_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    run_attempt: Option<u64>,
+    html_url: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct GitHubJobsResult {
+    #[allow(dead_code)]
+    total_count: u64,
+    jobs: Vec<GitHubJobResult>,
+}
+
+#[derive(serde::Deserialize)]
+struct GitHubJobResult {
+    id: u64,
+    name: String,
+    status: String,
+    conclusion: Option<String>,
+    html_url: Option<String>,
+    #[serde(default)]
+    steps: Vec<GitHubStepResult>,
+}
+
+#[derive(serde::Deserialize)]
+struct GitHubStepResult {
+    name: String,
+    #[allow(dead_code)]
+    number: u64,
+    #[allow(dead_code)]
+    status: String,
+    conclusion: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+struct GitHubReleaseRequest<'a> {
+    tag_name: &'a String,
+    name: &'a String,
+    body: &'a String,
+    prerelease: bool,
+}
+
+#[derive(serde::Deserialize)]
+struct GitHubReleaseResponse {
+    id: u64,
+    html_url: String,
+    name: Option<String>,
+    body: Option<String>,
+    prerelease: bool,
+}
+
+// ----
+// end of raw requests to, and responses from, GitHub
+// ----
+
+/// A repository on GitHub, or on a forge providing a GitHub compatible API.
+///
+pub struct GitHubForge {
+    /// the repository's API URL (e.g., 'https://api.github.com/repos/org/repo')
+    url: String,
+    client: GitHubClient,
+}
+
+impl GitHubForge {
+    pub fn new(cfg: &WSGitHubConfig, token: &String) -> GitHubForge {
+        GitHubForge::with_url(
+            cfg.get_api_url("").trim_end_matches('/').into(),
+            token.clone(),
+        )
+    }
+
+    pub fn with_url(url: String, token: String) -> GitHubForge {
+        GitHubForge {
+            url,
+            client: GitHubClient::new(&token),
+        }
+    }
+
+    /// Send a request to 'path' on the repository's API, with 'body' as JSON
+    /// if provided, and parse its response. Returns 'None' if not found.
+    ///
+    async fn request<B: serde::Serialize, R: serde::de::DeserializeOwned>(
+        self: &Self,
+        method: reqwest::Method,
+        path: &str,
+        query: &[(&str, &String)],
+        body: Option<&B>,
+    ) -> ForgeResult<Option<R>> {
+        let api_url = format!("{}/{}", self.url, path);
+        let mut request = self.client.request(method, &api_url).query(query);
+        if let Some(b) = body {
+            request = request.json(b);
+        }
+
+        let response = self.client.send(request).await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        } else if !response.status().is_success() {
+            log::error!("Unable to request '{}': {}", api_url, response.status());
+            return Err(ForgeError::ResponseError);
+        }
+
+        match response.json::<R>().await {
+            Ok(r) => Ok(Some(r)),
+            Err(err) => {
+                log::error!("Unable to parse response from '{}': {}", api_url, err);
+                Err(ForgeError::ResponseError)
+            }
+        }
+    }
+}
+
+/// Translate a raw GitHub workflow run, for tag 'tag', into a pipeline run.
+///
+fn to_pipeline_run(tag: &String, res: &GitHubWorkflowResult) -> PipelineRun {
+    let status = match &res.status {
+        None => PipelineStatus::Unknown,
+        Some(v) => match v.as_str() {
+            "queued" | "waiting" | "pending" | "requested" => PipelineStatus::Queued,
+            "in_progress" => PipelineStatus::InProgress,
+            "completed" => PipelineStatus::Completed,
+            _ => {
+                log::trace!("unknown workflow run status: {}", v);
+                PipelineStatus::Unknown
+            }
+        },
+    };
+
+    let started_at = res
+        .run_started_at
+        .or(res.created_at)
+        .unwrap_or_else(chrono::Utc::now);
+    let created_at = res.created_at.unwrap_or(started_at);
+
+    PipelineRun {
+        id: res.id,
+        name: res.name.clone().unwrap_or_default(),
+        tag: res.head_branch.clone().unwrap_or(tag.clone()),
+        status,
+        success: res.conclusion.as_deref() == Some("success"),
+        attempt: res.run_attempt,
+        created_at,
+        updated_at: res.updated_at.unwrap_or(created_at),
+        started_at,
+        url: res.html_url.clone(),
+    }
+}
+
+fn to_forge_release(res: GitHubReleaseResponse) -> ForgeRelease {
+    ForgeRelease {
+        id: res.id.to_string(),
+        name: res.name,
+        body: res.body,
+        prerelease: Some(res.prerelease),
+        url: res.html_url,
+    }
+}
+
+#[async_trait::async_trait]
+impl Forge for GitHubForge {
+    async fn create_pull_request(self: &Self, pr: &PullRequest) -> ForgeResult<PullRequestInfo> {
+        let req = GitHubPullRequestRequest {
+            title: &pr.title,
+            head: &pr.head,
+            base: &pr.base,
+            body: &pr.body,
+        };
+        match self
+            .request::<_, GitHubPullRequestResponse>(
+                reqwest::Method::POST,
+                "pulls",
+                &[],
+                Some(&req),
+            )
+            .await?
+        {
+            Some(r) => Ok(PullRequestInfo {
+                number: r.number,
+                url: r.html_url,
+            }),
+            None => Err(ForgeError::NotFoundError),
+        }
+    }
+
+    async fn get_pipeline_runs(
+        self: &Self,
+        tag: &String,
+        workflows: &Vec<WSWorkflowConfig>,
+    ) -> ForgeResult<Vec<PipelineRun>> {
+        // busy tags may have more runs than fit in a page
+        let api_url = format!("{}/actions/runs", self.url);
+        let runs = match self
+            .client
+            .get_all_pages::<GitHubRunResult>(&api_url, &[("branch", tag)])
+            .await?
+        {
+            Some(pages) => pages
+                .into_iter()
+                .flat_map(|p| p.workflow_runs)
+                .collect::<Vec<GitHubWorkflowResult>>(),
+            None => return Err(ForgeError::NotFoundError),
+        };
+
+        let mut latest = vec![];
+        for workflow in workflows {
+            let run = runs
+                .iter()
+                .filter(|e| {
+                    workflow.matches(e.name.as_ref(), e.path.as_ref(), e.workflow_id)
+                        && e.conclusion.as_deref() != Some("cancelled")
+                })
+                .max_by(|a, b| a.created_at.cmp(&b.created_at));
+
+            match run {
+                None => log::debug!("No run found for workflow {} on '{}'", workflow, tag),
+                Some(v) => latest.push(to_pipeline_run(&tag, &v)),
+            };
+        }
+        Ok(latest)
+    }
+
+    async fn get_pipeline_jobs(self: &Self, run_id: u64) -> ForgeResult<Vec<PipelineJob>> {
+        let api_url = format!("{}/actions/runs/{}/jobs", self.url, run_id);
+        let jobs = match self
+            .client
+            .get_all_pages::<GitHubJobsResult>(&api_url, &[])
+            .await?
+        {
+            Some(pages) => pages.into_iter().flat_map(|p| p.jobs),
+            None => return Err(ForgeError::NotFoundError),
+        };
+
+        Ok(jobs
+            .map(|job| PipelineJob {
+                id: job.id,
+                name: job.name,
+                status: job.status,
+                conclusion: job.conclusion,
+                url: job.html_url,
+                steps: job
+                    .steps
+                    .into_iter()
+                    .map(|step| PipelineStep {
+                        name: step.name,
+                        conclusion: step.conclusion,
+                    })
+                    .collect(),
+            })
+            .collect())
+    }
+
+    async fn get_job_log(self: &Self, job_id: u64) -> ForgeResult<String> {
+        // GitHub redirects to the log's download location, which is followed
+        // by the client.
+        let api_url = format!("{}/actions/jobs/{}/logs", self.url, job_id);
+        let response = self
+            .client
+            .send(self.client.request(reqwest::Method::GET, &api_url))
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(ForgeError::NotFoundError);
+        } else if !response.status().is_success() {
+            log::error!("Unable to request '{}': {}", api_url, response.status());
+            return Err(ForgeError::ResponseError);
+        }
+
+        match response.text().await {
+            Ok(log) => Ok(log),
+            Err(err) => {
+                log::error!("Unable to read response from '{}': {}", api_url, err);
+                Err(ForgeError::ResponseError)
+            }
+        }
+    }
+
+    async fn get_release(self: &Self, tag: &String) -> ForgeResult<Option<ForgeRelease>> {
+        let res = self
+            .request::<(), GitHubReleaseResponse>(
+                reqwest::Method::GET,
+                &format!("releases/tags/{}", tag),
+                &[],
+                None,
+            )
+            .await?;
+        Ok(res.map(to_forge_release))
+    }
+
+    async fn create_release(self: &Self, req: &ReleaseRequest) -> ForgeResult<String> {
+        let body = GitHubReleaseRequest {
+            tag_name: &req.tag,
+            name: &req.name,
+            body: &req.body,
+            prerelease: req.prerelease,
+        };
+        match self
+            .request::<_, GitHubReleaseResponse>(
+                reqwest::Method::POST,
+                "releases",
+                &[],
+                Some(&body),
+            )
+            .await?
+        {
+            Some(r) => Ok(r.html_url),
+            None => Err(ForgeError::NotFoundError),
+        }
+    }
+
+    async fn update_release(
+        self: &Self,
+        release: &ForgeRelease,
+        req: &ReleaseRequest,
+    ) -> ForgeResult<String> {
+        let body = GitHubReleaseRequest {
+            tag_name: &req.tag,
+            name: &req.name,
+            body: &req.body,
+            prerelease: req.prerelease,
+        };
+        match self
+            .request::<_, GitHubReleaseResponse>(
+                reqwest::Method::PATCH,
+                &format!("releases/{}", release.id),
+                &[],
+                Some(&body),
+            )
+            .await?
+        {
+            Some(r) => Ok(r.html_url),
+            None => Err(ForgeError::NotFoundError),
+        }
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::forge::{
+    errors::{ForgeError, ForgeResult},
+    retry,
+};
+
+/// Results requested per page, the maximum allowed by GitHub.
+const PER_PAGE: &str = "100";
+
+/// HTTP client for GitHub's API, shared by everything talking to GitHub.
+/// Requests are authenticated, retried with backoff on server errors and rate
+/// limits, and the remaining API quota is reported in debug output.
+///
+#[derive(Clone)]
+pub struct GitHubClient {
+    client: reqwest::Client,
+    token: String,
+}
+
+impl GitHubClient {
+    pub fn new(token: &String) -> GitHubClient {
+        GitHubClient {
+            client: reqwest::Client::new(),
+            token: token.clone(),
+        }
+    }
+
+    /// Build a request to 'url' on GitHub's API.
+    ///
+    pub fn request(self: &Self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, url)
+            .bearer_auth(&self.token)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .header("User-Agent", "s3gw-arc-rs")
+    }
+
+    /// Send 'request', retrying it as per the forge retry policy (see
+    /// 'retry::send()').
+    ///
+    pub async fn send(
+        self: &Self,
+        request: reqwest::RequestBuilder,
+    ) -> ForgeResult<reqwest::Response> {
+        retry::send(&self.client, request, false).await
+    }
+
+    /// Send 'request' like 'send()', but as a query not changing anything on
+    /// GitHub (e.g., a GraphQL query), which may thus be resent whatever its
+    /// method.
+    ///
+    pub async fn send_query(
+        self: &Self,
+        request: reqwest::RequestBuilder,
+    ) -> ForgeResult<reqwest::Response> {
+        retry::send(&self.client, request, true).await
+    }
+
+    /// Obtain every page of results for a GET request to 'url', following the
+    /// 'Link' headers provided by GitHub. Returns 'None' if not found.
+    ///
+    pub async fn get_all_pages<R: serde::de::DeserializeOwned>(
+        self: &Self,
+        url: &str,
+        query: &[(&str, &String)],
+    ) -> ForgeResult<Option<Vec<R>>> {
+        let mut request = self
+            .request(reqwest::Method::GET, url)
+            .query(query)
+            .query(&[("per_page", PER_PAGE)]);
+
+        let mut pages = vec![];
+        loop {
+            let response = self.send(request).await?;
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                return Ok(None);
+            } else if !response.status().is_success() {
+                log::error!("Unable to request '{}': {}", url, response.status());
+                return Err(ForgeError::ResponseError);
+            }
+
+            let next = crate::common::get_next_page(&response);
+            match response.json::<R>().await {
+                Ok(page) => pages.push(page),
+                Err(err) => {
+                    log::error!("Unable to parse response from '{}': {}", url, err);
+                    return Err(ForgeError::ResponseError);
+                }
+            };
+
+            match next {
+                Some(v) => request = self.request(reqwest::Method::GET, v.as_str()),
+                None => break,
+            };
+        }
+        Ok(Some(pages))
+    }
+}
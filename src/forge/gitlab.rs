@@ -0,0 +1,375 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::ws::config::{WSGitHubConfig, WSWorkflowConfig};
+
+use super::{
+    errors::{ForgeError, ForgeResult},
+    retry, Forge, ForgeRelease, PipelineRun, PipelineStatus, PullRequest, PullRequestInfo,
+    ReleaseRequest,
+};
+
+const GITLAB_API_BASE: &str = "https://gitlab.com/api/v4";
+/// Results requested per page, the maximum allowed by GitLab.
+const PER_PAGE: &str = "100";
+
+// ----
+// raw requests to, and responses from, GitLab
+// ----
+
+#[derive(serde::Serialize)]
+struct GitLabMergeRequestRequest<'a> {
+    source_branch: &'a String,
+    target_branch: &'a String,
+    title: &'a String,
+    description: &'a String,
+}
+
+#[derive(serde::Deserialize)]
+struct GitLabMergeRequestResponse {
+    iid: u64,
+    web_url: String,
+}
+
+#[derive(serde::Deserialize)]
+struct GitLabPipelineResult {
+    id: u64,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(rename = "ref")]
+    refname: String,
+    status: String,
+    web_url: Option<String>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    started_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(serde::Serialize)]
+struct GitLabReleaseRequest<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tag_name: Option<&'a String>,
+    name: &'a String,
+    description: &'a String,
+}
+
+#[derive(serde::Deserialize)]
+struct GitLabReleaseLinks {
+    #[serde(rename = "self")]
+    url: String,
+}
+
+#[derive(serde::Deserialize)]
+struct GitLabReleaseResponse {
+    tag_name: String,
+    name: Option<String>,
+    description: Option<String>,
+    #[serde(rename = "_links")]
+    links: GitLabReleaseLinks,
+}
+
+// ----
+// end of raw requests to, and responses from, GitLab
+// ----
+
+/// A project on GitLab. Merge requests stand in for pull requests, and
+/// pipelines for workflow runs. Pipelines can only be matched by name, and
+/// releases have no notion of pre-releases.
+///
+pub struct GitLabForge {
+    /// the project's API URL (e.g., 'https://gitlab.com/api/v4/projects/org%2Frepo')
+    url: String,
+    token: String,
+    client: reqwest::Client,
+}
+
+impl GitLabForge {
+    pub fn new(cfg: &WSGitHubConfig, token: &String) -> GitLabForge {
+        let base = match &cfg.api_base {
+            Some(v) => v.trim_end_matches('/'),
+            None => GITLAB_API_BASE,
+        };
+        GitLabForge {
+            url: format!(
+                "{}/projects/{}%2F{}",
+                base,
+                cfg.org.replace('/', "%2F"),
+                cfg.repo
+            ),
+            token: token.clone(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Build a request to 'url' on GitLab's API.
+    ///
+    fn build_request(self: &Self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, url)
+            .bearer_auth(&self.token)
+            .header("User-Agent", "s3gw-arc-rs")
+    }
+
+    /// Send a request to 'path' on the project's API, with 'body' as JSON if
+    /// provided, and parse its response. Returns 'None' if not found.
+    ///
+    async fn request<B: serde::Serialize, R: serde::de::DeserializeOwned>(
+        self: &Self,
+        method: reqwest::Method,
+        path: &str,
+        query: &[(&str, &String)],
+        body: Option<&B>,
+    ) -> ForgeResult<Option<R>> {
+        let api_url = format!("{}/{}", self.url, path);
+        let mut request = self.build_request(method, &api_url).query(query);
+        if let Some(b) = body {
+            request = request.json(b);
+        }
+
+        let response = retry::send(&self.client, request, false).await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        } else if !response.status().is_success() {
+            log::error!("Unable to request '{}': {}", api_url, response.status());
+            return Err(ForgeError::ResponseError);
+        }
+
+        match response.json::<R>().await {
+            Ok(r) => Ok(Some(r)),
+            Err(err) => {
+                log::error!("Unable to parse response from '{}': {}", api_url, err);
+                Err(ForgeError::ResponseError)
+            }
+        }
+    }
+
+    /// Obtain every page of results for a GET request to 'path' on the
+    /// project's API, following the pagination headers provided by GitLab.
+    /// Returns 'None' if not found.
+    ///
+    async fn get_all_pages<R: serde::de::DeserializeOwned>(
+        self: &Self,
+        path: &str,
+        query: &[(&str, &String)],
+    ) -> ForgeResult<Option<Vec<R>>> {
+        let api_url = format!("{}/{}", self.url, path);
+        let mut request = self
+            .build_request(reqwest::Method::GET, &api_url)
+            .query(query)
+            .query(&[("per_page", PER_PAGE)]);
+
+        let mut results = vec![];
+        loop {
+            let response = retry::send(&self.client, request, false).await?;
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                return Ok(None);
+            } else if !response.status().is_success() {
+                log::error!("Unable to request '{}': {}", api_url, response.status());
+                return Err(ForgeError::ResponseError);
+            }
+
+            let next = get_next_page(&response);
+            match response.json::<Vec<R>>().await {
+                Ok(page) => results.extend(page),
+                Err(err) => {
+                    log::error!("Unable to parse response from '{}': {}", api_url, err);
+                    return Err(ForgeError::ResponseError);
+                }
+            };
+
+            match next {
+                Some(v) => request = self.build_request(reqwest::Method::GET, v.as_str()),
+                None => break,
+            };
+        }
+        Ok(Some(results))
+    }
+}
+
+/// Obtain the next page's URL from 'response', from its 'Link' header, or else
+/// from its 'X-Next-Page' header, which GitLab leaves empty on the last page.
+///
+fn get_next_page(response: &reqwest::Response) -> Option<reqwest::Url> {
+    if let Some(v) = crate::common::get_next_page(&response) {
+        return Some(v);
+    }
+    let page = response.headers().get("x-next-page")?.to_str().ok()?;
+    if page.is_empty() {
+        return None;
+    }
+
+    let mut url = response.url().clone();
+    let query: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(k, _)| k != "page")
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(query)
+        .append_pair("page", page);
+    Some(url)
+}
+
+/// Translate a raw GitLab pipeline into a pipeline run. Pipelines waiting on
+/// a manual action are considered completed, without success.
+///
+fn to_pipeline_run(res: &GitLabPipelineResult) -> PipelineRun {
+    let status = match res.status.as_str() {
+        "created" | "waiting_for_resource" | "preparing" | "pending" | "scheduled" => {
+            PipelineStatus::Queued
+        }
+        "running" => PipelineStatus::InProgress,
+        "success" | "failed" | "canceled" | "skipped" | "manual" => PipelineStatus::Completed,
+        _ => {
+            log::trace!("unknown pipeline status: {}", res.status);
+            PipelineStatus::Unknown
+        }
+    };
+
+    PipelineRun {
+        id: res.id,
+        name: res.name.clone().unwrap_or_default(),
+        tag: res.refname.clone(),
+        status,
+        success: res.status == "success",
+        attempt: None,
+        created_at: res.created_at,
+        updated_at: res.updated_at,
+        started_at: res.started_at.unwrap_or(res.created_at),
+        url: res.web_url.clone(),
+    }
+}
+
+fn to_forge_release(res: GitLabReleaseResponse) -> ForgeRelease {
+    ForgeRelease {
+        id: res.tag_name,
+        name: res.name,
+        body: res.description,
+        prerelease: None,
+        url: res.links.url,
+    }
+}
+
+#[async_trait::async_trait]
+impl Forge for GitLabForge {
+    async fn create_pull_request(self: &Self, pr: &PullRequest) -> ForgeResult<PullRequestInfo> {
+        let req = GitLabMergeRequestRequest {
+            source_branch: &pr.head,
+            target_branch: &pr.base,
+            title: &pr.title,
+            description: &pr.body,
+        };
+        match self
+            .request::<_, GitLabMergeRequestResponse>(
+                reqwest::Method::POST,
+                "merge_requests",
+                &[],
+                Some(&req),
+            )
+            .await?
+        {
+            Some(r) => Ok(PullRequestInfo {
+                number: r.iid,
+                url: r.web_url,
+            }),
+            None => Err(ForgeError::NotFoundError),
+        }
+    }
+
+    async fn get_pipeline_runs(
+        self: &Self,
+        tag: &String,
+        workflows: &Vec<WSWorkflowConfig>,
+    ) -> ForgeResult<Vec<PipelineRun>> {
+        let pipelines = match self
+            .get_all_pages::<GitLabPipelineResult>("pipelines", &[("ref", tag)])
+            .await?
+        {
+            Some(r) => r,
+            None => return Err(ForgeError::NotFoundError),
+        };
+
+        let mut latest = vec![];
+        for workflow in workflows {
+            let pipeline = pipelines
+                .iter()
+                .filter(|e| workflow.matches(e.name.as_ref(), None, None) && e.status != "canceled")
+                .max_by(|a, b| a.created_at.cmp(&b.created_at));
+
+            match pipeline {
+                None => log::debug!("No pipeline found for {} on '{}'", workflow, tag),
+                Some(v) => latest.push(to_pipeline_run(&v)),
+            };
+        }
+        Ok(latest)
+    }
+
+    async fn get_release(self: &Self, tag: &String) -> ForgeResult<Option<ForgeRelease>> {
+        let res = self
+            .request::<(), GitLabReleaseResponse>(
+                reqwest::Method::GET,
+                &format!("releases/{}", tag),
+                &[],
+                None,
+            )
+            .await?;
+        Ok(res.map(to_forge_release))
+    }
+
+    async fn create_release(self: &Self, req: &ReleaseRequest) -> ForgeResult<String> {
+        let body = GitLabReleaseRequest {
+            tag_name: Some(&req.tag),
+            name: &req.name,
+            description: &req.body,
+        };
+        match self
+            .request::<_, GitLabReleaseResponse>(
+                reqwest::Method::POST,
+                "releases",
+                &[],
+                Some(&body),
+            )
+            .await?
+        {
+            Some(r) => Ok(r.links.url),
+            None => Err(ForgeError::NotFoundError),
+        }
+    }
+
+    async fn update_release(
+        self: &Self,
+        release: &ForgeRelease,
+        req: &ReleaseRequest,
+    ) -> ForgeResult<String> {
+        let body = GitLabReleaseRequest {
+            tag_name: None,
+            name: &req.name,
+            description: &req.body,
+        };
+        match self
+            .request::<_, GitLabReleaseResponse>(
+                reqwest::Method::PUT,
+                &format!("releases/{}", release.id),
+                &[],
+                Some(&body),
+            )
+            .await?
+        {
+            Some(r) => Ok(r.links.url),
+            None => Err(ForgeError::NotFoundError),
+        }
+    }
+}
@@ -0,0 +1,311 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use graphql_client::GraphQLQuery;
+
+use crate::ws::config::{WSGitHubConfig, WSWorkflowConfig};
+
+use super::{
+    errors::{ForgeError, ForgeResult},
+    GitHubClient, PipelineRun, PipelineStatus,
+};
+
+// ----
+// GitHub GraphQL queries, generated from 'src/forge/graphql/'
+// ----
+
+type DateTime = chrono::DateTime<chrono::Utc>;
+#[allow(clippy::upper_case_acronyms)]
+type URI = String;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/forge/graphql/schema.graphql",
+    query_path = "src/forge/graphql/release_status.graphql",
+    response_derives = "Debug"
+)]
+struct ReleaseStatus;
+
+use release_status::{
+    CheckConclusionState, CheckStatusState, CommitChecks, ReleaseStatusRepositoryRefsNodesTarget,
+    ReleaseStatusRepositoryRefsNodesTargetOnTagTarget,
+};
+
+// ----
+// end of GitHub GraphQL queries
+// ----
+
+/// An open pull request on a GitHub repository.
+///
+pub struct OpenPullRequest {
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+    pub head: String,
+    pub base: String,
+}
+
+/// A workflow run for a tag, along with what's needed to match it against
+/// the tracked workflows.
+///
+struct TagRun {
+    name: String,
+    path: Option<String>,
+    workflow_id: Option<u64>,
+    cancelled: bool,
+    run: PipelineRun,
+}
+
+/// Release status of a GitHub repository, as obtained in a few round-trips
+/// from GitHub's GraphQL API: workflow runs for each requested tag, and the
+/// repository's open pull requests.
+///
+pub struct BatchedReleaseStatus {
+    runs: HashMap<String, Vec<TagRun>>,
+    pub pull_requests: Vec<OpenPullRequest>,
+}
+
+impl BatchedReleaseStatus {
+    /// Obtain the latest run of each of 'workflows' for 'tag', ignoring
+    /// cancelled runs. Same semantics as 'Forge::get_pipeline_runs()'.
+    ///
+    pub fn get_pipeline_runs(
+        self: &Self,
+        tag: &String,
+        workflows: &Vec<WSWorkflowConfig>,
+    ) -> Vec<PipelineRun> {
+        let runs = match self.runs.get(tag) {
+            Some(v) => v,
+            None => return vec![],
+        };
+
+        let mut latest = vec![];
+        for workflow in workflows {
+            let run = runs
+                .iter()
+                .filter(|e| {
+                    workflow.matches(Some(&e.name), e.path.as_ref(), e.workflow_id) && !e.cancelled
+                })
+                .max_by(|a, b| a.run.created_at.cmp(&b.run.created_at));
+
+            match run {
+                None => log::debug!("No run found for workflow {} on '{}'", workflow, tag),
+                Some(v) => latest.push(v.run.clone()),
+            };
+        }
+        latest
+    }
+}
+
+/// Obtain the GraphQL API endpoint for the GitHub instance 'cfg' is hosted on.
+///
+fn get_graphql_url(cfg: &WSGitHubConfig) -> String {
+    let base = match &cfg.api_base {
+        Some(v) => v.trim_end_matches('/'),
+        None => crate::ws::config::GITHUB_API_BASE,
+    };
+    // GitHub Enterprise serves REST at '/api/v3' and GraphQL at '/api/graphql'
+    match base.strip_suffix("/api/v3") {
+        Some(host) => format!("{}/api/graphql", host),
+        None => format!("{}/graphql", base),
+    }
+}
+
+/// Obtain the longest prefix common to all 'tags', used to narrow down the
+/// tags requested from GitHub.
+///
+fn get_common_prefix(tags: &Vec<String>) -> String {
+    let mut prefix = match tags.first() {
+        Some(v) => v.clone(),
+        None => return String::new(),
+    };
+    for tag in tags {
+        while !tag.starts_with(&prefix) {
+            prefix.pop();
+        }
+    }
+    prefix
+}
+
+/// Obtain the release status for 'tags' on the GitHub repository configured
+/// by 'cfg', authenticated with 'token'. Tags, their workflow runs, and open
+/// pull requests are obtained in one request per 100 matching tags.
+///
+/// Note that GitHub's GraphQL API does not expose workflow run attempts, so
+/// the runs' attempts are left unknown.
+///
+pub async fn get_release_status(
+    cfg: &WSGitHubConfig,
+    token: &String,
+    tags: &Vec<String>,
+) -> ForgeResult<BatchedReleaseStatus> {
+    let mut status = BatchedReleaseStatus {
+        runs: HashMap::new(),
+        pull_requests: vec![],
+    };
+    if tags.is_empty() {
+        return Ok(status);
+    }
+
+    let url = get_graphql_url(&cfg);
+    let tag_query = get_common_prefix(&tags);
+    let mut after: Option<String> = None;
+    loop {
+        let body = ReleaseStatus::build_query(release_status::Variables {
+            owner: cfg.org.clone(),
+            name: cfg.repo.clone(),
+            tag_query: tag_query.clone(),
+            after: after.clone(),
+        });
+        let repository = match query::<release_status::ResponseData>(&url, &token, &body).await? {
+            release_status::ResponseData {
+                repository: Some(r),
+            } => r,
+            release_status::ResponseData { repository: None } => {
+                log::error!("Repository '{}/{}' not found", cfg.org, cfg.repo);
+                return Err(ForgeError::NotFoundError);
+            }
+        };
+
+        if after.is_none() {
+            status.pull_requests = repository
+                .pull_requests
+                .nodes
+                .unwrap_or_default()
+                .into_iter()
+                .flatten()
+                .map(|pr| OpenPullRequest {
+                    number: pr.number as u64,
+                    title: pr.title,
+                    url: pr.url,
+                    head: pr.head_ref_name,
+                    base: pr.base_ref_name,
+                })
+                .collect();
+        }
+
+        let refs = match repository.refs {
+            Some(r) => r,
+            None => break,
+        };
+        for tag_ref in refs.nodes.unwrap_or_default().into_iter().flatten() {
+            if !tags.contains(&tag_ref.name) {
+                continue;
+            }
+            let checks = match tag_ref.target {
+                Some(ReleaseStatusRepositoryRefsNodesTarget::Commit(c)) => c,
+                Some(ReleaseStatusRepositoryRefsNodesTarget::Tag(t)) => match t.target {
+                    ReleaseStatusRepositoryRefsNodesTargetOnTagTarget::Commit(c) => c,
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            let runs = get_tag_runs(&tag_ref.name, checks);
+            status.runs.insert(tag_ref.name, runs);
+        }
+
+        if !refs.page_info.has_next_page {
+            break;
+        }
+        after = refs.page_info.end_cursor;
+    }
+
+    Ok(status)
+}
+
+/// Send GraphQL query 'body' to 'url', and obtain its response data.
+///
+async fn query<R: serde::de::DeserializeOwned>(
+    url: &String,
+    token: &String,
+    body: &impl serde::Serialize,
+) -> ForgeResult<R> {
+    let client = GitHubClient::new(&token);
+    let response = client
+        .send_query(client.request(reqwest::Method::POST, &url).json(body))
+        .await?;
+    if !response.status().is_success() {
+        log::error!("Unable to query '{}': {}", url, response.status());
+        return Err(ForgeError::ResponseError);
+    }
+
+    let res = match response.json::<graphql_client::Response<R>>().await {
+        Ok(r) => r,
+        Err(err) => {
+            log::error!("Unable to parse response from '{}': {}", url, err);
+            return Err(ForgeError::ResponseError);
+        }
+    };
+    if let Some(errors) = &res.errors {
+        for err in errors {
+            log::error!("Error querying '{}': {}", url, err);
+        }
+    }
+    match res.data {
+        Some(data) => Ok(data),
+        None => Err(ForgeError::ResponseError),
+    }
+}
+
+/// Translate the check suites for 'tag''s commit into workflow runs. Check
+/// suites not triggered by a workflow are ignored.
+///
+fn get_tag_runs(tag: &String, checks: CommitChecks) -> Vec<TagRun> {
+    let suites = match checks.check_suites {
+        Some(s) => s.nodes.unwrap_or_default(),
+        None => return vec![],
+    };
+
+    let mut runs = vec![];
+    for suite in suites.into_iter().flatten() {
+        let wfrun = match suite.workflow_run {
+            Some(r) => r,
+            None => continue,
+        };
+        let status = match suite.status {
+            CheckStatusState::QUEUED
+            | CheckStatusState::PENDING
+            | CheckStatusState::REQUESTED
+            | CheckStatusState::WAITING => PipelineStatus::Queued,
+            CheckStatusState::IN_PROGRESS => PipelineStatus::InProgress,
+            CheckStatusState::COMPLETED => PipelineStatus::Completed,
+            CheckStatusState::Other(v) => {
+                log::trace!("unknown check suite status: {}", v);
+                PipelineStatus::Unknown
+            }
+        };
+
+        runs.push(TagRun {
+            name: wfrun.workflow.name.clone(),
+            path: wfrun.file.map(|f| f.path),
+            workflow_id: wfrun.workflow.database_id.map(|id| id as u64),
+            cancelled: matches!(suite.conclusion, Some(CheckConclusionState::CANCELLED)),
+            run: PipelineRun {
+                id: wfrun.database_id.unwrap_or_default() as u64,
+                name: wfrun.workflow.name,
+                tag: tag.clone(),
+                status,
+                success: matches!(suite.conclusion, Some(CheckConclusionState::SUCCESS)),
+                attempt: None,
+                created_at: wfrun.created_at,
+                updated_at: suite.updated_at,
+                started_at: wfrun.created_at,
+                url: Some(wfrun.url),
+            },
+        });
+    }
+    runs
+}
@@ -0,0 +1,216 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use super::errors::{ForgeError, ForgeResult};
+
+/// Maximum number of times a request is retried after failing transiently.
+const MAX_RETRIES: u32 = 4;
+/// Wait before the first retry, doubling on each following retry.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// Wait on a secondary rate limit not telling us how long to wait, as
+/// recommended by GitHub.
+const SECONDARY_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+/// Longest we are willing to wait for a rate limit to be lifted, before giving
+/// up on the request.
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(120);
+
+/// Send 'request' with 'client', retrying with exponential backoff should it
+/// fail on the network, on a server error, or on a rate limit we can wait out.
+/// Requests rejected by a rate limit are always retried, but failed requests
+/// that may not be safely resent (e.g., a 'POST' creating a pull request,
+/// which may still have been acted upon) are not, unless they are a query not
+/// changing anything on the forge (e.g., a GraphQL query). Returns the last
+/// response obtained, whatever its status.
+///
+pub async fn send(
+    client: &reqwest::Client,
+    request: reqwest::RequestBuilder,
+    is_query: bool,
+) -> ForgeResult<reqwest::Response> {
+    let request = match request.build() {
+        Ok(r) => r,
+        Err(err) => {
+            log::error!("Unable to build forge request: {}", err);
+            return Err(ForgeError::RequestError);
+        }
+    };
+    let url = request.url().to_string();
+    let resend = is_query || is_safe_to_resend(request.method());
+
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 0;
+    loop {
+        let req = match request.try_clone() {
+            Some(r) => r,
+            None => {
+                log::error!("Unable to send request to '{}' more than once", url);
+                return Err(ForgeError::RequestError);
+            }
+        };
+        let res = client.execute(req).await;
+        let wait = match &res {
+            Ok(response) => {
+                log_quota(&response);
+                get_retry_wait(&response, &backoff, resend)
+            }
+            Err(err) => {
+                log::debug!("Request to '{}' failed: {}", url, err);
+                match resend {
+                    true => Some(backoff),
+                    false => None,
+                }
+            }
+        };
+
+        match wait {
+            Some(w) if attempt < MAX_RETRIES => {
+                attempt += 1;
+                log::debug!(
+                    "Retrying '{}' in {}s (retry {}/{})",
+                    url,
+                    w.as_secs(),
+                    attempt,
+                    MAX_RETRIES
+                );
+                tokio::time::sleep(w).await;
+                backoff *= 2;
+            }
+            _ => {
+                return match res {
+                    Ok(response) => Ok(response),
+                    Err(err) => {
+                        log::error!("Unable to request '{}': {}", url, err);
+                        Err(ForgeError::RequestError)
+                    }
+                };
+            }
+        };
+    }
+}
+
+/// Obtain a header's value from 'response', if present and valid. Rate limit
+/// headers are prefixed with 'x-' by GitHub, but not by GitLab, so either is
+/// accepted.
+///
+fn get_header<'a>(response: &'a reqwest::Response, name: &str) -> Option<&'a str> {
+    let value = match response.headers().get(name) {
+        Some(v) => v,
+        None => response.headers().get(name.strip_prefix("x-")?)?,
+    };
+    value.to_str().ok()
+}
+
+/// Report the remaining API quota, as provided by the forge in 'response''s
+/// headers, in debug output.
+///
+fn log_quota(response: &reqwest::Response) {
+    let (remaining, limit) = match (
+        get_header(&response, "x-ratelimit-remaining"),
+        get_header(&response, "x-ratelimit-limit"),
+    ) {
+        (Some(r), Some(l)) => (r, l),
+        _ => return,
+    };
+    let resets = match get_rate_limit_reset(&response) {
+        Some(v) => format!(", resets at {}", v.format("%H:%M:%S")),
+        None => String::new(),
+    };
+    log::debug!(
+        "API quota ({}): {}/{} remaining{}",
+        get_header(&response, "x-ratelimit-resource").unwrap_or("core"),
+        remaining,
+        limit,
+        resets
+    );
+}
+
+/// Obtain when the API quota will reset, from 'response''s headers.
+///
+fn get_rate_limit_reset(response: &reqwest::Response) -> Option<chrono::DateTime<chrono::Utc>> {
+    let reset = get_header(&response, "x-ratelimit-reset")?
+        .parse::<i64>()
+        .ok()?;
+    chrono::TimeZone::timestamp_opt(&chrono::Utc, reset, 0).single()
+}
+
+/// Whether failed requests with 'method' can be safely sent again. Besides
+/// idempotent methods, this includes 'PATCH', for we only use it to set fields
+/// to given values.
+///
+fn is_safe_to_resend(method: &reqwest::Method) -> bool {
+    matches!(
+        *method,
+        reqwest::Method::GET | reqwest::Method::PATCH | reqwest::Method::PUT
+    )
+}
+
+/// Obtain how long to wait before retrying the request that obtained
+/// 'response', if it should be retried at all. Transient server errors are
+/// retried after 'backoff', provided 'resend' is set. Rate limits, having
+/// rejected the request, are retried once lifted, if soon enough.
+///
+fn get_retry_wait(
+    response: &reqwest::Response,
+    backoff: &Duration,
+    resend: bool,
+) -> Option<Duration> {
+    let status = response.status();
+    if matches!(status.as_u16(), 500 | 502 | 503 | 504) {
+        return match resend {
+            true => Some(*backoff),
+            false => None,
+        };
+    } else if status != reqwest::StatusCode::FORBIDDEN
+        && status != reqwest::StatusCode::TOO_MANY_REQUESTS
+    {
+        return None;
+    }
+
+    // secondary rate limits tell us how long to wait, primary rate limits when
+    // they reset.
+    let wait = if let Some(v) = get_header(&response, "retry-after") {
+        match v.parse::<u64>() {
+            Ok(secs) => Duration::from_secs(secs),
+            Err(_) => SECONDARY_RATE_LIMIT_WAIT,
+        }
+    } else if get_header(&response, "x-ratelimit-remaining") == Some("0") {
+        match get_rate_limit_reset(&response) {
+            Some(reset) => (reset - chrono::Utc::now())
+                .to_std()
+                .unwrap_or(Duration::ZERO),
+            None => SECONDARY_RATE_LIMIT_WAIT,
+        }
+    } else if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        SECONDARY_RATE_LIMIT_WAIT
+    } else {
+        // forbidden for reasons other than rate limiting
+        return None;
+    };
+
+    if wait > MAX_RATE_LIMIT_WAIT {
+        log::error!(
+            "API rate limit exceeded for '{}', lifted in {}s",
+            response.url(),
+            wait.as_secs()
+        );
+        return None;
+    }
+    log::warn!(
+        "API rate limit exceeded, waiting {}s to retry",
+        wait.as_secs()
+    );
+    Some(wait)
+}
@@ -23,6 +23,16 @@ pub struct GitRepo {
     pub(crate) repo: git2::Repository,
 }
 
+/// A commit, as obtained when walking a repository's history.
+///
+#[derive(Clone)]
+pub struct GitCommitInfo {
+    pub sha: String,
+    pub summary: String,
+    pub body: String,
+    pub is_merge: bool,
+}
+
 impl GitRepo {
     pub fn get_git_repo(self: &Self) -> &git2::Repository {
         &self.repo
@@ -162,8 +172,63 @@ impl GitRepo {
         }
     }
 
+    /// Obtain the URL of a given remote by name.
+    ///
+    pub fn get_remote_url(self: &Self, name: &str) -> Result<String, ()> {
+        let remote = self.get_remote(name)?;
+        match remote.url() {
+            Some(url) => Ok(url.to_string()),
+            None => {
+                log::error!("Remote '{}' has no valid URL", name);
+                Err(())
+            }
+        }
+    }
+
+    /// Set the URL of a given remote by name.
+    ///
+    pub fn set_remote_url(self: &Self, name: &str, url: &str) -> Result<(), ()> {
+        match self.repo.remote_set_url(name, url) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                log::error!("Unable to set URL for remote '{}': {}", name, e);
+                Err(())
+            }
+        }
+    }
+
+    /// Obtain remote callbacks authenticating with the credentials the remote
+    /// asks for, if 'with_auth' is true: the user's ssh key agent for ssh
+    /// remotes, git's credential helpers for https remotes, and default
+    /// credentials otherwise. Remotes not requiring authentication, such as
+    /// local repositories, never ask for credentials.
+    ///
+    fn get_remote_callbacks(with_auth: bool) -> Option<git2::RemoteCallbacks<'static>> {
+        if !with_auth {
+            return None;
+        }
+        let mut cbs = git2::RemoteCallbacks::new();
+        cbs.credentials(|url, user, allowed_types| {
+            log::trace!(
+                "auth url: {}, username: {:?}, allowed_types: {:?}",
+                url,
+                user,
+                allowed_types
+            );
+            if allowed_types.contains(git2::CredentialType::SSH_KEY) {
+                git2::Cred::ssh_key_from_agent(user.unwrap_or("git"))
+            } else if allowed_types.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
+                let cfg = git2::Config::open_default()?;
+                git2::Cred::credential_helper(&cfg, url, user)
+            } else {
+                git2::Cred::default()
+            }
+        });
+        Some(cbs)
+    }
+
     /// Open a connection for the provided remote. If 'with_auth' is true, then
-    /// the connection will be authenticated using the user's ssh key agent.
+    /// the connection will be authenticated if the remote requires it.
     ///
     pub(crate) fn open_remote<'a, 'b>(
         self: &'a Self,
@@ -171,23 +236,7 @@ impl GitRepo {
         direction: git2::Direction,
         with_auth: bool,
     ) -> Result<git2::RemoteConnection<'a, 'b, '_>, ()> {
-        let cbs: Option<git2::RemoteCallbacks> = if with_auth {
-            let mut cbs = git2::RemoteCallbacks::new();
-            cbs.credentials(|url, user, allowed_types| {
-                let username = user.unwrap();
-                log::trace!(
-                    "auth url: {}, username: {}, allowed_types: {:?}",
-                    url,
-                    username,
-                    allowed_types
-                );
-                git2::Cred::ssh_key_from_agent(username)
-            });
-            Some(cbs)
-        } else {
-            None
-        };
-
+        let cbs = GitRepo::get_remote_callbacks(with_auth);
         let conn = match remote.connect_auth(direction, cbs, None) {
             Ok(v) => v,
             Err(e) => {
@@ -199,6 +248,34 @@ impl GitRepo {
         Ok(conn)
     }
 
+    /// Check whether the remote at 'url' can be reached, without requiring a
+    /// local repository. If 'with_auth' is true, then the connection will be
+    /// authenticated if the remote requires it, and checked for pushing.
+    ///
+    pub fn check_remote_url(url: &String, with_auth: bool) -> Result<(), ()> {
+        let mut remote = match git2::Remote::create_detached(url.as_str()) {
+            Ok(r) => r,
+            Err(e) => {
+                log::error!("Unable to create remote for '{}': {}", url, e);
+                return Err(());
+            }
+        };
+        let direction = if with_auth {
+            git2::Direction::Push
+        } else {
+            git2::Direction::Fetch
+        };
+        let cbs = GitRepo::get_remote_callbacks(with_auth);
+        let res = match remote.connect_auth(direction, cbs, None) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                log::error!("Unable to connect to remote '{}': {}", url, e);
+                Err(())
+            }
+        };
+        res
+    }
+
     /// Update a single remote for this repository, by name.
     ///
     fn do_remote_update_single(self: &Self, name: &str, auth: bool) -> Result<(), ()> {
@@ -718,6 +795,243 @@ impl GitRepo {
         Ok(())
     }
 
+    /// Delete local tag 'name'.
+    ///
+    pub fn delete_tag(self: &Self, name: &String) -> Result<(), ()> {
+        match self.repo.tag_delete(&name) {
+            Ok(()) => {
+                log::debug!("Deleted tag '{}'", name);
+                Ok(())
+            }
+            Err(err) => {
+                log::error!("Unable to delete tag '{}': {}", name, err);
+                Err(())
+            }
+        }
+    }
+
+    /// Delete local branch 'name'. If the branch is currently checked out,
+    /// HEAD will be detached first.
+    ///
+    pub fn delete_branch(self: &Self, name: &String) -> Result<(), ()> {
+        let mut branch = match self.repo.find_branch(&name, git2::BranchType::Local) {
+            Ok(b) => b,
+            Err(err) => {
+                log::error!("Unable to find branch '{}': {}", name, err);
+                return Err(());
+            }
+        };
+        if branch.is_head() {
+            let oid = match branch.get().target() {
+                Some(oid) => oid,
+                None => {
+                    log::error!("Unable to obtain target for branch '{}'", name);
+                    return Err(());
+                }
+            };
+            if let Err(err) = self.repo.set_head_detached(oid) {
+                log::error!("Unable to detach HEAD from branch '{}': {}", name, err);
+                return Err(());
+            }
+        }
+        match branch.delete() {
+            Ok(()) => {
+                log::debug!("Deleted branch '{}'", name);
+                Ok(())
+            }
+            Err(err) => {
+                log::error!("Unable to delete branch '{}': {}", name, err);
+                Err(())
+            }
+        }
+    }
+
+    /// Reset local branch 'name' to commit 'oid'. If the branch is currently
+    /// checked out, the working tree will be reset as well.
+    ///
+    pub fn reset_branch(self: &Self, name: &String, oid: &String) -> Result<(), ()> {
+        let commit = match git2::Oid::from_str(&oid).and_then(|o| self.repo.find_commit(o)) {
+            Ok(c) => c,
+            Err(err) => {
+                log::error!("Unable to find commit '{}': {}", oid, err);
+                return Err(());
+            }
+        };
+        let branch = match self.repo.find_branch(&name, git2::BranchType::Local) {
+            Ok(b) => b,
+            Err(err) => {
+                log::error!("Unable to find branch '{}': {}", name, err);
+                return Err(());
+            }
+        };
+
+        if branch.is_head() {
+            match self
+                .repo
+                .reset(commit.as_object(), git2::ResetType::Hard, None)
+            {
+                Ok(()) => {}
+                Err(err) => {
+                    log::error!("Unable to reset '{}' to '{}': {}", name, oid, err);
+                    return Err(());
+                }
+            };
+        } else {
+            let refname = format!("refs/heads/{}", name);
+            let msg = format!("reset to {}", oid);
+            if let Err(err) = self.repo.reference(&refname, commit.id(), true, &msg) {
+                log::error!("Unable to reset '{}' to '{}': {}", name, oid, err);
+                return Err(());
+            }
+        }
+        log::debug!("Reset branch '{}' to '{}'", name, oid);
+
+        Ok(())
+    }
+
+    /// Find the commit for revision 'rev' (e.g., a possibly abbreviated SHA1),
+    /// if it exists in this repository.
+    ///
+    pub fn find_commit(self: &Self, rev: &String) -> Option<git2::Oid> {
+        match self.repo.revparse_single(&format!("{}^{{commit}}", rev)) {
+            Ok(obj) => Some(obj.id()),
+            Err(err) => {
+                log::trace!("Unable to find commit '{}': {}", rev, err);
+                None
+            }
+        }
+    }
+
+    /// Check whether 'commit' is reachable from 'refspec'.
+    ///
+    pub fn is_ancestor(self: &Self, commit: git2::Oid, refspec: &String) -> Result<bool, ()> {
+        let head = match self.get_oid_by_refspec(&refspec) {
+            Ok(obj) => match obj.peel_to_commit() {
+                Ok(c) => c.id(),
+                Err(err) => {
+                    log::error!("Unable to find commit for '{}': {}", refspec, err);
+                    return Err(());
+                }
+            },
+            Err(()) => return Err(()),
+        };
+        if head == commit {
+            return Ok(true);
+        }
+        match self.repo.graph_descendant_of(head, commit) {
+            Ok(v) => Ok(v),
+            Err(err) => {
+                log::error!(
+                    "Unable to check whether '{}' descends from '{}': {}",
+                    refspec,
+                    commit,
+                    err
+                );
+                Err(())
+            }
+        }
+    }
+
+    /// Obtain the contents of the file at 'path' on the commit 'refspec' points
+    /// to, if it exists.
+    ///
+    pub fn read_file(self: &Self, refspec: &String, path: &PathBuf) -> Result<Option<Vec<u8>>, ()> {
+        let tree = match self.get_oid_by_refspec(&refspec) {
+            Ok(obj) => match obj.peel_to_tree() {
+                Ok(t) => t,
+                Err(err) => {
+                    log::error!("Unable to find tree for '{}': {}", refspec, err);
+                    return Err(());
+                }
+            },
+            Err(()) => return Err(()),
+        };
+        let entry = match tree.get_path(&path) {
+            Ok(e) => e,
+            Err(err) => {
+                log::debug!(
+                    "Unable to find '{}' on '{}': {}",
+                    path.display(),
+                    refspec,
+                    err
+                );
+                return Ok(None);
+            }
+        };
+        match entry
+            .to_object(&self.repo)
+            .and_then(|obj| obj.peel_to_blob())
+        {
+            Ok(blob) => Ok(Some(blob.content().to_vec())),
+            Err(err) => {
+                log::error!(
+                    "Unable to read '{}' on '{}': {}",
+                    path.display(),
+                    refspec,
+                    err
+                );
+                Err(())
+            }
+        }
+    }
+
+    /// Obtain the commits on the first-parent history of 'refspec', newest
+    /// first, stopping at those reachable from 'from', if provided.
+    ///
+    pub fn log(
+        self: &Self,
+        from: Option<&String>,
+        refspec: &String,
+    ) -> Result<Vec<GitCommitInfo>, ()> {
+        let mut walk = match self.repo.revwalk() {
+            Ok(w) => w,
+            Err(err) => {
+                log::error!("Unable to walk repository history: {}", err);
+                return Err(());
+            }
+        };
+        walk.simplify_first_parent().unwrap();
+        let head = match self.find_commit(&refspec) {
+            Some(oid) => oid,
+            None => {
+                log::error!("Unable to find commit for '{}'", refspec);
+                return Err(());
+            }
+        };
+        if let Err(err) = walk.push(head) {
+            log::error!("Unable to walk history from '{}': {}", refspec, err);
+            return Err(());
+        }
+        if let Some(from) = from {
+            match self.find_commit(&from) {
+                Some(oid) => walk.hide(oid).unwrap(),
+                None => {
+                    log::error!("Unable to find commit for '{}'", from);
+                    return Err(());
+                }
+            };
+        }
+
+        let mut commits: Vec<GitCommitInfo> = vec![];
+        for oid in walk {
+            let commit = match oid.and_then(|oid| self.repo.find_commit(oid)) {
+                Ok(c) => c,
+                Err(err) => {
+                    log::error!("Unable to obtain commit walking '{}': {}", refspec, err);
+                    return Err(());
+                }
+            };
+            commits.push(GitCommitInfo {
+                sha: commit.id().to_string(),
+                summary: commit.summary().unwrap_or_default().to_string(),
+                body: commit.body().unwrap_or_default().to_string(),
+                is_merge: commit.parent_count() > 1,
+            });
+        }
+
+        Ok(commits)
+    }
+
     /// Obtain the difference in commits between a given refspec and a specified head.
     ///
     pub fn diff(
@@ -14,7 +14,9 @@
 
 mod args;
 mod common;
+mod forge;
 mod git;
+mod registry;
 mod release;
 mod version;
 mod ws;
@@ -0,0 +1,56 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::ws::config::{WSRegistryConfig, WSRegistryKind};
+
+use self::errors::RegistryResult;
+
+pub mod errors;
+mod ghcr;
+mod oci;
+mod quay;
+
+/// A container registry, from which the tags for the release's images can be
+/// obtained.
+///
+#[async_trait::async_trait]
+pub trait Registry: Send + Sync {
+    /// Obtain all tags for the image at repository 'repo'.
+    async fn get_tags(self: &Self, repo: &String) -> RegistryResult<Vec<String>>;
+
+    /// Obtain the reference to pull the image at repository 'repo' with tag
+    /// 'tag' (e.g., 'quay.io/s3gw/s3gw:v0.17.0').
+    fn get_image_ref(self: &Self, repo: &String, tag: &String) -> String;
+}
+
+/// Obtain the registry described by 'cfg'.
+///
+pub fn from_config(cfg: &WSRegistryConfig) -> Box<dyn Registry> {
+    match cfg.kind {
+        WSRegistryKind::Quay => Box::new(quay::QuayRegistry::new(&cfg)),
+        WSRegistryKind::Oci => Box::new(oci::OciRegistry::new(&cfg)),
+        WSRegistryKind::Ghcr => Box::new(ghcr::GhcrRegistry::new(&cfg)),
+    }
+}
+
+/// Obtain the reference to image 'repo' with tag 'tag' on the registry at
+/// 'url', as used to pull it.
+///
+fn image_ref(url: &String, repo: &String, tag: &String) -> String {
+    let host = url
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_end_matches('/');
+    format!("{}/{}:{}", host, repo, tag)
+}
@@ -0,0 +1,38 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Display;
+
+#[derive(Clone, Copy, Debug)]
+pub enum RegistryError {
+    RequestError,
+    ResponseError,
+    AuthError,
+    TokenError,
+    NotFoundError,
+}
+
+impl Display for RegistryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            RegistryError::RequestError => "error requesting from registry",
+            RegistryError::ResponseError => "unexpected response from registry",
+            RegistryError::AuthError => "unable to authenticate with registry",
+            RegistryError::TokenError => "unable to obtain registry token",
+            RegistryError::NotFoundError => "repository not found",
+        })
+    }
+}
+
+pub type RegistryResult<T> = Result<T, RegistryError>;
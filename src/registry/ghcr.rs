@@ -0,0 +1,50 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::ws::config::WSRegistryConfig;
+
+use super::{errors::RegistryResult, oci::OciRegistry, Registry};
+
+const GHCR_URL: &str = "https://ghcr.io";
+
+/// GitHub's Container Registry. It provides the OCI Distribution API, handing
+/// out anonymous tokens for public images, but requires repositories to be in
+/// lowercase.
+///
+pub struct GhcrRegistry {
+    oci: OciRegistry,
+}
+
+impl GhcrRegistry {
+    pub fn new(cfg: &WSRegistryConfig) -> GhcrRegistry {
+        GhcrRegistry {
+            oci: OciRegistry::with_url(
+                cfg.url.clone().unwrap_or(GHCR_URL.into()),
+                cfg.username.clone(),
+                cfg.token.clone(),
+            ),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Registry for GhcrRegistry {
+    async fn get_tags(self: &Self, repo: &String) -> RegistryResult<Vec<String>> {
+        self.oci.get_tags(&repo.to_lowercase()).await
+    }
+
+    fn get_image_ref(self: &Self, repo: &String, tag: &String) -> String {
+        self.oci.get_image_ref(&repo.to_lowercase(), &tag)
+    }
+}
@@ -0,0 +1,275 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use crate::ws::{config::WSRegistryConfig, token::WSTokenSource};
+
+use super::{
+    errors::{RegistryError, RegistryResult},
+    Registry,
+};
+
+// ----
+// raw responses from the OCI Distribution API
+// ----
+
+#[derive(serde::Deserialize)]
+struct OciTagList {
+    tags: Option<Vec<String>>,
+}
+
+#[derive(serde::Deserialize)]
+struct OciAuthToken {
+    token: Option<String>,
+    access_token: Option<String>,
+}
+
+// ----
+// end of raw responses from the OCI Distribution API
+// ----
+
+enum OciAuth {
+    Anonymous,
+    Basic(String, String),
+    Bearer(String),
+}
+
+/// A registry providing the OCI Distribution API (e.g., Harbor, or a plain
+/// 'distribution' registry). Authentication is negotiated according to the
+/// registry's challenge, obtaining a token from the registry's token service
+/// if so required.
+///
+pub struct OciRegistry {
+    url: String,
+    username: Option<String>,
+    token: Option<WSTokenSource>,
+}
+
+impl OciRegistry {
+    pub fn new(cfg: &WSRegistryConfig) -> OciRegistry {
+        OciRegistry::with_url(
+            cfg.url.clone().unwrap_or_default(),
+            cfg.username.clone(),
+            cfg.token.clone(),
+        )
+    }
+
+    pub fn with_url(
+        url: String,
+        username: Option<String>,
+        token: Option<WSTokenSource>,
+    ) -> OciRegistry {
+        OciRegistry {
+            url,
+            username,
+            token,
+        }
+    }
+
+    /// Obtain the credentials to authenticate with, if a token is configured.
+    ///
+    fn get_credentials(self: &Self) -> RegistryResult<Option<(String, String)>> {
+        let source = match &self.token {
+            None => return Ok(None),
+            Some(v) => v,
+        };
+        match source.resolve() {
+            Ok(password) => Ok(Some((self.username.clone().unwrap_or_default(), password))),
+            Err(err) => {
+                log::error!("Unable to obtain registry token from {}: {}", source, err);
+                Err(RegistryError::TokenError)
+            }
+        }
+    }
+
+    /// Obtain the means to authenticate with the registry, according to the
+    /// provided 'WWW-Authenticate' header value, 'challenge', to pull from
+    /// repository 'repo'.
+    ///
+    async fn authenticate(
+        self: &Self,
+        client: &reqwest::Client,
+        challenge: &str,
+        repo: &String,
+    ) -> RegistryResult<OciAuth> {
+        let (scheme, params_str) = match challenge.split_once(' ') {
+            Some((s, p)) => (s.to_lowercase(), p),
+            None => (challenge.to_lowercase(), ""),
+        };
+        let params_re = regex::Regex::new(r#"(\w+)="([^"]*)""#).unwrap();
+        let params: HashMap<&str, &str> = params_re
+            .captures_iter(params_str)
+            .map(|c| (c.get(1).unwrap().as_str(), c.get(2).unwrap().as_str()))
+            .collect();
+        let credentials = self.get_credentials()?;
+
+        if scheme == "basic" {
+            return match credentials {
+                Some((username, password)) => Ok(OciAuth::Basic(username, password)),
+                None => {
+                    log::error!("Registry at '{}' requires credentials", self.url);
+                    Err(RegistryError::AuthError)
+                }
+            };
+        } else if scheme != "bearer" {
+            log::error!("Unknown registry authentication scheme '{}'", scheme);
+            return Err(RegistryError::AuthError);
+        }
+
+        let realm = match params.get("realm") {
+            Some(v) => v,
+            None => {
+                log::error!("Registry authentication challenge without realm");
+                return Err(RegistryError::AuthError);
+            }
+        };
+        let scope = match params.get("scope") {
+            Some(v) => v.to_string(),
+            None => format!("repository:{}:pull", repo),
+        };
+        let mut query = vec![("scope", scope)];
+        if let Some(service) = params.get("service") {
+            query.push(("service", service.to_string()));
+        }
+
+        let mut request = client
+            .get(*realm)
+            .query(&query)
+            .header("User-Agent", "s3gw-arc-rs");
+        if let Some((username, password)) = credentials {
+            request = request.basic_auth(username, Some(password));
+        }
+        let response = match request.send().await {
+            Ok(r) => r,
+            Err(err) => {
+                log::error!("Unable to obtain registry token from '{}': {}", realm, err);
+                return Err(RegistryError::AuthError);
+            }
+        };
+        if !response.status().is_success() {
+            log::error!(
+                "Unable to obtain registry token from '{}': {}",
+                realm,
+                response.status()
+            );
+            return Err(RegistryError::AuthError);
+        }
+
+        match response.json::<OciAuthToken>().await {
+            Ok(OciAuthToken {
+                token: Some(token), ..
+            })
+            | Ok(OciAuthToken {
+                access_token: Some(token),
+                ..
+            }) => Ok(OciAuth::Bearer(token)),
+            Ok(_) => {
+                log::error!("Registry token service at '{}' returned no token", realm);
+                Err(RegistryError::AuthError)
+            }
+            Err(err) => {
+                log::error!("Unable to parse registry token from '{}': {}", realm, err);
+                Err(RegistryError::AuthError)
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Registry for OciRegistry {
+    /// Obtain all tags for repository 'repo', following the registry's
+    /// pagination if needed.
+    ///
+    async fn get_tags(self: &Self, repo: &String) -> RegistryResult<Vec<String>> {
+        let client = reqwest::Client::new();
+        let api_url = format!("{}/v2/{}/tags/list", self.url.trim_end_matches('/'), repo);
+        let mut url = match reqwest::Url::parse(&api_url) {
+            Ok(v) => v,
+            Err(err) => {
+                log::error!("Invalid registry URL '{}': {}", api_url, err);
+                return Err(RegistryError::RequestError);
+            }
+        };
+
+        let mut auth = OciAuth::Anonymous;
+        let mut tags = vec![];
+        loop {
+            let request = client.get(url.clone()).header("User-Agent", "s3gw-arc-rs");
+            let request = match &auth {
+                OciAuth::Anonymous => request,
+                OciAuth::Basic(username, password) => request.basic_auth(username, Some(password)),
+                OciAuth::Bearer(token) => request.bearer_auth(token),
+            };
+            let response = match request.send().await {
+                Ok(r) => r,
+                Err(err) => {
+                    log::error!(
+                        "Unable to obtain tags for '{}' from '{}': {}",
+                        repo,
+                        url,
+                        err
+                    );
+                    return Err(RegistryError::RequestError);
+                }
+            };
+
+            let status = response.status();
+            if status == reqwest::StatusCode::UNAUTHORIZED {
+                if let OciAuth::Anonymous = auth {
+                    let challenge = match response
+                        .headers()
+                        .get(reqwest::header::WWW_AUTHENTICATE)
+                        .and_then(|v| v.to_str().ok())
+                    {
+                        Some(v) => v.to_string(),
+                        None => {
+                            log::error!("Registry at '{}' requires unknown authentication", url);
+                            return Err(RegistryError::AuthError);
+                        }
+                    };
+                    auth = self.authenticate(&client, &challenge, &repo).await?;
+                    continue;
+                }
+                log::error!("Unauthorized to obtain tags for '{}'", repo);
+                return Err(RegistryError::AuthError);
+            } else if status == reqwest::StatusCode::NOT_FOUND {
+                return Err(RegistryError::NotFoundError);
+            } else if !status.is_success() {
+                log::error!("Unable to obtain tags for '{}': {}", repo, status);
+                return Err(RegistryError::RequestError);
+            }
+
+            let next = crate::common::get_next_page(&response);
+            match response.json::<OciTagList>().await {
+                Ok(list) => tags.extend(list.tags.unwrap_or_default()),
+                Err(err) => {
+                    log::error!("Unable to parse tags for '{}': {}", repo, err);
+                    return Err(RegistryError::ResponseError);
+                }
+            };
+
+            match next {
+                Some(v) => url = v,
+                None => break,
+            };
+        }
+
+        Ok(tags)
+    }
+
+    fn get_image_ref(self: &Self, repo: &String, tag: &String) -> String {
+        super::image_ref(&self.url, &repo, &tag)
+    }
+}
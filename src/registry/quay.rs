@@ -0,0 +1,113 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use crate::ws::{config::WSRegistryConfig, token::WSTokenSource};
+
+use super::{
+    errors::{RegistryError, RegistryResult},
+    Registry,
+};
+
+const QUAY_URL: &str = "https://quay.io";
+
+// ----
+// raw responses from Quay.io for repository tags
+// ----
+
+#[derive(serde::Deserialize)]
+struct QuayRepositoryTagResult {
+    tags: HashMap<String, QuayRepositoryTagEntry>,
+}
+
+#[derive(serde::Deserialize)]
+struct QuayRepositoryTagEntry {
+    #[allow(dead_code)]
+    name: String,
+}
+
+// ----
+// end of raw responses from Quay.io for repository tags
+// ----
+
+/// A registry providing Quay's API, such as quay.io.
+///
+pub struct QuayRegistry {
+    url: String,
+    token: Option<WSTokenSource>,
+}
+
+impl QuayRegistry {
+    pub fn new(cfg: &WSRegistryConfig) -> QuayRegistry {
+        QuayRegistry {
+            url: cfg.url.clone().unwrap_or(QUAY_URL.into()),
+            token: cfg.token.clone(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Registry for QuayRegistry {
+    /// Obtain all tags from the specified repository 'repo', in the form of
+    /// 'namespace/repository'.
+    ///
+    async fn get_tags(self: &Self, repo: &String) -> RegistryResult<Vec<String>> {
+        let api_url = format!(
+            "{}/api/v1/repository/{}",
+            self.url.trim_end_matches('/'),
+            repo
+        );
+
+        let mut request = reqwest::Client::new()
+            .get(&api_url)
+            .query(&[("includeTags", "true")]);
+        if let Some(source) = &self.token {
+            request = match source.resolve() {
+                Ok(token) => request.bearer_auth(token),
+                Err(err) => {
+                    log::error!("Unable to obtain quay token from {}: {}", source, err);
+                    return Err(RegistryError::TokenError);
+                }
+            };
+        }
+
+        let response = match request.send().await {
+            Ok(r) => r,
+            Err(err) => {
+                log::error!("Unable to obtain tags from quay for '{}': {}", repo, err);
+                return Err(RegistryError::RequestError);
+            }
+        };
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(RegistryError::NotFoundError);
+        }
+
+        match response.json::<QuayRepositoryTagResult>().await {
+            Ok(r) => Ok(r.tags.into_keys().collect()),
+            Err(err) => {
+                log::error!(
+                    "Unable to obtain resulting tags from quay for '{}': {}",
+                    repo,
+                    err
+                );
+                Err(RegistryError::ResponseError)
+            }
+        }
+    }
+
+    fn get_image_ref(self: &Self, repo: &String, tag: &String) -> String {
+        super::image_ref(&self.url, &repo, &tag)
+    }
+}
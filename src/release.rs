@@ -14,17 +14,21 @@
 
 use std::path::PathBuf;
 
+use crate::common::OutputFormat;
 use crate::version::Version;
 use crate::ws::workspace::Workspace;
-use crate::{boomln, infoln};
+use crate::{boomln, errorln, infoln, successln, warnln};
 
+mod changelog;
 pub mod cmds;
 mod common;
 pub mod errors;
+mod journal;
 mod list;
 mod process;
 mod status;
 mod sync;
+mod watch;
 
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct ReleaseState {
@@ -76,6 +80,11 @@ impl Release {
     pub fn write(self: &Self) -> Result<(), ()> {
         assert!(self.confdir.exists());
 
+        if self.ws.dry_run.is_some() {
+            log::debug!("dry-run: not writing release state");
+            return Ok(());
+        }
+
         let state = match &self.state {
             None => {
                 log::debug!("No state to write to file!");
@@ -114,9 +123,28 @@ impl Release {
         Ok(())
     }
 
-    pub async fn status(self: &Self, version: &Version) {
+    /// Show the status of release 'version', including the workflow 'details'
+    /// requested. If 'watch' is provided, then watch the latest release
+    /// workflow until it completes, polling at the provided interval.
+    ///
+    pub async fn status(
+        self: &Self,
+        version: &Version,
+        format: &OutputFormat,
+        watch: &Option<std::time::Duration>,
+        details: &status::WorkflowDetails,
+    ) {
         infoln!("Show release status for version {}", version);
 
+        if let Ok(Some(journal)) = journal::ReleaseJournal::load(&self.ws) {
+            warnln!(
+                "Unfinished release steps found for '{}' ({} completed)",
+                journal.tagver,
+                journal.entries.len()
+            );
+            infoln!("Use 'arc rel resume' or 'arc rel rollback'.");
+        }
+
         match self.ws.sync() {
             Ok(_) => {}
             Err(_) => {
@@ -126,13 +154,32 @@ impl Release {
         };
 
         let release_versions = common::get_release_versions(&self.ws, version);
-        if release_versions.contains_key(&version.get_version_id()) {
+        if release_versions.contains_key(&version) {
             infoln!("Release version {} already exists.", version);
         } else if release_versions.len() == 0 {
             infoln!("Release version {} has not been started yet.", version);
             return;
         };
 
-        status::status(&self.ws, &version, &release_versions).await;
+        if status::status(&self.ws, &version, &release_versions, &format, &details)
+            .await
+            .is_err()
+        {
+            boomln!("Unable to output release status!");
+            return;
+        }
+
+        if let (Some(poll_interval), Some((_, latest))) = (watch, release_versions.last_key_value())
+        {
+            match watch::wait_for_release_workflow(&self.ws, &latest, &poll_interval).await {
+                Ok(run) if run.success => {
+                    successln!("Release workflow for '{}' succeeded.", run.tag);
+                }
+                Ok(run) => watch::report_failure(&run),
+                Err(err) => {
+                    errorln!("Unable to watch release workflow: {}", err);
+                }
+            };
+        }
     }
 }
@@ -0,0 +1,611 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    path::PathBuf,
+};
+
+use crate::{
+    errorln, infoln,
+    release::{
+        common,
+        errors::{ReleaseError, ReleaseResult},
+        Release,
+    },
+    successln,
+    version::Version,
+    warnln,
+    ws::{repository::Repository, workspace::Workspace},
+};
+
+/// How to group changes in a changelog.
+///
+#[derive(Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum ChangelogGrouping {
+    /// By Conventional Commit type (e.g., 'feat: ...', 'fix(rgw): ...')
+    #[default]
+    Commits,
+    /// By the labels of the pull request introducing each change, falling back
+    /// to the Conventional Commit type
+    Labels,
+}
+
+/// Kind of change, as per its Conventional Commit type or pull request label.
+/// Kinds are rendered in the order they are declared.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeKind {
+    Breaking,
+    Feature,
+    Fix,
+    Performance,
+    Refactor,
+    Documentation,
+    Testing,
+    Build,
+    CI,
+    Chore,
+    Other,
+}
+
+impl ChangeKind {
+    /// Obtain the kind of change for a Conventional Commit type (e.g., 'feat')
+    /// or a pull request label (e.g., 'kind/bug').
+    ///
+    fn from_type(value: &str) -> Option<ChangeKind> {
+        let value = value.trim().to_ascii_lowercase();
+        let value = match value.rsplit_once('/') {
+            Some((_, v)) => v,
+            None => value.as_str(),
+        };
+        match value {
+            "breaking" | "breaking-change" => Some(ChangeKind::Breaking),
+            "feat" | "feature" | "enhancement" => Some(ChangeKind::Feature),
+            "fix" | "bug" | "bugfix" => Some(ChangeKind::Fix),
+            "perf" | "performance" => Some(ChangeKind::Performance),
+            "refactor" | "style" | "cleanup" => Some(ChangeKind::Refactor),
+            "docs" | "doc" | "documentation" => Some(ChangeKind::Documentation),
+            "test" | "tests" | "testing" => Some(ChangeKind::Testing),
+            "build" | "deps" | "dependencies" => Some(ChangeKind::Build),
+            "ci" => Some(ChangeKind::CI),
+            "chore" => Some(ChangeKind::Chore),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ChangeKind::Breaking => "Breaking Changes",
+            ChangeKind::Feature => "Features",
+            ChangeKind::Fix => "Bug Fixes",
+            ChangeKind::Performance => "Performance",
+            ChangeKind::Refactor => "Refactoring",
+            ChangeKind::Documentation => "Documentation",
+            ChangeKind::Testing => "Testing",
+            ChangeKind::Build => "Build",
+            ChangeKind::CI => "Continuous Integration",
+            ChangeKind::Chore => "Chores",
+            ChangeKind::Other => "Other Changes",
+        })
+    }
+}
+
+/// A single change, as introduced by a commit or a pull request.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChangeEntry {
+    pub repo: String,
+    pub sha: String,
+    pub scope: Option<String>,
+    pub summary: String,
+    pub pr: Option<u64>,
+}
+
+impl ChangeEntry {
+    fn render(self: &Self) -> String {
+        let reference = match self.pr {
+            Some(number) => format!("{}#{}", self.repo, number),
+            None => format!("{}@{}", self.repo, &self.sha[..self.sha.len().min(7)]),
+        };
+        match &self.scope {
+            Some(scope) => format!("- **{}:** {} ({})", scope, self.summary, reference),
+            None => format!("- {} ({})", self.summary, reference),
+        }
+    }
+}
+
+/// Changes introduced by a release, across all workspace repositories, per
+/// kind of change.
+///
+pub struct Changelog {
+    pub version: Version,
+    pub entries: BTreeMap<ChangeKind, Vec<ChangeEntry>>,
+}
+
+impl Changelog {
+    fn add(self: &mut Self, kind: ChangeKind, entry: ChangeEntry) {
+        self.entries.entry(kind).or_default().push(entry);
+    }
+
+    /// Whether no changes have been found.
+    ///
+    pub fn is_empty(self: &Self) -> bool {
+        self.entries.values().all(|e| e.is_empty())
+    }
+
+    /// Render the changes as markdown sections, one per kind of change.
+    ///
+    pub fn render_sections(self: &Self) -> String {
+        if self.is_empty() {
+            return String::from("No changes.\n");
+        }
+        let mut sections: Vec<String> = vec![];
+        for (kind, entries) in &self.entries {
+            let lines: Vec<String> = entries.iter().map(|e| e.render()).collect();
+            sections.push(format!("## {}\n\n{}\n", kind, lines.join("\n")));
+        }
+        sections.join("\n")
+    }
+
+    /// Render the changelog as a markdown release notes draft.
+    ///
+    pub fn render(self: &Self) -> String {
+        format!(
+            "# Release Notes - v{}\n\n{}",
+            self.version,
+            self.render_sections()
+        )
+    }
+
+    /// Regroup changes introduced by pull requests per their labels, for
+    /// repositories with a GitHub repository configured, authenticating with
+    /// each repository's forge token. Changes whose pull requests have no known
+    /// labels are kept as they are.
+    ///
+    pub async fn apply_labels(self: &mut Self, ws: &Workspace) -> ReleaseResult<()> {
+        let mut tokens: HashMap<String, String> = HashMap::new();
+        for repo in ws.repos.as_vec() {
+            if repo.config.github.as_ref().is_some_and(|gh| gh.is_github()) {
+                tokens.insert(repo.name.clone(), common::get_forge_token(&ws, &repo)?);
+            }
+        }
+
+        let mut regrouped = Changelog {
+            version: self.version.clone(),
+            entries: BTreeMap::new(),
+        };
+        for (kind, entries) in &self.entries {
+            for entry in entries {
+                let repo = match ws.repos.get(&entry.repo) {
+                    Some(r) => r,
+                    None => {
+                        regrouped.add(*kind, entry.clone());
+                        continue;
+                    }
+                };
+                let label_kind = match (*kind, entry.pr, tokens.get(&repo.name)) {
+                    (ChangeKind::Breaking, _, _) | (_, None, _) | (_, _, None) => None,
+                    (_, Some(number), Some(token)) => {
+                        get_pull_request_labels(&repo, &token, number)
+                            .await?
+                            .iter()
+                            .filter_map(|label| ChangeKind::from_type(&label))
+                            .min()
+                    }
+                };
+                regrouped.add(label_kind.unwrap_or(*kind), entry.clone());
+            }
+        }
+        self.entries = regrouped.entries;
+
+        Ok(())
+    }
+}
+
+// ----
+// raw responses from GitHub for pull requests
+// ----
+
+#[derive(serde::Deserialize)]
+struct GitHubLabel {
+    name: String,
+}
+
+#[derive(serde::Deserialize)]
+struct GitHubPullRequest {
+    labels: Vec<GitHubLabel>,
+}
+
+// ----
+// end of raw responses from GitHub for pull requests
+// ----
+
+/// Obtain the labels of pull request 'number' on repository 'repo', if the
+/// repository has a GitHub repository configured.
+///
+async fn get_pull_request_labels(
+    repo: &Repository,
+    github_token: &String,
+    number: u64,
+) -> ReleaseResult<Vec<String>> {
+    let gh_config = match &repo.config.github {
+        Some(c) if c.is_github() => c,
+        _ => {
+            log::debug!("No GitHub repository for '{}', skip labels", repo.name);
+            return Ok(vec![]);
+        }
+    };
+
+    let api_url = gh_config.get_api_url(&format!("pulls/{}", number));
+    let client = crate::forge::GitHubClient::new(&github_token);
+    let response = match client
+        .send(client.request(reqwest::Method::GET, &api_url))
+        .await
+    {
+        Ok(r) => r,
+        Err(err) => {
+            errorln!("Unable to obtain pull request {}: {}", number, err);
+            return Err(ReleaseError::UnknownError);
+        }
+    };
+    if !response.status().is_success() {
+        warnln!(
+            "Unable to obtain pull request {} on '{}': {}",
+            number,
+            repo.name,
+            response.status()
+        );
+        return Ok(vec![]);
+    }
+    match response.json::<GitHubPullRequest>().await {
+        Ok(pr) => Ok(pr.labels.into_iter().map(|l| l.name).collect()),
+        Err(err) => {
+            errorln!("Unable to parse pull request {}: {}", number, err);
+            Err(ReleaseError::UnknownError)
+        }
+    }
+}
+
+/// Parse a commit's 'summary' and 'body' into a change entry for repository
+/// 'repo', along with its kind. Merge commits other than those merging a pull
+/// request, and commits made by the release process itself, are skipped.
+///
+fn parse_commit(
+    repo: &String,
+    sha: &String,
+    summary: &String,
+    body: &String,
+    is_merge: bool,
+) -> Option<(ChangeKind, ChangeEntry)> {
+    let release_re = regex::Regex::new(
+        r"^(release candidate \d+ for v|pre-release \S+ for v|[Rr]elease v|Update charts to version )",
+    )
+    .unwrap();
+    let merge_re = regex::Regex::new(r"^Merge pull request #(\d+) from \S+").unwrap();
+    let squash_re = regex::Regex::new(r"^(.*\S)\s+\(#(\d+)\)$").unwrap();
+    let conventional_re = regex::Regex::new(
+        r"^(?P<type>[A-Za-z]+)(\((?P<scope>[^)]+)\))?(?P<breaking>!)?:\s*(?P<summary>.+)$",
+    )
+    .unwrap();
+
+    if release_re.is_match(&summary) {
+        return None;
+    }
+
+    let (summary, pr) = if is_merge {
+        let number = match merge_re.captures(&summary) {
+            Some(m) => m[1].parse::<u64>().ok(),
+            None => return None,
+        };
+        let title = match body.lines().map(|l| l.trim()).find(|l| !l.is_empty()) {
+            Some(v) => v.to_string(),
+            None => summary.clone(),
+        };
+        (title, number)
+    } else {
+        match squash_re.captures(&summary) {
+            Some(m) => (m[1].to_string(), m[2].parse::<u64>().ok()),
+            None => (summary.clone(), None),
+        }
+    };
+
+    let mut entry = ChangeEntry {
+        repo: repo.clone(),
+        sha: sha.clone(),
+        scope: None,
+        summary: summary.clone(),
+        pr,
+    };
+
+    let kind = match conventional_re.captures(&summary) {
+        None => ChangeKind::Other,
+        Some(m) => match ChangeKind::from_type(&m["type"]) {
+            None => ChangeKind::Other,
+            Some(kind) => {
+                entry.scope = m.name("scope").map(|s| s.as_str().to_string());
+                entry.summary = m["summary"].to_string();
+                if m.name("breaking").is_some() || body.contains("BREAKING CHANGE:") {
+                    ChangeKind::Breaking
+                } else {
+                    kind
+                }
+            }
+        },
+    };
+
+    Some((kind, entry))
+}
+
+/// Obtain the latest release before 'version' on repository 'repo', if any.
+///
+fn get_previous_release(repo: &Repository, version: &Version) -> ReleaseResult<Option<Version>> {
+    match repo.get_versions() {
+        Ok(versions) => Ok(versions
+            .into_keys()
+            .filter(|v| !v.is_prerelease() && v < version)
+            .last()),
+        Err(err) => {
+            errorln!("Unable to obtain versions for '{}': {}", repo.name, err);
+            Err(ReleaseError::UnknownError)
+        }
+    }
+}
+
+/// Collect the changes introduced by release 'version', walking each
+/// workspace repository's history from 'version' (its tag, release branch, or
+/// default branch, whichever exists) back to the previous release, or to
+/// release 'from' if provided. Changes are grouped by their Conventional
+/// Commit type.
+///
+pub fn collect(
+    ws: &Workspace,
+    version: &Version,
+    from: &Option<Version>,
+) -> ReleaseResult<Changelog> {
+    let mut changelog = Changelog {
+        version: version.clone(),
+        entries: BTreeMap::new(),
+    };
+
+    for repo in ws.repos.as_vec() {
+        let previous = match from {
+            Some(v) => {
+                match repo.get_versions() {
+                    Ok(versions) if versions.contains_key(&v) => {}
+                    Ok(_) => {
+                        errorln!("Release {} not found on '{}'", v, repo.name);
+                        return Err(ReleaseError::UnknownError);
+                    }
+                    Err(err) => {
+                        errorln!("Unable to obtain versions for '{}': {}", repo.name, err);
+                        return Err(ReleaseError::UnknownError);
+                    }
+                };
+                Some(v.clone())
+            }
+            None => get_previous_release(&repo, &version)?,
+        };
+
+        let to_refspec = match repo.get_version_refspec(&version) {
+            Ok(v) => v,
+            Err(err) => {
+                errorln!("Unable to find v{} on '{}': {}", version, repo.name, err);
+                return Err(ReleaseError::UnknownError);
+            }
+        };
+        let from_refspec = match &previous {
+            Some(v) => Some(repo.get_version_tag_refspec(&v)),
+            None => {
+                warnln!(
+                    "No release prior to v{} found on '{}', using its whole history.",
+                    version,
+                    repo.name
+                );
+                None
+            }
+        };
+        log::debug!(
+            "Changelog for '{}' from '{}' to '{}'",
+            repo.name,
+            from_refspec.clone().unwrap_or_default(),
+            to_refspec
+        );
+
+        let commits = match repo.get_commits(from_refspec.as_ref(), &to_refspec) {
+            Ok(v) => v,
+            Err(err) => {
+                errorln!("Unable to obtain commits for '{}': {}", repo.name, err);
+                return Err(ReleaseError::UnknownError);
+            }
+        };
+        for commit in commits.iter().rev() {
+            if let Some((kind, entry)) = parse_commit(
+                &repo.name,
+                &commit.sha,
+                &commit.summary,
+                &commit.body,
+                commit.is_merge,
+            ) {
+                changelog.add(kind, entry);
+            }
+        }
+    }
+
+    Ok(changelog)
+}
+
+/// Generate release notes for 'version', comparing against release 'from' if
+/// provided, or the previous release otherwise, grouped as per 'grouping'.
+/// Release notes are written to 'outfile' if provided, or stdout otherwise.
+///
+pub async fn changelog(
+    release: &Release,
+    version: &Version,
+    from: &Option<Version>,
+    grouping: &ChangelogGrouping,
+    outfile: &Option<PathBuf>,
+) -> ReleaseResult<()> {
+    infoln!("Generate release notes for v{}", version);
+    if let Err(()) = release.ws.sync() {
+        errorln!("Unable to synchronize workspace repositories!");
+        return Err(ReleaseError::SyncError);
+    }
+
+    let mut changelog = collect(&release.ws, &version, &from)?;
+    if *grouping == ChangelogGrouping::Labels {
+        changelog.apply_labels(&release.ws).await?;
+    }
+
+    match outfile {
+        None => print!("{}", changelog.render()),
+        Some(path) => {
+            if let Err(err) = std::fs::write(&path, changelog.render()) {
+                errorln!(
+                    "Unable to write release notes to '{}': {}",
+                    path.display(),
+                    err
+                );
+                return Err(ReleaseError::UnknownError);
+            }
+            successln!("Wrote release notes to '{}'", path.display());
+        }
+    };
+
+    Ok(())
+}
+
+/// Generate a release notes draft for 'version', written to the release's
+/// config directory, returning its path.
+///
+pub fn write_draft(release: &Release, version: &Version) -> ReleaseResult<PathBuf> {
+    infoln!("Generate release notes for v{}...", version);
+    let changelog = collect(&release.ws, &version, &None)?;
+    let path = release
+        .confdir
+        .join(format!("release-notes-v{}.md", version));
+    if let Err(err) = std::fs::write(&path, changelog.render()) {
+        errorln!(
+            "Unable to write release notes to '{}': {}",
+            path.display(),
+            err
+        );
+        return Err(ReleaseError::UnknownError);
+    }
+    infoln!("Generated release notes at '{}'", path.display());
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_commit, ChangeEntry, ChangeKind, Changelog};
+    use crate::version::Version;
+    use std::collections::BTreeMap;
+
+    fn parse(summary: &str, body: &str, is_merge: bool) -> Option<(ChangeKind, ChangeEntry)> {
+        parse_commit(
+            &"s3gw".into(),
+            &"0123456789abcdef".into(),
+            &summary.into(),
+            &body.into(),
+            is_merge,
+        )
+    }
+
+    #[test]
+    fn conventional_commits() {
+        let (kind, entry) = parse("feat(rgw): add object lock (#42)", "", false).unwrap();
+        assert_eq!(kind, ChangeKind::Feature);
+        assert_eq!(entry.scope, Some("rgw".into()));
+        assert_eq!(entry.summary, "add object lock");
+        assert_eq!(entry.pr, Some(42));
+
+        let (kind, entry) = parse("fix: crash on empty bucket", "", false).unwrap();
+        assert_eq!(kind, ChangeKind::Fix);
+        assert_eq!(entry.scope, None);
+        assert_eq!(entry.pr, None);
+
+        let (kind, _) = parse("refactor(sfs)!: drop v1 schema", "", false).unwrap();
+        assert_eq!(kind, ChangeKind::Breaking);
+        let (kind, _) = parse("feat: new api", "BREAKING CHANGE: old api gone", false).unwrap();
+        assert_eq!(kind, ChangeKind::Breaking);
+
+        let (kind, entry) = parse("Update the README", "", false).unwrap();
+        assert_eq!(kind, ChangeKind::Other);
+        assert_eq!(entry.summary, "Update the README");
+        let (kind, entry) = parse("wip: something", "", false).unwrap();
+        assert_eq!(kind, ChangeKind::Other);
+        assert_eq!(entry.summary, "wip: something");
+    }
+
+    #[test]
+    fn merges_and_release_commits() {
+        let (kind, entry) = parse(
+            "Merge pull request #7 from someone/branch",
+            "\ndocs: describe hotfixes\n",
+            true,
+        )
+        .unwrap();
+        assert_eq!(kind, ChangeKind::Documentation);
+        assert_eq!(entry.summary, "describe hotfixes");
+        assert_eq!(entry.pr, Some(7));
+
+        assert!(parse("Merge branch 'main' into feature", "", true).is_none());
+        assert!(parse("release candidate 2 for v0.17.0", "", false).is_none());
+        assert!(parse("pre-release 0.17.0-beta.1 for v0.17.0", "", false).is_none());
+        assert!(parse("Release v0.17.0", "", false).is_none());
+        assert!(parse("Update charts to version 0.17.0", "", false).is_none());
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!(ChangeKind::from_type("kind/bug"), Some(ChangeKind::Fix));
+        assert_eq!(
+            ChangeKind::from_type("enhancement"),
+            Some(ChangeKind::Feature)
+        );
+        assert_eq!(ChangeKind::from_type("area/rgw"), None);
+    }
+
+    #[test]
+    fn render() {
+        let mut changelog = Changelog {
+            version: Version::from_str(&"0.17.0".into()).unwrap(),
+            entries: BTreeMap::new(),
+        };
+        assert_eq!(
+            changelog.render(),
+            "# Release Notes - v0.17.0\n\nNo changes.\n"
+        );
+
+        for (summary, repo) in [
+            ("fix: crash (#3)", "s3gw-ui"),
+            ("feat(rgw): object lock", "s3gw"),
+        ] {
+            let (kind, mut entry) = parse(summary, "", false).unwrap();
+            entry.repo = repo.into();
+            changelog.add(kind, entry);
+        }
+        assert_eq!(
+            changelog.render(),
+            "# Release Notes - v0.17.0\n\n\
+             ## Features\n\n- **rgw:** object lock (s3gw@0123456)\n\n\
+             ## Bug Fixes\n\n- crash (s3gw-ui#3)\n"
+        );
+    }
+}
@@ -12,11 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
 use crate::{boomln, errorln, infoln, successln, version::Version, warnln};
 
-use super::ReleaseState;
+use super::{
+    changelog::ChangelogGrouping, process::announce::AnnouncementFormat, status::WorkflowDetails,
+    Release, ReleaseState,
+};
+use crate::common::OutputFormat;
+use crate::ws::dryrun::DryRunPlan;
 
 #[derive(Clone, Copy, Debug)]
 pub enum CmdVersionError {
@@ -28,27 +33,66 @@ pub enum CmdVersionError {
 #[derive(clap::Subcommand)]
 pub enum Cmds {
     /// List releases.
-    List,
+    List(ListCommand),
     /// Release status.
     Status(StatusCommand),
     /// Sync release state.
     Sync(SyncCommand),
     /// Start a new release process.
     Start(StartCommand),
+    /// Start a patch release, cherry-picking fixes onto existing release branches.
+    Hotfix(HotfixCommand),
     /// Continue the release process.
     Continue(ContinueCommand),
     /// Finish the release process.
     Finish(FinishCommand),
-
+    /// Publish GitHub releases for a release's tags.
+    Publish(PublishCommand),
+    /// Re-run the failed jobs of the latest release candidate's workflow.
+    Rebuild(RebuildCommand),
+    /// Resume an unfinished release step.
+    Resume,
+    /// Roll back an unfinished release step.
+    Rollback,
+
+    /// Generate release notes from the changes since the previous release.
+    Changelog(ChangelogCommand),
     /// Generate release announcement.
     Announce(AnnounceCommand),
 }
 
+#[derive(clap::Args)]
+pub struct ListCommand {
+    /// Output format
+    #[arg(value_name = "FORMAT", short, long, value_enum, default_value_t)]
+    output: OutputFormat,
+}
+
 #[derive(clap::Args)]
 pub struct StatusCommand {
     /// Version for which to obtain status
     #[arg(value_name = "VERSION", short, long)]
     version: Option<String>,
+
+    /// Output format
+    #[arg(value_name = "FORMAT", short, long, value_enum, default_value_t)]
+    output: OutputFormat,
+
+    /// Watch the latest release workflow until it completes
+    #[arg(long)]
+    watch: bool,
+
+    /// Seconds to wait between polls at first, doubling up to a minute
+    #[arg(value_name = "SECONDS", long, default_value_t = 10)]
+    poll_interval: u64,
+
+    /// Show the release workflow's jobs, and which of them failed
+    #[arg(long)]
+    details: bool,
+
+    /// Show the last lines of each failed job's log (implies '--details')
+    #[arg(value_name = "LINES", long)]
+    logs: Option<usize>,
 }
 
 #[derive(clap::Args)]
@@ -64,9 +108,43 @@ pub struct StartCommand {
     #[arg(value_name = "VERSION")]
     version: String,
 
-    /// Release notes
+    /// Release notes, generated from the changes since the previous release
+    /// if not provided
     #[arg(value_name = "FILE", short, long)]
-    notes: PathBuf,
+    notes: Option<PathBuf>,
+
+    /// Cut release branches without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Show what would be done, without modifying any repository
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(clap::Args)]
+pub struct HotfixCommand {
+    /// Patch version to release (e.g., 0.17.1)
+    #[arg(value_name = "VERSION")]
+    version: String,
+
+    /// Release notes, generated from the changes since the previous release
+    /// if not provided
+    #[arg(value_name = "FILE", short, long)]
+    notes: Option<PathBuf>,
+
+    /// Commit to cherry-pick, optionally on a given repository (e.g., 's3gw:abc123')
+    #[arg(value_name = "[REPO:]SHA", long, required_unless_present = "pr")]
+    pick: Vec<String>,
+
+    /// Pull request whose commits to cherry-pick, on the 's3gw' repository
+    /// unless specified (e.g., 's3gw-ui#42')
+    #[arg(value_name = "[REPO#]NUMBER", long)]
+    pr: Vec<String>,
+
+    /// Show what would be done, without modifying any repository
+    #[arg(long)]
+    dry_run: bool,
 }
 
 #[derive(clap::Args)]
@@ -82,6 +160,18 @@ pub struct ContinueCommand {
     /// Force continuing a release regardless of previous candidate state
     #[arg(short, long)]
     force: bool,
+
+    /// Wait for the previous candidate's release workflow to complete
+    #[arg(short, long)]
+    wait: bool,
+
+    /// Seconds to wait between polls at first, doubling up to a minute
+    #[arg(value_name = "SECONDS", long, default_value_t = 10)]
+    poll_interval: u64,
+
+    /// Show what would be done, without modifying any repository
+    #[arg(long)]
+    dry_run: bool,
 }
 
 #[derive(clap::Args)]
@@ -93,6 +183,67 @@ pub struct FinishCommand {
     /// Force finishing a release regardless of previous candidae state
     #[arg(short, long)]
     force: bool,
+
+    /// Wait for the last candidate's release workflow to complete
+    #[arg(short, long)]
+    wait: bool,
+
+    /// Seconds to wait between polls at first, doubling up to a minute
+    #[arg(value_name = "SECONDS", long, default_value_t = 10)]
+    poll_interval: u64,
+
+    /// Show what would be done, without modifying any repository
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(clap::Args)]
+pub struct RebuildCommand {
+    /// Release version to rebuild (e.g., v0.17.1)
+    #[arg(value_name = "VERSION", short, long)]
+    version: Option<String>,
+
+    /// Watch the release workflow until it completes
+    #[arg(long)]
+    watch: bool,
+
+    /// Seconds to wait between polls at first, doubling up to a minute
+    #[arg(value_name = "SECONDS", long, default_value_t = 10)]
+    poll_interval: u64,
+}
+
+#[derive(clap::Args)]
+pub struct PublishCommand {
+    /// Release version, or release candidate, to publish (e.g., 0.17.1 or 0.17.1-rc2)
+    #[arg(value_name = "VERSION", short, long)]
+    version: Option<String>,
+
+    /// Also publish on repository NAME, besides 's3gw' (e.g., 's3gw-ui')
+    #[arg(value_name = "NAME", long)]
+    repo: Vec<String>,
+
+    /// Show what would be done, without publishing any release
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(clap::Args)]
+pub struct ChangelogCommand {
+    /// Release version to generate release notes for (e.g., 0.17.1)
+    #[arg(value_name = "VERSION", short, long)]
+    version: Option<String>,
+
+    /// Release to compare against, instead of the previous release
+    #[arg(value_name = "VERSION", long)]
+    from: Option<String>,
+
+    /// How to group changes
+    #[arg(value_name = "GROUPING", long, value_enum, default_value_t)]
+    group_by: ChangelogGrouping,
+
+    /// Write release notes to FILE instead of stdout
+    #[arg(value_name = "FILE", short, long)]
+    outfile: Option<PathBuf>,
 }
 
 #[derive(clap::Args)]
@@ -101,6 +252,15 @@ pub struct AnnounceCommand {
     #[arg(value_name = "VERSION", short, long)]
     version: String,
 
+    /// Announcement format
+    #[arg(value_name = "FORMAT", short, long, value_enum, default_value_t)]
+    format: AnnouncementFormat,
+
+    /// Release notes to announce, instead of the release's
+    #[arg(value_name = "FILE", short, long)]
+    changelog: Option<PathBuf>,
+
+    /// Write announcement to FILE instead of stdout
     #[arg(value_name = "FILE", short, long)]
     outfile: Option<PathBuf>,
 }
@@ -122,9 +282,22 @@ pub async fn handle_cmds(cmd: &Cmds) {
     };
 
     match cmd {
-        Cmds::List => {
+        Cmds::List(ListCommand { output }) | Cmds::Status(StatusCommand { output, .. }) => {
+            if *output != OutputFormat::Table {
+                crate::common::set_messages_to_stderr();
+            }
+        }
+        Cmds::Changelog(ChangelogCommand { outfile: None, .. })
+        | Cmds::Announce(AnnounceCommand { outfile: None, .. }) => {
+            crate::common::set_messages_to_stderr();
+        }
+        _ => {}
+    };
+
+    match cmd {
+        Cmds::List(list_cmd) => {
             log::debug!("List existing releases");
-            match crate::release::list::list(&ws) {
+            match crate::release::list::list(&ws, &list_cmd.output) {
                 Ok(()) => {}
                 Err(()) => {
                     boomln!("Unable to list releases!");
@@ -157,7 +330,17 @@ pub async fn handle_cmds(cmd: &Cmds) {
                     return;
                 }
             };
-            release.status(&version).await;
+            release
+                .status(
+                    &version,
+                    &status_cmd.output,
+                    &get_wait(status_cmd.watch, status_cmd.poll_interval),
+                    &WorkflowDetails {
+                        jobs: status_cmd.details || status_cmd.logs.is_some(),
+                        log_lines: status_cmd.logs,
+                    },
+                )
+                .await;
         }
         Cmds::Sync(sync_cmd) => {
             log::debug!("Synchronize release state");
@@ -196,8 +379,10 @@ pub async fn handle_cmds(cmd: &Cmds) {
                 }
             };
 
-            if !check_notes_file(&start_cmd.notes) {
-                return;
+            if let Some(n) = &start_cmd.notes {
+                if !check_notes_file(&n) {
+                    return;
+                }
             }
 
             if let Some(s) = &release.state {
@@ -214,7 +399,13 @@ pub async fn handle_cmds(cmd: &Cmds) {
                 return;
             }
 
-            match crate::release::process::start::start(&mut release, &version, &start_cmd.notes) {
+            let plan = maybe_dry_run(&mut release, start_cmd.dry_run);
+            match crate::release::process::start::start(
+                &mut release,
+                &version,
+                &start_cmd.notes,
+                start_cmd.yes,
+            ) {
                 Ok(()) => {
                     successln!("Release for version {} successfully started!", &version);
                 }
@@ -222,6 +413,56 @@ pub async fn handle_cmds(cmd: &Cmds) {
                     boomln!("Error starting new release: {}", err);
                 }
             };
+            if let Some(p) = plan {
+                p.print();
+            }
+        }
+        Cmds::Hotfix(hotfix_cmd) => {
+            infoln!("Start a hotfix release for version {}", hotfix_cmd.version);
+            let version = match crate::version::Version::from_str(&hotfix_cmd.version) {
+                Ok(v) => v,
+                Err(_) => {
+                    errorln!("Error parsing provided version!");
+                    return;
+                }
+            };
+
+            if let Some(n) = &hotfix_cmd.notes {
+                if !check_notes_file(&n) {
+                    return;
+                }
+            }
+
+            if let Some(s) = &release.state {
+                warnln!("On-going release detected!");
+                infoln!(
+                    "Detected version {}, attempting to start {}!",
+                    s.release_version,
+                    version
+                );
+                return;
+            }
+
+            let plan = maybe_dry_run(&mut release, hotfix_cmd.dry_run);
+            match crate::release::process::hotfix::hotfix(
+                &mut release,
+                &version,
+                &hotfix_cmd.notes,
+                &hotfix_cmd.pick,
+                &hotfix_cmd.pr,
+            )
+            .await
+            {
+                Ok(()) => {
+                    successln!("Hotfix release {} successfully started!", &version);
+                }
+                Err(err) => {
+                    boomln!("Error starting hotfix release: {}", err);
+                }
+            };
+            if let Some(p) = plan {
+                p.print();
+            }
         }
         Cmds::Continue(continue_cmd) => {
             let relver = match check_version_against_state(&release.state, &continue_cmd.version) {
@@ -243,11 +484,13 @@ pub async fn handle_cmds(cmd: &Cmds) {
             }
 
             infoln!("Continue a release process for version {}", relver);
+            let plan = maybe_dry_run(&mut release, continue_cmd.dry_run);
             match crate::release::process::cont::continue_release(
                 &mut release,
                 &relver,
                 &continue_cmd.notes,
                 continue_cmd.force,
+                &get_wait(continue_cmd.wait, continue_cmd.poll_interval),
             )
             .await
             {
@@ -258,6 +501,9 @@ pub async fn handle_cmds(cmd: &Cmds) {
                     boomln!("Error continuing release: {}", err);
                 }
             };
+            if let Some(p) = plan {
+                p.print();
+            }
         }
         Cmds::Finish(finish_cmd) => {
             let relver = match check_version_against_state(&release.state, &finish_cmd.version) {
@@ -273,8 +519,14 @@ pub async fn handle_cmds(cmd: &Cmds) {
             };
 
             infoln!("Finish release process for version {}", relver);
-            match crate::release::process::finish::finish(&mut release, &relver, finish_cmd.force)
-                .await
+            let plan = maybe_dry_run(&mut release, finish_cmd.dry_run);
+            match crate::release::process::finish::finish(
+                &mut release,
+                &relver,
+                finish_cmd.force,
+                &get_wait(finish_cmd.wait, finish_cmd.poll_interval),
+            )
+            .await
             {
                 Ok(()) => {
                     successln!("Finished release {}!", relver);
@@ -283,6 +535,120 @@ pub async fn handle_cmds(cmd: &Cmds) {
                     boomln!("Error finishing release: {}", err);
                 }
             };
+            if let Some(p) = plan {
+                p.print();
+            }
+        }
+        Cmds::Publish(publish_cmd) => {
+            let version = match check_version_against_state(&release.state, &publish_cmd.version) {
+                Ok(v) => v,
+                Err(CmdVersionError::VersionNotProvidedError) => {
+                    errorln!("Must provide a version to publish, or have a started release!");
+                    return;
+                }
+                Err(_) => {
+                    // all other errors are output by the check function.
+                    return;
+                }
+            };
+
+            infoln!("Publish GitHub releases for version {}", version);
+            let plan = maybe_dry_run(&mut release, publish_cmd.dry_run);
+            match crate::release::process::publish::publish(&release, &version, &publish_cmd.repo)
+                .await
+            {
+                Ok(()) => {
+                    successln!("Published GitHub releases for {}!", version);
+                }
+                Err(err) => {
+                    boomln!("Error publishing release: {}", err);
+                }
+            };
+            if let Some(p) = plan {
+                p.print();
+            }
+        }
+        Cmds::Rebuild(rebuild_cmd) => {
+            let version = match check_version_against_state(&release.state, &rebuild_cmd.version) {
+                Ok(v) => v,
+                Err(CmdVersionError::VersionNotProvidedError) => {
+                    errorln!("Must provide a version to rebuild, or have a started release!");
+                    return;
+                }
+                Err(_) => {
+                    // all other errors are output by the check function.
+                    return;
+                }
+            };
+
+            infoln!("Rebuild release version {}", version);
+            match crate::release::process::rebuild::rebuild(
+                &release,
+                &version,
+                &get_wait(rebuild_cmd.watch, rebuild_cmd.poll_interval),
+            )
+            .await
+            {
+                Ok(()) => {}
+                Err(err) => {
+                    boomln!("Error rebuilding release: {}", err);
+                }
+            };
+        }
+        Cmds::Resume => {
+            match crate::release::process::resume::resume(&mut release).await {
+                Ok(()) => {}
+                Err(err) => {
+                    boomln!("Error resuming release: {}", err);
+                    infoln!("Use 'arc rel resume' to retry, or 'arc rel rollback' to undo.");
+                }
+            };
+        }
+        Cmds::Rollback => {
+            match crate::release::process::resume::rollback(&mut release) {
+                Ok(()) => {}
+                Err(err) => {
+                    boomln!("Error rolling back release: {}", err);
+                }
+            };
+        }
+        Cmds::Changelog(changelog_cmd) => {
+            let relver = match check_version_against_state(&release.state, &changelog_cmd.version) {
+                Ok(v) => v,
+                Err(CmdVersionError::VersionNotProvidedError) => {
+                    errorln!("Must provide a version, or have a release state initiated!");
+                    return;
+                }
+                Err(_) => {
+                    // all other errors are output by the check function.
+                    return;
+                }
+            };
+            let from = match &changelog_cmd.from {
+                None => None,
+                Some(v) => match Version::from_str(&v) {
+                    Ok(v) => Some(v),
+                    Err(()) => {
+                        boomln!("Unable to parse provided version '{}'", v);
+                        return;
+                    }
+                },
+            };
+
+            match crate::release::changelog::changelog(
+                &release,
+                &relver,
+                &from,
+                &changelog_cmd.group_by,
+                &changelog_cmd.outfile,
+            )
+            .await
+            {
+                Ok(()) => {}
+                Err(err) => {
+                    boomln!("Error generating release notes for '{}': {}", relver, err);
+                }
+            };
         }
         Cmds::Announce(announce_cmd) => {
             let relver = match Version::from_str(&announce_cmd.version) {
@@ -299,6 +665,8 @@ pub async fn handle_cmds(cmd: &Cmds) {
             match crate::release::process::announce::announce(
                 &mut release,
                 &relver,
+                &announce_cmd.format,
+                &announce_cmd.changelog,
                 &announce_cmd.outfile,
             ) {
                 Ok(()) => {}
@@ -308,13 +676,33 @@ pub async fn handle_cmds(cmd: &Cmds) {
                 }
             }
         }
-        Cmds::List => {
+        Cmds::List(_) => {
             boomln!("Should not have reached here!");
             return;
         }
     };
 }
 
+/// Put the release's workspace in dry-run mode if 'dry_run' is true, returning
+/// the plan on which actions will be recorded.
+///
+fn maybe_dry_run(release: &mut Release, dry_run: bool) -> Option<DryRunPlan> {
+    if !dry_run {
+        return None;
+    }
+    warnln!("Dry-run mode: no repository will be modified.");
+    Some(release.ws.set_dry_run())
+}
+
+/// Obtain the interval to start polling at if 'wait' is true.
+///
+fn get_wait(wait: bool, poll_interval: u64) -> Option<Duration> {
+    match wait {
+        true => Some(Duration::from_secs(poll_interval)),
+        false => None,
+    }
+}
+
 fn check_notes_file(notes: &PathBuf) -> bool {
     if !notes.exists() {
         errorln!(
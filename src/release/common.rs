@@ -15,17 +15,187 @@
 use std::{
     collections::BTreeMap,
     fmt::{Display, Write},
+    path::PathBuf,
 };
 
 use crate::{
+    errorln,
+    forge::{Forge, PullRequest},
+    release::errors::{ReleaseError, ReleaseResult},
+    successln,
     version::Version,
-    ws::{repository::Repository, workspace::Workspace},
+    ws::{dryrun::DryRunAction, repository::Repository, workspace::Workspace},
 };
 
+/// Obtain the token to authenticate with repository 'repo''s forge: the
+/// repository's own token, if configured, or else the user's GitHub token.
+///
+pub fn get_forge_token(ws: &Workspace, repo: &Repository) -> ReleaseResult<String> {
+    let cfg = match &repo.config.github {
+        Some(c) => c,
+        None => {
+            errorln!("Forge not configured for '{}'", repo.name);
+            return Err(ReleaseError::UnknownError);
+        }
+    };
+    let token = match &cfg.token {
+        Some(source) => source.resolve(),
+        None => ws.config.user.get_github_token(),
+    };
+    match token {
+        Ok(v) => Ok(v),
+        Err(err) => {
+            errorln!(
+                "Unable to obtain {} token for '{}': {}",
+                cfg.kind,
+                repo.name,
+                err
+            );
+            Err(ReleaseError::UnknownError)
+        }
+    }
+}
+
+/// Obtain the forge hosting repository 'repo'.
+///
+pub fn get_forge(ws: &Workspace, repo: &Repository) -> ReleaseResult<Box<dyn Forge>> {
+    let token = get_forge_token(&ws, &repo)?;
+    match &repo.config.github {
+        Some(cfg) => Ok(crate::forge::from_config(&cfg, &token)),
+        None => Err(ReleaseError::UnknownError),
+    }
+}
+
+/// Push 'branch' to repository 'repo', and open a pull request titled 'title'
+/// from it against the repository's default branch, reflecting release version
+/// 'relver'.
+///
+pub async fn create_pull_request(
+    ws: &Workspace,
+    repo: &Repository,
+    branch: &String,
+    title: &String,
+    relver: &Version,
+) -> ReleaseResult<()> {
+    // push branch to repository
+    let refspec = format!("refs/heads/{}:refs/heads/{}", branch, branch);
+    if let Err(err) = repo.push(&refspec) {
+        log::error!("Unable to push '{}' to remote repository: {}", branch, err);
+        return Err(ReleaseError::PushingError);
+    }
+
+    // open pull request against default branch
+    let default_branch = match repo.get_default_branch_name() {
+        Ok(v) => v,
+        Err(err) => {
+            log::error!(
+                "Unable to obtain default branch name for repository '{}': {}",
+                repo.name,
+                err
+            );
+            return Err(ReleaseError::UnknownError);
+        }
+    };
+    let user_config = &ws.config.user;
+
+    let req = PullRequest {
+        title: title.clone(),
+        body: format!(
+            "Updates '{}' to reflect v{}\n\nSigned-off-by: {} \\<{}>",
+            default_branch, relver, user_config.name, user_config.email
+        ),
+        head: branch.clone(),
+        base: default_branch.clone(),
+    };
+
+    if repo.dry_run_record(DryRunAction::PullRequest {
+        title: req.title.clone(),
+        head: req.head.clone(),
+        base: req.base.clone(),
+    }) {
+        return Ok(());
+    }
+
+    let forge = get_forge(&ws, &repo)?;
+    let (url, number) = match forge.create_pull_request(&req).await {
+        Ok(r) => (r.url, r.number),
+        Err(err) => {
+            log::error!(
+                "Unable to open pull request for '{}' against '{}' on '{}': {}",
+                branch,
+                default_branch,
+                repo.name,
+                err
+            );
+            return Err(ReleaseError::UnknownError);
+        }
+    };
+    successln!("Opened Pull Request {} at {}", number, url);
+
+    Ok(())
+}
+
+/// Obtain the release notes for 'version' from the 's3gw' repository, as of
+/// 'version''s tag, or release branch if not yet tagged, if they exist.
+///
+pub fn get_release_notes(ws: &Workspace, version: &Version) -> ReleaseResult<Option<String>> {
+    let repo = ws.repos.main();
+    if version.patch.is_none() {
+        errorln!("Release notes require a full version, got '{}'", version);
+        return Err(ReleaseError::UnknownError);
+    }
+    let refspec = match repo.get_version_refspec(&version) {
+        Ok(v) => v,
+        Err(err) => {
+            errorln!("Unable to find v{} on '{}': {}", version, repo.name, err);
+            return Err(ReleaseError::UnknownError);
+        }
+    };
+    let path = PathBuf::from(format!(
+        "docs/release-notes/s3gw-v{}.md",
+        version.get_release_version()
+    ));
+    match repo.read_file(&refspec, &path) {
+        Ok(v) => Ok(v),
+        Err(err) => {
+            errorln!(
+                "Unable to read '{}' on '{}': {}",
+                path.display(),
+                repo.name,
+                err
+            );
+            Err(ReleaseError::UnknownError)
+        }
+    }
+}
+
+/// Obtain the head of repository 'repo''s release branch for 'relver', to be
+/// recorded in the journal as the commit preceding any release changes. When in
+/// dry-run mode the release branch may not have been created, in which case a
+/// placeholder is returned instead.
+///
+pub fn get_release_branch_head(repo: &Repository, relver: &Version) -> ReleaseResult<String> {
+    match repo.get_release_branch_head(&relver) {
+        Ok(v) => Ok(v),
+        Err(_) if repo.is_dry_run() => Ok(format!(
+            "refs/heads/{}",
+            relver.to_str_fmt(&repo.config.release_branch_format)
+        )),
+        Err(err) => {
+            errorln!(
+                "Unable to obtain '{}' release branch head: {}",
+                repo.name,
+                err
+            );
+            Err(ReleaseError::UnknownError)
+        }
+    }
+}
+
 /// Obtains versions corresponding to release 'relver' from the 's3gw' repository.
 ///
-pub fn get_release_versions(ws: &Workspace, relver: &Version) -> BTreeMap<u64, Version> {
-    get_release_versions_from_repo(&ws.repos.s3gw, &relver)
+pub fn get_release_versions(ws: &Workspace, relver: &Version) -> BTreeMap<Version, Version> {
+    get_release_versions_from_repo(&ws.repos.main(), &relver)
 }
 
 /// Obtain versions corresponding to release 'relver' from the provided repository.
@@ -33,26 +203,20 @@ pub fn get_release_versions(ws: &Workspace, relver: &Version) -> BTreeMap<u64, V
 pub fn get_release_versions_from_repo(
     repo: &Repository,
     relver: &Version,
-) -> BTreeMap<u64, Version> {
-    let min_id = relver.min().get_version_id();
-    let max_id = relver.max().get_version_id();
-
+) -> BTreeMap<Version, Version> {
     let version_tree = &repo.get_versions().unwrap();
-    let avail = version_tree.range((
-        std::ops::Bound::Included(min_id),
-        std::ops::Bound::Included(max_id),
-    ));
+    let avail = version_tree.range(relver.get_range());
 
-    let mut versions = BTreeMap::<u64, Version>::new();
-    for (vid, v) in avail {
-        versions.insert(vid.clone(), v.clone());
+    let mut versions = BTreeMap::<Version, Version>::new();
+    for (ver, v) in avail {
+        versions.insert(ver.clone(), v.clone());
     }
 
     versions
 }
 
 pub struct StatusTable {
-    pub entries: BTreeMap<u64, StatusTableEntry>,
+    pub entries: BTreeMap<Version, StatusTableEntry>,
 }
 
 pub struct StatusTableEntry {
@@ -143,16 +307,15 @@ fn cleanup_formatted_str(s: &String) -> String {
 impl StatusTable {
     pub fn new_entry(self: &mut Self, ver: &Version) -> &mut StatusTableEntry {
         let entry = StatusTableEntry::new(&ver);
-        self.entries.insert(ver.get_version_id(), entry);
-        self.entries.get_mut(&ver.get_version_id()).unwrap()
+        self.entries.insert(ver.clone(), entry);
+        self.entries.get_mut(&ver).unwrap()
     }
 
     pub fn _add_record(self: &mut Self, ver: &Version, rec: &String) {
-        let verid = ver.get_version_id();
-        let entry = if !self.entries.contains_key(&verid) {
+        let entry = if !self.entries.contains_key(&ver) {
             self.new_entry(ver)
         } else {
-            self.entries.get_mut(&verid).unwrap()
+            self.entries.get_mut(&ver).unwrap()
         };
         entry.add_record(&rec);
     }
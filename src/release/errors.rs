@@ -27,11 +27,18 @@ pub enum ReleaseError {
     SubmoduleError,
     TaggingError,
     SyncError,
+    JournalError,
+    UnfinishedReleaseError,
+    BranchNotFoundError,
+    CherryPickError,
+    PublishError,
 
     // github release build process
     ReleaseBuildOnGoingError,
     ReleaseBuildFailedError,
     ReleaseBuildNotFoundError,
+    ReleaseBuildSucceededError,
+    RebuildError,
 
     UnknownError,
 }
@@ -50,10 +57,17 @@ impl Display for ReleaseError {
             ReleaseError::SubmoduleError => "submodule error",
             ReleaseError::TaggingError => "error tagging release",
             ReleaseError::SyncError => "error synchronizing",
+            ReleaseError::JournalError => "release journal error",
+            ReleaseError::UnfinishedReleaseError => "unfinished release found",
+            ReleaseError::BranchNotFoundError => "release branch not found",
+            ReleaseError::CherryPickError => "error cherry-picking commits",
+            ReleaseError::PublishError => "error publishing release",
             // github release build process
             ReleaseError::ReleaseBuildOnGoingError => "release build in progress",
             ReleaseError::ReleaseBuildFailedError => "release build failed",
             ReleaseError::ReleaseBuildNotFoundError => "release build not found",
+            ReleaseError::ReleaseBuildSucceededError => "release build already succeeded",
+            ReleaseError::RebuildError => "error re-running release build",
             // unknown error
             ReleaseError::UnknownError => "unknown error",
         })
@@ -70,6 +84,8 @@ pub enum ChartsError {
     CommitError,
     MissingFinalBranch,
     PublishError,
+    CherryPickError,
+    PullRequestError,
 
     UnknownError,
 }
@@ -83,6 +99,8 @@ impl Display for ChartsError {
             ChartsError::CommitError => "error committing chart file",
             ChartsError::MissingFinalBranch => "final branch not defined for repository",
             ChartsError::PublishError => "error publishing chart",
+            ChartsError::CherryPickError => "error cherry-picking chart update",
+            ChartsError::PullRequestError => "error opening chart pull request",
             ChartsError::UnknownError => "unknown error",
         })
     }
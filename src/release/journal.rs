@@ -0,0 +1,368 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fmt::Display, path::PathBuf};
+
+use crate::{
+    errorln, infoln,
+    release::errors::{ReleaseError, ReleaseResult},
+    successln,
+    version::Version,
+    warnln,
+    ws::{repository::Repository, workspace::Workspace},
+};
+
+/// A single step of the release process, as performed on a given repository.
+///
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq)]
+pub enum JournalStep {
+    BranchCreated {
+        branch: String,
+    },
+    TagCreated {
+        tag: String,
+    },
+    BranchPushed {
+        branch: String,
+    },
+    TagPushed {
+        tag: String,
+    },
+    Committed {
+        branch: String,
+        previous: String,
+    },
+    CherryPicked {
+        branch: String,
+        previous: String,
+        commits: Vec<String>,
+    },
+    ChartPublished {
+        branch: String,
+    },
+    PullRequestOpened {
+        branch: String,
+    },
+}
+
+impl Display for JournalStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JournalStep::BranchCreated { branch } => write!(f, "created branch '{}'", branch),
+            JournalStep::TagCreated { tag } => write!(f, "created tag '{}'", tag),
+            JournalStep::BranchPushed { branch } => write!(f, "pushed branch '{}'", branch),
+            JournalStep::TagPushed { tag } => write!(f, "pushed tag '{}'", tag),
+            JournalStep::Committed { branch, previous } => {
+                write!(f, "committed to '{}' (previously at {})", branch, previous)
+            }
+            JournalStep::CherryPicked {
+                branch,
+                previous,
+                commits,
+            } => {
+                write!(
+                    f,
+                    "cherry-picked {} commit(s) onto '{}' (previously at {})",
+                    commits.len(),
+                    branch,
+                    previous
+                )
+            }
+            JournalStep::ChartPublished { branch } => {
+                write!(f, "published chart to '{}'", branch)
+            }
+            JournalStep::PullRequestOpened { branch } => {
+                write!(f, "opened pull request against '{}'", branch)
+            }
+        }
+    }
+}
+
+impl JournalStep {
+    /// Whether this step refers to the same action as 'other'. Commits and
+    /// cherry-picks are identified by their branch alone, given the previous
+    /// HEAD is only known before committing.
+    ///
+    fn matches(self: &Self, other: &JournalStep) -> bool {
+        match (self, other) {
+            (
+                JournalStep::Committed { branch: a, .. },
+                JournalStep::Committed { branch: b, .. },
+            ) => a == b,
+            (
+                JournalStep::CherryPicked { branch: a, .. },
+                JournalStep::CherryPicked { branch: b, .. },
+            ) => a == b,
+            _ => self == other,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct JournalEntry {
+    pub repo: String,
+    pub step: JournalStep,
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Records each completed step of an on-going release for version 'tagver',
+/// so that a failed release can be either resumed or rolled back. The journal
+/// is kept at '.arc/journal.json', next to the release state, and is removed
+/// once all steps have been completed.
+///
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct ReleaseJournal {
+    pub relver: Version,
+    pub tagver: Version,
+    pub notes: Option<PathBuf>,
+    pub entries: Vec<JournalEntry>,
+
+    /// where the journal is persisted. If None, the journal is kept in memory
+    /// only (e.g., when in dry-run mode).
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl ReleaseJournal {
+    fn get_path(ws: &Workspace) -> PathBuf {
+        ws.get_config_dir().join("journal.json")
+    }
+
+    /// Load an existing journal from workspace 'ws', if any.
+    ///
+    pub fn load(ws: &Workspace) -> ReleaseResult<Option<ReleaseJournal>> {
+        let path = ReleaseJournal::get_path(&ws);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let f = match std::fs::File::open(&path) {
+            Ok(f) => f,
+            Err(err) => {
+                log::error!("Error opening journal at '{}': {}", path.display(), err);
+                return Err(ReleaseError::JournalError);
+            }
+        };
+        let mut journal: ReleaseJournal = match serde_json::from_reader(f) {
+            Ok(j) => j,
+            Err(err) => {
+                log::error!("Error reading journal from '{}': {}", path.display(), err);
+                return Err(ReleaseError::JournalError);
+            }
+        };
+        journal.path = Some(path);
+        Ok(Some(journal))
+    }
+
+    /// Begin, or pick up, the journal for releasing 'tagver' from release
+    /// 'relver'. Fails if a journal for a different version exists, in which
+    /// case it must first be resumed or rolled back.
+    ///
+    pub fn begin(
+        ws: &Workspace,
+        relver: &Version,
+        tagver: &Version,
+        notes: Option<&PathBuf>,
+    ) -> ReleaseResult<ReleaseJournal> {
+        if let Some(mut journal) = ReleaseJournal::load(&ws)? {
+            if &journal.tagver != tagver {
+                errorln!("Unfinished release steps found for '{}'!", journal.tagver);
+                infoln!("Use 'arc rel resume' or 'arc rel rollback' first.");
+                return Err(ReleaseError::UnfinishedReleaseError);
+            }
+            if ws.dry_run.is_some() {
+                journal.path = None;
+            }
+            if let Some(n) = notes {
+                journal.notes = Some(n.clone());
+            }
+            log::debug!("Picking up release journal for '{}'", tagver);
+            return Ok(journal);
+        }
+
+        let journal = ReleaseJournal {
+            relver: relver.clone(),
+            tagver: tagver.clone(),
+            notes: notes.map(|n| std::fs::canonicalize(&n).unwrap_or(n.clone())),
+            entries: vec![],
+            path: match &ws.dry_run {
+                None => Some(ReleaseJournal::get_path(&ws)),
+                Some(_) => None,
+            },
+        };
+        journal.write()?;
+        Ok(journal)
+    }
+
+    fn write(self: &Self) -> ReleaseResult<()> {
+        let path = match &self.path {
+            None => return Ok(()),
+            Some(p) => p,
+        };
+        let f = match std::fs::File::options()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(&path)
+        {
+            Ok(f) => f,
+            Err(err) => {
+                log::error!(
+                    "Error opening journal at '{}' for writing: {}",
+                    path.display(),
+                    err
+                );
+                return Err(ReleaseError::JournalError);
+            }
+        };
+        match serde_json::to_writer_pretty(f, &self) {
+            Ok(()) => Ok(()),
+            Err(err) => {
+                log::error!("Error writing journal to '{}': {}", path.display(), err);
+                Err(ReleaseError::JournalError)
+            }
+        }
+    }
+
+    /// Whether 'step' has already been completed for repository 'repo'.
+    ///
+    pub fn has(self: &Self, repo: &Repository, step: &JournalStep) -> bool {
+        self.entries
+            .iter()
+            .any(|e| e.repo == repo.name && e.step.matches(&step))
+    }
+
+    /// Run 'f' to perform 'step' on repository 'repo', unless it has already
+    /// been completed, recording it on success.
+    ///
+    pub fn run<F>(self: &mut Self, repo: &Repository, step: JournalStep, f: F) -> ReleaseResult<()>
+    where
+        F: FnOnce() -> ReleaseResult<()>,
+    {
+        if self.has(&repo, &step) {
+            log::debug!("journal: {}: skip '{}', already done", repo.name, step);
+            return Ok(());
+        }
+        f()?;
+        self.record(&repo, step)
+    }
+
+    /// Record 'step' as completed for repository 'repo'.
+    ///
+    pub fn record(self: &mut Self, repo: &Repository, step: JournalStep) -> ReleaseResult<()> {
+        log::debug!("journal: {}: {}", repo.name, step);
+        self.entries.push(JournalEntry {
+            repo: repo.name.clone(),
+            step,
+            at: chrono::Utc::now(),
+        });
+        self.write()
+    }
+
+    /// All steps have been completed. Remove the journal.
+    ///
+    pub fn finish(self: Self) -> ReleaseResult<()> {
+        let path = match &self.path {
+            None => return Ok(()),
+            Some(p) => p,
+        };
+        if let Err(err) = std::fs::remove_file(&path) {
+            log::error!("Error removing journal at '{}': {}", path.display(), err);
+            return Err(ReleaseError::JournalError);
+        }
+        log::debug!("Release journal for '{}' finished", self.tagver);
+        Ok(())
+    }
+
+    /// Undo the steps recorded in this journal, most recent first. Tags and
+    /// branches created are deleted, both locally and from the remote, and
+    /// local commits are dropped. Steps that can't be safely undone, such as
+    /// commits pushed to pre-existing branches, are reported instead.
+    ///
+    pub fn rollback(self: Self, ws: &Workspace) -> ReleaseResult<()> {
+        let created_branches: Vec<(&String, &String)> = self
+            .entries
+            .iter()
+            .filter_map(|e| match &e.step {
+                JournalStep::BranchCreated { branch } => Some((&e.repo, branch)),
+                _ => None,
+            })
+            .collect();
+
+        let mut failed = false;
+        for entry in self.entries.iter().rev() {
+            let repo = match ws.repos.get(&entry.repo) {
+                Some(r) => r,
+                None => {
+                    errorln!("Unknown repository '{}' in journal!", entry.repo);
+                    failed = true;
+                    continue;
+                }
+            };
+
+            infoln!("Rolling back {}: {}", repo.name, entry.step);
+            let res = match &entry.step {
+                JournalStep::TagCreated { tag } => repo.delete_local_tag(&tag),
+                JournalStep::TagPushed { tag } => {
+                    repo.delete_remote_ref(&format!("refs/tags/{}", tag))
+                }
+                JournalStep::BranchCreated { branch } => repo.delete_local_branch(&branch),
+                JournalStep::BranchPushed { branch } => {
+                    if created_branches.contains(&(&entry.repo, branch)) {
+                        repo.delete_remote_ref(&format!("refs/heads/{}", branch))
+                    } else {
+                        warnln!(
+                            "Commits pushed to '{}' on '{}' must be reverted manually.",
+                            branch,
+                            repo.name
+                        );
+                        Ok(())
+                    }
+                }
+                JournalStep::Committed { branch, previous }
+                | JournalStep::CherryPicked {
+                    branch, previous, ..
+                } => repo.reset_branch(&branch, &previous),
+                JournalStep::ChartPublished { branch } => {
+                    warnln!(
+                        "Chart published to '{}' on '{}' must be reverted manually.",
+                        branch,
+                        repo.name
+                    );
+                    Ok(())
+                }
+                JournalStep::PullRequestOpened { branch } => {
+                    warnln!(
+                        "Pull request against '{}' on '{}' must be closed manually.",
+                        branch,
+                        repo.name
+                    );
+                    Ok(())
+                }
+            };
+            if let Err(err) = res {
+                errorln!("Unable to roll back {}: {}", entry.step, err);
+                failed = true;
+            }
+        }
+
+        if failed {
+            errorln!("Rollback of '{}' incomplete, journal kept.", self.tagver);
+            return Err(ReleaseError::JournalError);
+        }
+
+        successln!("Rolled back '{}'", self.tagver);
+        self.finish()
+    }
+}
@@ -16,13 +16,14 @@ use std::collections::BTreeMap;
 
 use tabled::settings::{Merge, Style};
 
+use crate::common::OutputFormat;
 use crate::version::Version;
 use crate::ws::workspace::Workspace;
 use crate::{boomln, errorln, infoln};
 
 struct ReleaseVersionTreeEntry {
     pub release: Version,
-    pub by_tag: BTreeMap<u64, ReleaseTagEntry>,
+    pub by_tag: BTreeMap<Version, ReleaseTagEntry>,
 }
 
 struct ReleaseTagEntry {
@@ -30,8 +31,28 @@ struct ReleaseTagEntry {
     pub repos: Vec<String>,
 }
 
-/// List releases in a given workspace 'ws'.
-pub fn list(ws: &Workspace) -> Result<(), ()> {
+/// Machine-readable representation of the release version tree.
+///
+#[derive(serde::Serialize)]
+struct ReleaseListOutput {
+    repositories: Vec<String>,
+    releases: Vec<ReleaseListEntry>,
+}
+
+#[derive(serde::Serialize)]
+struct ReleaseListEntry {
+    release: String,
+    tags: Vec<ReleaseListTagEntry>,
+}
+
+#[derive(serde::Serialize)]
+struct ReleaseListTagEntry {
+    version: String,
+    repositories: Vec<String>,
+}
+
+/// List releases in a given workspace 'ws', in the provided output 'format'.
+pub fn list(ws: &Workspace, format: &OutputFormat) -> Result<(), ()> {
     infoln!("List releases on workspace");
 
     // sync workspace first
@@ -45,7 +66,7 @@ pub fn list(ws: &Workspace) -> Result<(), ()> {
 
     let repos = ws.repos.as_vec();
 
-    let mut version_tree = BTreeMap::<u64, ReleaseVersionTreeEntry>::new();
+    let mut version_tree = BTreeMap::<Version, ReleaseVersionTreeEntry>::new();
 
     for repo in &repos {
         let releases = match repo.get_releases() {
@@ -61,24 +82,21 @@ pub fn list(ws: &Workspace) -> Result<(), ()> {
         };
 
         for (_, base_ver) in &releases {
-            let base_ver_id = base_ver.version.get_version_id();
-            if !version_tree.contains_key(&base_ver_id) {
+            if !version_tree.contains_key(&base_ver.version) {
                 version_tree.insert(
-                    base_ver_id.clone(),
+                    base_ver.version.clone(),
                     ReleaseVersionTreeEntry {
                         release: base_ver.version.clone(),
-                        by_tag: BTreeMap::<u64, ReleaseTagEntry>::new(),
+                        by_tag: BTreeMap::<Version, ReleaseTagEntry>::new(),
                     },
                 );
             }
-            let tag_tree = &mut version_tree.get_mut(&base_ver_id).unwrap().by_tag;
+            let tag_tree = &mut version_tree.get_mut(&base_ver.version).unwrap().by_tag;
             for (_, release) in &base_ver.releases {
                 for (_, tagver) in &release.versions {
-                    let tagver_id = tagver.get_version_id();
-
-                    if !tag_tree.contains_key(&tagver_id) {
+                    if !tag_tree.contains_key(tagver) {
                         tag_tree.insert(
-                            tagver_id.clone(),
+                            tagver.clone(),
                             ReleaseTagEntry {
                                 version: tagver.clone(),
                                 repos: vec![],
@@ -86,7 +104,7 @@ pub fn list(ws: &Workspace) -> Result<(), ()> {
                         );
                     }
                     tag_tree
-                        .get_mut(&tagver_id)
+                        .get_mut(tagver)
                         .unwrap()
                         .repos
                         .push(repo.name.clone());
@@ -96,13 +114,41 @@ pub fn list(ws: &Workspace) -> Result<(), ()> {
     }
 
     let repo_names = repos.iter().map(|e| e.name.clone()).collect();
-    print_version_table(&repo_names, &version_tree);
-    Ok(())
+    match format {
+        OutputFormat::Table => {
+            print_version_table(&repo_names, &version_tree);
+            Ok(())
+        }
+        _ => crate::common::print_structured(format, &to_list_output(&repo_names, &version_tree)),
+    }
+}
+
+fn to_list_output(
+    repo_names: &Vec<String>,
+    releases: &BTreeMap<Version, ReleaseVersionTreeEntry>,
+) -> ReleaseListOutput {
+    ReleaseListOutput {
+        repositories: repo_names.clone(),
+        releases: releases
+            .values()
+            .map(|relver| ReleaseListEntry {
+                release: relver.release.get_base_version_str(),
+                tags: relver
+                    .by_tag
+                    .values()
+                    .map(|tag| ReleaseListTagEntry {
+                        version: tag.version.get_version_str(),
+                        repositories: tag.repos.clone(),
+                    })
+                    .collect(),
+            })
+            .collect(),
+    }
 }
 
 fn print_version_table(
     repo_names: &Vec<String>,
-    releases: &BTreeMap<u64, ReleaseVersionTreeEntry>,
+    releases: &BTreeMap<Version, ReleaseVersionTreeEntry>,
 ) {
     let mut builder = tabled::builder::Builder::default();
     let headers = std::iter::once(String::from("release")).chain(repo_names.clone());
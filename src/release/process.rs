@@ -16,6 +16,10 @@ pub mod announce;
 pub mod charts;
 pub mod cont;
 pub mod finish;
+pub mod hotfix;
+pub mod publish;
+pub mod rebuild;
+pub mod resume;
 pub mod start;
 pub mod submodules;
 pub mod validate;
@@ -13,45 +13,273 @@
 // limitations under the License.
 
 use handlebars::Handlebars;
-use std::{collections::HashMap, path::PathBuf};
+use std::path::PathBuf;
 
 use crate::{
-    release::{errors::ReleaseResult, Release},
+    errorln, infoln,
+    release::{
+        changelog, common,
+        errors::{ReleaseError, ReleaseResult},
+        Release,
+    },
+    successln,
     version::Version,
+    warnln,
+    ws::workspace::Workspace,
 };
 
-pub fn announce(
-    _release: &mut Release,
-    version: &Version,
-    _outfile: &Option<PathBuf>,
-) -> ReleaseResult<()> {
-    let mut hb = Handlebars::new();
-    let tmpl_str = "
-The s3gw team is {{mood}} to announce the release of S3 Gateway v{{version}}!
-This release includes a few exciting changes, most notably:
+/// Formats an announcement can be rendered in, each with its own template.
+///
+#[derive(Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum AnnouncementFormat {
+    /// Plain text email, to the mailing list
+    #[default]
+    Email,
+    /// Slack-style markdown
+    Slack,
+    /// Body for a GitHub release
+    Github,
+}
+
+impl AnnouncementFormat {
+    fn name(self: &Self) -> &'static str {
+        match self {
+            AnnouncementFormat::Email => "email",
+            AnnouncementFormat::Slack => "slack",
+            AnnouncementFormat::Github => "github",
+        }
+    }
+
+    fn default_template(self: &Self) -> &'static str {
+        match self {
+            AnnouncementFormat::Email => EMAIL_TEMPLATE,
+            AnnouncementFormat::Slack => SLACK_TEMPLATE,
+            AnnouncementFormat::Github => GITHUB_TEMPLATE,
+        }
+    }
+}
+
+const EMAIL_TEMPLATE: &str = "\
+Subject: [ANN] S3 Gateway v{{version}} released
+
+The s3gw team is happy to announce the release of S3 Gateway v{{version}}!
+
+{{notes}}
 
-{{changelog}}
-    
 Get the container images from:
-    
-    quay.io/s3gw/s3gw:v{{version}}
-    quay.io/s3gw/s3gw-ui:v{{version}}
-        
-or through our Helm Chart at https://artifacthub.io/packages/helm/s3gw/s3gw/{{version}}
 
-For more information, check our changelog at
+{{#each images}}
+    {{reference}}
+{{/each}}
+{{#if chart_version}}
+
+or through our Helm Chart at https://artifacthub.io/packages/helm/s3gw/s3gw/{{chart_version}}
+{{/if}}
+
+For more information, check our release notes at
 
     https://s3gw-docs.readthedocs.io/en/main/release-notes/s3gw-v{{version}}/
 ";
 
-    hb.register_template_string("announcement", tmpl_str)
-        .unwrap();
-    let mut data = HashMap::new();
-    data.insert("mood", String::from("excited"));
-    data.insert("version", version.to_string());
-    data.insert("changelog", String::from("things that changed"));
+const SLACK_TEMPLATE: &str = "\
+:tada: *S3 Gateway v{{version}} has been released!*
+
+{{notes}}
+
+*Container images*
+{{#each images}}
+• `{{reference}}`
+{{/each}}
+{{#if chart_version}}
+*Helm Chart*: https://artifacthub.io/packages/helm/s3gw/s3gw/{{chart_version}}
+{{/if}}
+*Release notes*: https://s3gw-docs.readthedocs.io/en/main/release-notes/s3gw-v{{version}}/
+";
+
+const GITHUB_TEMPLATE: &str = "\
+{{notes}}
+
+## Container images
+
+{{#each images}}
+- `{{reference}}`
+{{/each}}
+{{#if chart_version}}
+
+## Helm Chart
+
+Version `{{chart_version}}`, available at https://artifacthub.io/packages/helm/s3gw/s3gw/{{chart_version}}
+{{/if}}
+";
+
+/// An image released, on a given registry.
+///
+#[derive(serde::Serialize)]
+struct AnnouncementImage {
+    registry: String,
+    name: String,
+    reference: String,
+}
+
+/// Data available to announcement templates.
+///
+#[derive(serde::Serialize)]
+struct AnnouncementData {
+    version: String,
+    notes: String,
+    images: Vec<AnnouncementImage>,
+    chart_version: Option<String>,
+}
 
-    println!("{}", hb.render("announcement", &data).unwrap());
+/// Generate the announcement for release 'version' in format 'format', writing
+/// it to 'outfile' if provided, or stdout otherwise. The announcement is
+/// rendered from the workspace's template for the format, at
+/// '.arc/templates/announce-FORMAT.hbs', if it exists, or the default template
+/// otherwise. Release notes are obtained from 'changelog_file' if provided,
+/// the release's notes on the 's3gw' repository, or generated from the changes
+/// since the previous release, in that order.
+///
+pub fn announce(
+    release: &mut Release,
+    version: &Version,
+    format: &AnnouncementFormat,
+    changelog_file: &Option<PathBuf>,
+    outfile: &Option<PathBuf>,
+) -> ReleaseResult<()> {
+    let ws = &release.ws;
+    if let Err(()) = ws.sync() {
+        errorln!("Unable to synchronize workspace repositories!");
+        return Err(ReleaseError::SyncError);
+    }
+
+    let notes = match changelog_file {
+        Some(path) => match std::fs::read_to_string(&path) {
+            Ok(v) => v,
+            Err(err) => {
+                errorln!("Unable to read changelog at '{}': {}", path.display(), err);
+                return Err(ReleaseError::UnknownError);
+            }
+        },
+        None => match common::get_release_notes(&ws, &version)? {
+            Some(v) => v,
+            None => {
+                warnln!("Release notes for v{} not found, generating them.", version);
+                changelog::collect(&ws, &version, &None)?.render_sections()
+            }
+        },
+    };
+
+    let data = AnnouncementData {
+        version: version.to_string(),
+        notes: notes.trim_end().to_string(),
+        images: get_images(&ws, &version),
+        chart_version: get_chart_version(&ws, &version)?,
+    };
+
+    let mut hb = Handlebars::new();
+    hb.register_escape_fn(handlebars::no_escape);
+    let tmpl_path = ws
+        .get_config_dir()
+        .join("templates")
+        .join(format!("announce-{}.hbs", format.name()));
+    let tmpl_str = if tmpl_path.exists() {
+        infoln!("Using announcement template at '{}'", tmpl_path.display());
+        match std::fs::read_to_string(&tmpl_path) {
+            Ok(v) => v,
+            Err(err) => {
+                errorln!(
+                    "Unable to read template at '{}': {}",
+                    tmpl_path.display(),
+                    err
+                );
+                return Err(ReleaseError::UnknownError);
+            }
+        }
+    } else {
+        format.default_template().to_string()
+    };
+    if let Err(err) = hb.register_template_string("announcement", tmpl_str) {
+        errorln!("Unable to parse announcement template: {}", err);
+        return Err(ReleaseError::UnknownError);
+    }
+    let announcement = match hb.render("announcement", &data) {
+        Ok(v) => v,
+        Err(err) => {
+            errorln!("Unable to render announcement: {}", err);
+            return Err(ReleaseError::UnknownError);
+        }
+    };
+
+    match outfile {
+        None => print!("{}", announcement),
+        Some(path) => {
+            if let Err(err) = std::fs::write(&path, announcement) {
+                errorln!(
+                    "Unable to write announcement to '{}': {}",
+                    path.display(),
+                    err
+                );
+                return Err(ReleaseError::UnknownError);
+            }
+            successln!("Wrote announcement to '{}'", path.display());
+        }
+    };
 
     Ok(())
 }
+
+/// Obtain the references for the release's images on each of the workspace's
+/// registries.
+///
+fn get_images(ws: &Workspace, version: &Version) -> Vec<AnnouncementImage> {
+    let tag = format!("v{}", version);
+    let mut images: Vec<AnnouncementImage> = vec![];
+    for cfg in &ws.config.registries {
+        let registry = crate::registry::from_config(&cfg);
+        for (name, repo) in &cfg.images {
+            images.push(AnnouncementImage {
+                registry: cfg.name.clone(),
+                name: name.clone(),
+                reference: registry.get_image_ref(&repo, &tag),
+            });
+        }
+    }
+    images
+}
+
+/// Obtain the Helm chart version released with 'version', if the chart exists.
+///
+fn get_chart_version(ws: &Workspace, version: &Version) -> ReleaseResult<Option<String>> {
+    let repo = ws.repos.charts();
+    let refspec = match repo.get_version_refspec(&version) {
+        Ok(v) => v,
+        Err(err) => {
+            errorln!("Unable to find v{} on '{}': {}", version, repo.name, err);
+            return Err(ReleaseError::UnknownError);
+        }
+    };
+    let chart = match repo.read_file(&refspec, &PathBuf::from("charts/s3gw/Chart.yaml")) {
+        Ok(Some(v)) => v,
+        Ok(None) => {
+            warnln!("Helm chart not found on '{}'", repo.name);
+            return Ok(None);
+        }
+        Err(err) => {
+            errorln!("Unable to read Helm chart on '{}': {}", repo.name, err);
+            return Err(ReleaseError::UnknownError);
+        }
+    };
+    match serde_yaml::from_str::<serde_yaml::Value>(&chart) {
+        Ok(v) => Ok(match &v["version"] {
+            serde_yaml::Value::String(s) => Some(s.clone()),
+            serde_yaml::Value::Null => None,
+            other => serde_yaml::to_string(other)
+                .ok()
+                .map(|s| s.trim().to_string()),
+        }),
+        Err(err) => {
+            errorln!("Unable to parse Helm chart on '{}': {}", repo.name, err);
+            Err(ReleaseError::UnknownError)
+        }
+    }
+}
@@ -12,139 +12,158 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::io::BufRead;
-use std::io::BufReader;
-use std::io::BufWriter;
-use std::io::Write;
 use std::path::PathBuf;
 
 use crate::errorln;
 use crate::infoln;
+use crate::release::common;
 use crate::release::errors::ChartsResult;
+use crate::warnln;
+use crate::ws::workspace::Workspace;
 use crate::{boomln, version::Version, ws::repository::Repository};
 
 use crate::release::errors::ChartsError;
+use crate::ws::dryrun::DryRunAction;
 
-/// Update the Helm chart to the provided version. Ensures the result is
-/// committed.
+use self::yaml::YamlDocument;
+
+mod yaml;
+
+/// Update the Helm chart to the provided version: its 'version' and
+/// 'appVersion' in 'Chart.yaml', and the repository's configured image tags in
+/// 'values.yaml'. Ensures the result is committed.
 ///
 pub fn update_charts(repo: &Repository, version: &Version) -> ChartsResult<()> {
-    let chart_path_rel = PathBuf::from("charts/s3gw/Chart.yaml");
-    let chart_path = repo.path.join(&chart_path_rel);
-    if !chart_path.exists() {
+    let chart_dir = PathBuf::from("charts/s3gw");
+    let chart_path_rel = chart_dir.join("Chart.yaml");
+    if !repo.path.join(&chart_path_rel).exists() {
         return Err(ChartsError::DoesNotExistError);
     }
 
-    if let Err(err) = chart_update_version(&chart_path, &version) {
-        boomln!("Unable to update chart version: {}", err);
-        return Err(err);
+    update_yaml_file(&repo, &chart_path_rel, |doc| {
+        if doc.get("version").is_none() {
+            boomln!("Unable to find chart version!");
+            return Err(ChartsError::ParsingError);
+        }
+        let mut updates = vec![("version".to_string(), version.to_string())];
+        match doc.get("appVersion") {
+            Some(cur) => updates.push(("appVersion".into(), get_version_like(&cur, &version))),
+            None => log::debug!("chart has no 'appVersion', skipping"),
+        };
+        Ok(updates)
+    })?;
+    let mut to_stage = vec![chart_path_rel];
+
+    let image_tags = &repo.config.chart_image_tags;
+    if !image_tags.is_empty() {
+        let values_path_rel = chart_dir.join("values.yaml");
+        if !repo.path.join(&values_path_rel).exists() {
+            boomln!("Unable to find chart values to update image tags!");
+            return Err(ChartsError::DoesNotExistError);
+        }
+        update_yaml_file(&repo, &values_path_rel, |doc| {
+            let mut updates = vec![];
+            for key in image_tags {
+                match doc.get(&key) {
+                    Some(cur) => updates.push((key.clone(), get_version_like(&cur, &version))),
+                    None => {
+                        boomln!("Unable to find image tag '{}' in chart values!", key);
+                        return Err(ChartsError::ParsingError);
+                    }
+                };
+            }
+            Ok(updates)
+        })?;
+        to_stage.push(values_path_rel);
     }
 
-    if let Err(err) = repo.stage_paths(&vec![chart_path_rel]) {
+    if let Err(err) = repo.stage_paths(&to_stage) {
         boomln!("Unable to stage chart changes: {}", err);
         return Err(ChartsError::StagingError);
     }
 
-    match std::process::Command::new("git")
-        .args([
-            "-C",
-            repo.path.to_str().unwrap(),
-            "commit",
-            "--gpg-sign",
-            "--signoff",
-            "-m",
-            format!("Update charts to version {}", version).as_str(),
-        ])
-        .status()
-    {
-        Ok(res) => {
-            if !res.success() {
-                boomln!("Unable to commit chart update: {}", res.code().unwrap());
-                return Err(ChartsError::UnknownError);
-            }
-        }
-        Err(err) => {
-            boomln!("Error committing chart update: {}", err);
-            return Err(ChartsError::CommitError);
-        }
-    };
+    if let Err(err) = repo.commit(&get_commit_msg(&version), false) {
+        boomln!("Error committing chart update: {}", err);
+        return Err(ChartsError::CommitError);
+    }
 
     Ok(())
 }
 
-/// Helper function. Replaces the existing version of the chart with the
-/// provided version. This is achieved by writing a copy of the chart to a
-/// temporary file, containing the new version, and replacing the chart file
-/// in the end.
+/// Obtain 'version' as a string formatted like 'current' -- i.e., prefixed
+/// with 'v' if 'current' is.
 ///
-fn chart_update_version(chart_path: &PathBuf, version: &Version) -> ChartsResult<()> {
-    let f = match std::fs::File::open(&chart_path) {
-        Ok(f) => f,
+fn get_version_like(current: &String, version: &Version) -> String {
+    if current.starts_with('v') {
+        format!("v{}", version)
+    } else {
+        version.to_string()
+    }
+}
+
+/// Helper function. Updates the YAML file at 'path_rel', within repository
+/// 'repo', with the values 'get_updates' obtains from its current contents,
+/// keyed by their dotted path. Comments and formatting are kept, and the
+/// result is validated before replacing the file, via a temporary file.
+///
+fn update_yaml_file<F>(repo: &Repository, path_rel: &PathBuf, get_updates: F) -> ChartsResult<()>
+where
+    F: FnOnce(&YamlDocument) -> ChartsResult<Vec<(String, String)>>,
+{
+    let path = repo.path.join(&path_rel);
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(v) => v,
         Err(err) => {
-            boomln!(
-                "Unable to open chart file at '{}': {}",
-                chart_path.display(),
-                err
-            );
+            boomln!("Unable to read '{}': {}", path.display(), err);
             return Err(ChartsError::UnknownError);
         }
     };
-
-    let mut tmp_chart_path = chart_path.clone();
-    tmp_chart_path.set_extension("yaml.tmp");
-    let tmp_chart = match std::fs::File::options()
-        .create_new(true)
-        .write(true)
-        .open(&tmp_chart_path)
-    {
-        Ok(f) => f,
+    let mut doc = match YamlDocument::parse(&contents) {
+        Ok(v) => v,
         Err(err) => {
-            boomln!("Unable to open tmp chart file: {}", err);
-            return Err(ChartsError::UnknownError);
+            boomln!("Unable to parse '{}': {}", path_rel.display(), err);
+            return Err(ChartsError::ParsingError);
         }
     };
 
-    let version_re = regex::Regex::new(r"^version:[ ]+(.*)$").unwrap();
-
-    let mut writer = BufWriter::new(tmp_chart);
-    let reader = BufReader::new(f);
-    for line_res in reader.lines() {
-        let mut line = match line_res {
-            Ok(s) => s,
-            Err(err) => {
-                boomln!("Unable to obtain line from chart file: {}", err);
-                return Err(ChartsError::ParsingError);
-            }
-        };
-
-        if let Some(m) = version_re.captures(&line) {
-            let cur_ver = match Version::from_str(&m[1].into()) {
-                Ok(v) => v,
-                Err(()) => {
-                    boomln!("Unable to parse current charts version!");
-                    return Err(ChartsError::ParsingError);
-                }
-            };
-            log::debug!("chart version: cur {} next {}", cur_ver, version);
-            line = format!("version: {}", version);
+    let updates = get_updates(&doc)?;
+    for (key, value) in &updates {
+        log::debug!(
+            "{}: '{}' cur {} next {}",
+            path_rel.display(),
+            key,
+            doc.get(&key).unwrap_or_default(),
+            value
+        );
+        if let Err(err) = doc.set(&key, &value) {
+            boomln!(
+                "Unable to update '{}' in '{}': {}",
+                key,
+                path_rel.display(),
+                err
+            );
+            return Err(ChartsError::ParsingError);
         }
-        line.push('\n');
-        match writer.write(line.as_bytes()) {
-            Ok(_) => {}
-            Err(err) => {
-                boomln!("Error writing to tmp charts file: {}", err);
-                return Err(ChartsError::UnknownError);
-            }
-        };
+    }
+    if let Err(err) = doc.validate(&updates) {
+        boomln!("Unable to update '{}': {}", path_rel.display(), err);
+        return Err(ChartsError::ParsingError);
     }
 
-    if let Err(err) = std::fs::remove_file(&chart_path) {
-        boomln!("Error removing charts file for replacement: {}", err);
-        return Err(ChartsError::UnknownError);
+    if repo.dry_run_record(DryRunAction::Write {
+        path: path_rel.clone(),
+    }) {
+        return Ok(());
     }
 
-    if let Err(err) = std::fs::rename(&tmp_chart_path, &chart_path) {
-        boomln!("Error renaming tmp charts file: {}", err);
+    let mut tmp_path = path.clone();
+    tmp_path.set_extension("yaml.tmp");
+    if let Err(err) = std::fs::write(&tmp_path, doc.to_string()) {
+        boomln!("Error writing to '{}': {}", tmp_path.display(), err);
+        return Err(ChartsError::UnknownError);
+    }
+    if let Err(err) = std::fs::rename(&tmp_path, &path) {
+        boomln!("Error replacing '{}': {}", path.display(), err);
         return Err(ChartsError::UnknownError);
     }
 
@@ -157,27 +176,146 @@ fn chart_update_version(chart_path: &PathBuf, version: &Version) -> ChartsResult
 ///    charts publishing workflow.
 /// 2. Updating the chart version on the repository's main brain.
 ///
-/// In the context of 2., we will not actually be pushing to the main branch
-/// directly, because we have no way of knowing whether other things need to be
-/// merged into main before the chart version is updated. Instead, we will open
-/// a pull request targeting main.
+/// This function handles 1. In the context of 2., we will not actually be
+/// pushing to the main branch directly, because we have no way of knowing
+/// whether other things need to be merged into main before the chart version
+/// is updated. Instead, 'open_charts_pull_request()' opens a pull request
+/// targeting main.
 ///
 pub fn finalize_charts_release(repo: &Repository, version: &Version) -> ChartsResult<()> {
     // publish the chart version we're finalizing
-    if let Err(err) = publish_chart(&repo, &version) {
-        return Err(err);
+    publish_chart(&repo, &version)
+}
+
+/// Open a pull request updating the chart version on the repository's default
+/// branch, by cherry-picking the chart version update for 'version' from its
+/// release branch onto a new branch off the default branch. Should the
+/// cherry-pick conflict, instructions to open the pull request manually are
+/// provided instead, so the release can still be finished.
+///
+pub async fn open_charts_pull_request(
+    ws: &Workspace,
+    repo: &Repository,
+    version: &Version,
+) -> ChartsResult<()> {
+    if repo.config.github.is_none() {
+        infoln!("No forge configured for '{}'.", repo.name);
+        infoln!("To finish the Helm Chart release, please do the following:");
+        infoln!("  1. cherry-pick the topmost commit to a new branch");
+        infoln!("  2. open a Pull Request against the 'main' branch.");
+        infoln!("  3. Ask for a reviewer, and merge the Pull Request.");
+        return Ok(());
     }
 
-    // finalize 'main' release, by updating the chart version and opening a pull
-    // request against main.
-    infoln!("To finish the Helm Chart release, please do the following:");
-    infoln!("  1. cherry-pick the topmost commit to a new branch");
-    infoln!("  2. open a Pull Request against the 'main' branch.");
-    infoln!("  3. Ask for a reviewer, and merge the Pull Request.");
+    let default_branch = match repo.get_default_branch_name() {
+        Ok(v) => v,
+        Err(err) => {
+            errorln!(
+                "Unable to obtain default branch for '{}': {}",
+                repo.name,
+                err
+            );
+            return Err(ChartsError::UnknownError);
+        }
+    };
+
+    // find the chart version update on the release branch
+    let release_branch = version.to_str_fmt(&repo.config.release_branch_format);
+    let commits = match repo.get_commits(
+        Some(&format!("refs/remotes/ro/{}", default_branch)),
+        &format!("refs/heads/{}", release_branch),
+    ) {
+        Ok(v) => v,
+        Err(err) => {
+            errorln!(
+                "Unable to obtain commits for '{}' on '{}': {}",
+                release_branch,
+                repo.name,
+                err
+            );
+            return Err(ChartsError::UnknownError);
+        }
+    };
+    let commit_msg = get_commit_msg(&version);
+    let update = match commits.iter().find(|c| c.summary == commit_msg) {
+        Some(c) => c.sha.clone(),
+        // the chart update is not committed when in dry-run mode
+        None if repo.is_dry_run() => format!("refs/heads/{}", release_branch),
+        None => {
+            errorln!(
+                "Unable to find chart update to {} on '{}'",
+                version,
+                release_branch
+            );
+            return Err(ChartsError::CherryPickError);
+        }
+    };
+
+    let branch_suffix = chrono::Utc::now().format("%Y%m%d-%H%M%S").to_string();
+    let dst_branch = format!("release-v{}-{}", version, branch_suffix);
+    if let Err(err) = repo.branch_from_default(&dst_branch) {
+        errorln!("Unable to branch default to '{}': {}", dst_branch, err);
+        return Err(ChartsError::UnknownError);
+    }
+
+    match repo.cherry_pick(&vec![update.clone()]) {
+        Ok(None) => {}
+        Ok(Some(conflict)) => {
+            warnln!(
+                "Chart update {} conflicts on '{}': {}",
+                conflict.commit,
+                dst_branch,
+                conflict.paths.join(", ")
+            );
+            infoln!("To finish the Helm Chart release, please do the following:");
+            infoln!(
+                "  1. cherry-pick {} from '{}' onto branch '{}' at '{}'",
+                update,
+                release_branch,
+                dst_branch,
+                repo.path.display()
+            );
+            infoln!("  2. resolve the conflicts, and push the branch.");
+            infoln!(
+                "  3. open a Pull Request against the '{}' branch.",
+                default_branch
+            );
+            infoln!("  4. Ask for a reviewer, and merge the Pull Request.");
+            return Ok(());
+        }
+        Err(err) => {
+            errorln!(
+                "Unable to cherry-pick {} onto '{}': {}",
+                update,
+                dst_branch,
+                err
+            );
+            return Err(ChartsError::CherryPickError);
+        }
+    };
+
+    // push and open pull request
+    if let Err(err) =
+        common::create_pull_request(&ws, &repo, &dst_branch, &commit_msg, &version).await
+    {
+        errorln!(
+            "Unable to open pull request for '{}' on '{}': {}",
+            dst_branch,
+            repo.name,
+            err
+        );
+        return Err(ChartsError::PullRequestError);
+    }
 
     Ok(())
 }
 
+/// Obtain the message for the commit updating the chart to 'version'.
+///
+fn get_commit_msg(version: &Version) -> String {
+    format!("Update charts to version {}", version)
+}
+
 /// Publishes the chart's current version by pushing the release branch to the
 /// chart's final release branch -- i.e., the branch that triggers the
 /// publishing workflow.
@@ -0,0 +1,418 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Display;
+
+#[derive(Debug, PartialEq)]
+pub enum YamlEditError {
+    ParsingError,
+    KeyNotFoundError,
+    UnsupportedValueError,
+    ValidationError,
+}
+
+impl Display for YamlEditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            YamlEditError::ParsingError => "error parsing YAML document",
+            YamlEditError::KeyNotFoundError => "key not found in YAML document",
+            YamlEditError::UnsupportedValueError => "unsupported value for in-place editing",
+            YamlEditError::ValidationError => "edited YAML document is not valid",
+        })
+    }
+}
+
+pub type YamlEditResult<T> = Result<T, YamlEditError>;
+
+/// How a scalar value is written in the document.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ScalarStyle {
+    Plain,
+    SingleQuoted,
+    DoubleQuoted,
+    /// Anything we won't edit in place (e.g., flow collections, anchors,
+    /// aliases, tags, block scalars, or nested mappings).
+    Other,
+}
+
+/// A mapping entry's scalar value, located within the document's lines.
+///
+struct YamlEntry {
+    /// dotted path to the entry (e.g., 'ui.imageTag')
+    path: String,
+    line: usize,
+    /// byte range of the value within its line, including any quotes
+    start: usize,
+    end: usize,
+    style: ScalarStyle,
+}
+
+/// A YAML document edited in place, line by line, preserving comments,
+/// quoting, and formatting. Only block mappings' scalar values can be edited,
+/// addressed by their dotted path from the document's root; keys within
+/// sequences (e.g., a Helm chart's 'dependencies') are never matched.
+///
+pub struct YamlDocument {
+    lines: Vec<String>,
+    entries: Vec<YamlEntry>,
+}
+
+impl YamlDocument {
+    /// Parse 'contents', which must be a valid YAML document.
+    ///
+    pub fn parse(contents: &str) -> YamlEditResult<YamlDocument> {
+        if let Err(err) = serde_yaml::from_str::<serde_yaml::Value>(&contents) {
+            log::error!("Unable to parse YAML document: {}", err);
+            return Err(YamlEditError::ParsingError);
+        }
+
+        let lines: Vec<String> = contents.lines().map(String::from).collect();
+        let entries = get_entries(&lines);
+        Ok(YamlDocument { lines, entries })
+    }
+
+    fn find(self: &Self, path: &str) -> Option<&YamlEntry> {
+        self.entries.iter().find(|e| e.path == path)
+    }
+
+    /// Obtain the scalar value at 'path', without quotes, if it exists.
+    ///
+    pub fn get(self: &Self, path: &str) -> Option<String> {
+        let entry = self.find(&path)?;
+        let raw = &self.lines[entry.line][entry.start..entry.end];
+        match entry.style {
+            ScalarStyle::Plain => Some(raw.to_string()),
+            ScalarStyle::SingleQuoted => Some(raw[1..raw.len() - 1].replace("''", "'")),
+            ScalarStyle::DoubleQuoted => serde_yaml::from_str::<String>(raw).ok(),
+            ScalarStyle::Other => None,
+        }
+    }
+
+    /// Set the scalar value at 'path' to 'value', keeping its quoting style.
+    ///
+    pub fn set(self: &mut Self, path: &str, value: &str) -> YamlEditResult<()> {
+        let (line, start, end, style) = match self.find(&path) {
+            Some(e) => (e.line, e.start, e.end, e.style),
+            None => {
+                log::error!("Unable to find '{}' in YAML document", path);
+                return Err(YamlEditError::KeyNotFoundError);
+            }
+        };
+
+        let new_value = match style {
+            ScalarStyle::Plain if is_plain_safe(&value) => value.to_string(),
+            ScalarStyle::Plain | ScalarStyle::DoubleQuoted => {
+                format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+            ScalarStyle::SingleQuoted => format!("'{}'", value.replace('\'', "''")),
+            ScalarStyle::Other => {
+                log::error!("Unable to edit '{}' in place: not a scalar value", path);
+                return Err(YamlEditError::UnsupportedValueError);
+            }
+        };
+        self.lines[line].replace_range(start..end, &new_value);
+
+        // later entries on the same line can't exist, so only this entry's
+        // span changes.
+        let len = new_value.len();
+        for entry in self.entries.iter_mut().filter(|e| e.line == line) {
+            entry.end = entry.start + len;
+        }
+        Ok(())
+    }
+
+    /// Ensure the document is valid YAML and that each of 'expected' values
+    /// is found at its path, as a YAML parser would read them.
+    ///
+    pub fn validate(self: &Self, expected: &[(String, String)]) -> YamlEditResult<()> {
+        let value = match serde_yaml::from_str::<serde_yaml::Value>(&self.to_string()) {
+            Ok(v) => v,
+            Err(err) => {
+                log::error!("Edited YAML document is invalid: {}", err);
+                return Err(YamlEditError::ValidationError);
+            }
+        };
+        for (path, expected_value) in expected {
+            let mut cur = &value;
+            for key in path.split('.') {
+                cur = &cur[key];
+            }
+            let found = match cur {
+                serde_yaml::Value::String(s) => s.clone(),
+                serde_yaml::Value::Number(n) => n.to_string(),
+                serde_yaml::Value::Bool(b) => b.to_string(),
+                _ => String::new(),
+            };
+            if &found != expected_value {
+                log::error!(
+                    "Edited YAML document has '{}' at '{}', expected '{}'",
+                    found,
+                    path,
+                    expected_value
+                );
+                return Err(YamlEditError::ValidationError);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for YamlDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether 'value' can be written as a plain scalar, and be read back as the
+/// same string.
+///
+fn is_plain_safe(value: &str) -> bool {
+    let re = regex::Regex::new(r"^[A-Za-z0-9_./+-][A-Za-z0-9_./+-]*$").unwrap();
+    re.is_match(&value) && !matches!(value, "true" | "false" | "null" | "yes" | "no" | "~")
+}
+
+/// Locate every block mapping entry in 'lines', along with its value.
+///
+fn get_entries(lines: &Vec<String>) -> Vec<YamlEntry> {
+    let key_re = regex::Regex::new(
+        r#"^(?P<key>"(?:[^"\\]|\\.)*"|'(?:[^']|'')*'|[^\s#'"\-{\[][^:#]*?|-[^\s:#][^:#]*?)\s*:(?:\s+|$)"#,
+    )
+    .unwrap();
+
+    let mut entries = vec![];
+    // keys of the mappings we're in, by indentation; sequence items are
+    // tracked as '-', so keys within them never match a path.
+    let mut stack: Vec<(usize, String)> = vec![];
+    let mut block_scalar_indent: Option<usize> = None;
+
+    for (idx, line) in lines.iter().enumerate() {
+        let content = line.trim_start();
+        let indent = line.len() - content.len();
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+        if let Some(block_indent) = block_scalar_indent {
+            if indent > block_indent {
+                continue;
+            }
+            block_scalar_indent = None;
+        }
+        if content == "---" || content == "..." {
+            stack.clear();
+            continue;
+        }
+
+        while stack.last().is_some_and(|(i, _)| *i >= indent) {
+            stack.pop();
+        }
+        if content == "-" || content.starts_with("- ") {
+            stack.push((indent, "-".into()));
+            continue;
+        }
+
+        let caps = match key_re.captures(&content) {
+            Some(c) => c,
+            None => continue,
+        };
+        let raw_key = caps.name("key").unwrap().as_str();
+        let key = match raw_key.chars().next() {
+            Some('"') => serde_yaml::from_str::<String>(raw_key).unwrap_or_default(),
+            Some('\'') => raw_key[1..raw_key.len() - 1].replace("''", "'"),
+            _ => raw_key.to_string(),
+        };
+        stack.push((indent, key));
+
+        let value_offset = indent + caps.get(0).unwrap().end();
+        let (start, end, style) = get_value_span(&line, value_offset);
+        if style == ScalarStyle::Other && line[start..end].starts_with(['|', '>']) {
+            block_scalar_indent = Some(indent);
+        }
+        if start == end {
+            // nested mapping, or sequence, follows
+            continue;
+        }
+
+        entries.push(YamlEntry {
+            path: stack
+                .iter()
+                .map(|(_, k)| k.as_str())
+                .collect::<Vec<&str>>()
+                .join("."),
+            line: idx,
+            start,
+            end,
+            style,
+        });
+    }
+    entries
+}
+
+/// Obtain the byte range and style of the value starting at or after byte
+/// 'offset' on 'line', excluding any trailing comment.
+///
+fn get_value_span(line: &str, offset: usize) -> (usize, usize, ScalarStyle) {
+    let rest = &line[offset..];
+    let start = offset + (rest.len() - rest.trim_start().len());
+    let value = &line[start..];
+
+    let (len, style) = match value.chars().next() {
+        None | Some('#') => (0, ScalarStyle::Plain),
+        Some('"') => {
+            let mut escaped = false;
+            let mut end = None;
+            for (i, c) in value.char_indices().skip(1) {
+                match c {
+                    '\\' if !escaped => escaped = true,
+                    '"' if !escaped => {
+                        end = Some(i + 1);
+                        break;
+                    }
+                    _ => escaped = false,
+                }
+            }
+            match end {
+                Some(e) => (e, ScalarStyle::DoubleQuoted),
+                None => (value.trim_end().len(), ScalarStyle::Other),
+            }
+        }
+        Some('\'') => {
+            let bytes = value.as_bytes();
+            let mut i = 1;
+            let mut end = None;
+            while i < bytes.len() {
+                if bytes[i] == b'\'' {
+                    if bytes.get(i + 1) == Some(&b'\'') {
+                        i += 2;
+                        continue;
+                    }
+                    end = Some(i + 1);
+                    break;
+                }
+                i += 1;
+            }
+            match end {
+                Some(e) => (e, ScalarStyle::SingleQuoted),
+                None => (value.trim_end().len(), ScalarStyle::Other),
+            }
+        }
+        Some(c) => {
+            let end = match value.find(" #") {
+                Some(i) => i,
+                None => value.len(),
+            };
+            let len = value[..end].trim_end().len();
+            if "{[&*!|>@`%".contains(c) {
+                (len, ScalarStyle::Other)
+            } else {
+                (len, ScalarStyle::Plain)
+            }
+        }
+    };
+    (start, start + len, style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{YamlDocument, YamlEditError};
+
+    const CHART: &str = "\
+# Helm chart for s3gw
+apiVersion: v2
+name: s3gw
+version: 0.16.0 # bumped on release
+appVersion: \"v0.16.0\"
+description: |
+  version: not a key
+dependencies:
+  - name: common
+    version: 1.2.3
+annotations:
+  'artifacthub.io/license': Apache-2.0
+";
+
+    #[test]
+    fn get_top_level_and_quoted() {
+        let doc = YamlDocument::parse(CHART).unwrap();
+        assert_eq!(doc.get("version"), Some("0.16.0".into()));
+        assert_eq!(doc.get("appVersion"), Some("v0.16.0".into()));
+        assert_eq!(
+            doc.get("annotations.artifacthub.io/license"),
+            Some("Apache-2.0".into())
+        );
+        assert_eq!(doc.get("description"), None);
+        assert_eq!(doc.get("dependencies"), None);
+    }
+
+    #[test]
+    fn set_preserves_formatting() {
+        let mut doc = YamlDocument::parse(CHART).unwrap();
+        doc.set("version", "0.17.0").unwrap();
+        doc.set("appVersion", "v0.17.0").unwrap();
+        assert_eq!(
+            doc.to_string(),
+            CHART
+                .replace("version: 0.16.0 #", "version: 0.17.0 #")
+                .replace("\"v0.16.0\"", "\"v0.17.0\"")
+        );
+        doc.validate(&[
+            ("version".into(), "0.17.0".into()),
+            ("appVersion".into(), "v0.17.0".into()),
+        ])
+        .unwrap();
+    }
+
+    #[test]
+    fn set_nested_values() {
+        let values = "\
+imageTag: v0.16.0
+ui:
+  enabled: true
+  # the UI's image
+  imageTag: 'v0.16.0'
+";
+        let mut doc = YamlDocument::parse(values).unwrap();
+        doc.set("imageTag", "v0.17.0").unwrap();
+        doc.set("ui.imageTag", "v0.17.0").unwrap();
+        assert_eq!(
+            doc.to_string(),
+            "imageTag: v0.17.0\nui:\n  enabled: true\n  # the UI's image\n  imageTag: 'v0.17.0'\n"
+        );
+        assert_eq!(
+            doc.set("ui.missing", "v0.17.0"),
+            Err(YamlEditError::KeyNotFoundError)
+        );
+        assert_eq!(
+            doc.set("ui", "v0.17.0"),
+            Err(YamlEditError::KeyNotFoundError)
+        );
+    }
+
+    #[test]
+    fn set_quotes_unsafe_plain_values() {
+        let mut doc = YamlDocument::parse("version: 0.16.0\n").unwrap();
+        doc.set("version", "1.0: rc").unwrap();
+        assert_eq!(doc.to_string(), "version: \"1.0: rc\"\n");
+        doc.validate(&[("version".into(), "1.0: rc".into())])
+            .unwrap();
+    }
+
+    #[test]
+    fn parse_rejects_invalid_yaml() {
+        assert!(YamlDocument::parse("version: [0.16.0\n").is_err());
+    }
+}
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
 use crate::boomln;
 use crate::release::errors::ReleaseResult;
@@ -27,6 +27,7 @@ pub async fn continue_release(
     version: &Version,
     notes: &Option<PathBuf>,
     force: bool,
+    wait: &Option<Duration>,
 ) -> ReleaseResult<()> {
     // Continuing a release requires to first synchronize the repositories, then
     // ensuring we can actually release. If so, we can start a new release
@@ -43,7 +44,7 @@ pub async fn continue_release(
         }
     };
 
-    if let Err(err) = super::validate::check_can_release(&ws, &version, force).await {
+    if let Err(err) = super::validate::check_can_release(&ws, &version, force, &wait).await {
         boomln!("Can't continue releasing due to validation error: {}", err);
         return Err(err);
     }
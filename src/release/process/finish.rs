@@ -12,37 +12,30 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{io::Write, path::PathBuf};
+use std::{io::Write, path::PathBuf, time::Duration};
 
 use crate::{
     boomln, errorln, infoln,
     release::sync,
     release::{
+        common,
         errors::ReleaseResult,
+        journal::{JournalStep, ReleaseJournal},
         process::{charts, start},
     },
     successln,
     version::Version,
-    ws::{repository::Repository, workspace::Workspace},
+    ws::{dryrun::DryRunAction, repository::Repository, workspace::Workspace},
 };
 
 use crate::release::{errors::ReleaseError, Release};
 
-#[derive(serde::Serialize)]
-struct CreatePullRequestRequest {
-    title: String,
-    head: String,
-    base: String,
-    body: String,
-}
-
-#[derive(serde::Deserialize)]
-struct CreatePullRequestResponse {
-    pub html_url: String,
-    pub number: i64,
-}
-
-pub async fn finish(release: &mut Release, version: &Version, force: bool) -> ReleaseResult<()> {
+pub async fn finish(
+    release: &mut Release,
+    version: &Version,
+    force: bool,
+    wait: &Option<Duration>,
+) -> ReleaseResult<()> {
     // 1. check whether release has been finished
     // 2. check whether release has been started
     // 3. sync repositories for the specified release
@@ -54,7 +47,7 @@ pub async fn finish(release: &mut Release, version: &Version, force: bool) -> Re
     let ws = &release.ws;
 
     let release_versions = crate::release::common::get_release_versions(&ws, &version);
-    if release_versions.contains_key(&version.get_version_id()) {
+    if release_versions.contains_key(&version) {
         errorln!("Release version {} already exists", version);
         return Err(ReleaseError::ReleaseExistsError);
     } else if release_versions.len() == 0 {
@@ -72,7 +65,7 @@ pub async fn finish(release: &mut Release, version: &Version, force: bool) -> Re
         }
     };
 
-    if let Err(err) = super::validate::check_can_release(&ws, &version, force).await {
+    if let Err(err) = super::validate::check_can_release(&ws, &version, force, &wait).await {
         boomln!("Can't finish release due to validation error: {}", err);
         return Err(err);
     }
@@ -86,15 +79,46 @@ pub async fn finish(release: &mut Release, version: &Version, force: bool) -> Re
     };
     infoln!("Basing release on highest candidate: {}", max);
 
+    let mut journal = ReleaseJournal::begin(&ws, &version, &version, None)?;
+    if let Err(err) = finish_release(&ws, &version, &mut journal).await {
+        if ws.dry_run.is_none() {
+            infoln!("Use 'arc rel resume' to retry, or 'arc rel rollback' to undo.");
+        }
+        return Err(err);
+    }
+    journal.finish()?;
+
+    successln!("Version {} released!", version);
+
+    Ok(())
+}
+
+/// Perform the steps finishing release 'version', recording each one of them
+/// in 'journal'. Steps already recorded are skipped, which allows resuming a
+/// release that failed to finish.
+///
+pub async fn finish_release(
+    ws: &Workspace,
+    version: &Version,
+    journal: &mut ReleaseJournal,
+) -> ReleaseResult<()> {
     // adjust charts version
 
-    infoln!("Update chart to version {}", version);
-    if let Err(err) = charts::update_charts(&ws.repos.charts, &version) {
-        boomln!("Error updating chart: {}", err);
-        return Err(ReleaseError::UnknownError);
-    }
+    let charts = &ws.repos.charts();
+    let branch = version.to_str_fmt(&charts.config.release_branch_format);
+    let previous = common::get_release_branch_head(&charts, &version)?;
+    journal.run(&charts, JournalStep::Committed { branch, previous }, || {
+        infoln!("Update chart to version {}", version);
+        match charts::update_charts(&charts, &version) {
+            Ok(()) => Ok(()),
+            Err(err) => {
+                boomln!("Error updating chart: {}", err);
+                Err(ReleaseError::UnknownError)
+            }
+        }
+    })?;
 
-    match start::perform_release(&ws, &version, &version, &None) {
+    match start::perform_release(&ws, &version, &version, &None, journal) {
         Ok(()) => {}
         Err(err) => {
             errorln!("Unable to finish release for {}: {}", version, err);
@@ -109,23 +133,71 @@ pub async fn finish(release: &mut Release, version: &Version, force: bool) -> Re
     //  a specific branch name in the charts repository so the release workflow
     //  can be triggered.
 
-    infoln!("Finalizing Helm Chart release");
-    if let Err(err) = charts::finalize_charts_release(&ws.repos.charts, &version) {
-        errorln!("Unable to finalize chart for publishing: {}", err);
-        return Err(ReleaseError::UnknownError);
+    let final_branch = match &charts.config.final_branch_format {
+        Some(fmt) => version.to_str_fmt(&fmt),
+        None => version.to_str_fmt(&charts.config.release_branch_format),
+    };
+    journal.run(
+        &charts,
+        JournalStep::ChartPublished {
+            branch: final_branch,
+        },
+        || {
+            infoln!("Finalizing Helm Chart release");
+            match charts::finalize_charts_release(&charts, &version) {
+                Ok(()) => Ok(()),
+                Err(err) => {
+                    errorln!("Unable to finalize chart for publishing: {}", err);
+                    Err(ReleaseError::UnknownError)
+                }
+            }
+        },
+    )?;
+
+    // open pull request against the charts repository's "main", updating
+    //  the chart version.
+    let charts_default = match charts.get_default_branch_name() {
+        Ok(v) => v,
+        Err(err) => {
+            errorln!("Unable to obtain charts default branch: {}", err);
+            return Err(ReleaseError::UnknownError);
+        }
+    };
+    let charts_pr_step = JournalStep::PullRequestOpened {
+        branch: charts_default,
+    };
+    if !journal.has(&charts, &charts_pr_step) {
+        infoln!("Opening Helm Chart pull request");
+        if let Err(err) = charts::open_charts_pull_request(&ws, &charts, &version).await {
+            errorln!("Unable to open chart pull request: {}", err);
+            return Err(ReleaseError::UnknownError);
+        }
+        journal.record(&charts, charts_pr_step)?;
     }
 
     // open pull request against s3gw.git's "main"
     //  This ensures we have a pull request ready with the new release notes, as
     //  well as updated documentation.
-    infoln!("Finalizing release");
-    if let Err(err) = finish_s3gw_update_default(&ws, &ws.repos.s3gw, &version).await {
-        errorln!("Unable to finalize s3gw repository's release: {}", err);
-        return Err(ReleaseError::UnknownError);
+    let s3gw = &ws.repos.main();
+    let default_branch = match s3gw.get_default_branch_name() {
+        Ok(v) => v,
+        Err(err) => {
+            errorln!("Unable to obtain s3gw default branch: {}", err);
+            return Err(ReleaseError::UnknownError);
+        }
+    };
+    let pr_step = JournalStep::PullRequestOpened {
+        branch: default_branch,
+    };
+    if !journal.has(&s3gw, &pr_step) {
+        infoln!("Finalizing release");
+        if let Err(err) = finish_s3gw_update_default(&ws, &s3gw, &version).await {
+            errorln!("Unable to finalize s3gw repository's release: {}", err);
+            return Err(ReleaseError::UnknownError);
+        }
+        journal.record(&s3gw, pr_step)?;
     }
 
-    successln!("Version {} released!", version);
-
     Ok(())
 }
 
@@ -208,7 +280,11 @@ async fn finish_s3gw_update_default(
     }
 
     // copy release notes to this new branch
-    if let Err(err) = std::fs::copy(&tmpfile.path(), &relver_notes_path_abs) {
+    if repo.dry_run_record(DryRunAction::Write {
+        path: relver_notes_path.clone(),
+    }) {
+        log::debug!("dry-run: not copying release notes");
+    } else if let Err(err) = std::fs::copy(&tmpfile.path(), &relver_notes_path_abs) {
         log::error!(
             "Error copying release notes from '{}' to '{}': {}",
             tmpfile.path().display(),
@@ -227,14 +303,18 @@ async fn finish_s3gw_update_default(
         );
         return Err(ReleaseError::UnknownError);
     }
-    if let Err(err) = adjust_mkdocs(&mkdocs_path_abs, &relver) {
+    if repo.dry_run_record(DryRunAction::Write {
+        path: mkdocs_path.clone(),
+    }) {
+        log::debug!("dry-run: not adjusting mkdocs file");
+    } else if let Err(err) = adjust_mkdocs(&mkdocs_path_abs, &relver) {
         log::error!("Error adjusting mkdocs file: {}", err);
         return Err(ReleaseError::UnknownError);
     }
 
     // sync new branch's submodules, so we can figure out later whether we need
     // to update to match the new release (and thus commit those changes).
-    match ws.repos.s3gw.update(true) {
+    match ws.repos.main().update(true) {
         Ok(()) => {
             log::trace!(
                 "Updated and synchronized submodules for branch '{}'",
@@ -281,7 +361,8 @@ async fn finish_s3gw_update_default(
     }
 
     // push and open pull request
-    if let Err(err) = create_pull_request(&ws, &repo, &dst_branch, &relver).await {
+    let title = format!("Release v{}", relver);
+    if let Err(err) = common::create_pull_request(&ws, &repo, &dst_branch, &title, &relver).await {
         log::error!(
             "Error creating pull request for '{}' on repository '{}': {}",
             dst_branch,
@@ -357,120 +438,3 @@ fn adjust_mkdocs(path: &PathBuf, relver: &Version) -> ReleaseResult<()> {
 
     Ok(())
 }
-
-/// Create a pull request from the specified branch, for the specified release
-/// version, on the 's3gw' repository.
-///
-///  note(joao): We could have assumed the 's3gw' repository, and used that from
-///  the 'workspace' provided. However, we may want this function later on for
-///  other repositories, like the 'charts' repository.
-///
-async fn create_pull_request(
-    ws: &Workspace,
-    repo: &Repository,
-    branch: &String,
-    relver: &Version,
-) -> ReleaseResult<()> {
-    // push branch to repository
-    let refspec = format!("refs/heads/{}:refs/heads/{}", branch, branch);
-    if let Err(err) = repo.push(&refspec) {
-        log::error!("Unable to push '{}' to remote repository: {}", branch, err);
-        return Err(ReleaseError::PushingError);
-    }
-
-    // open pull request against default branch
-    let default_branch = match repo.get_default_branch_name() {
-        Ok(v) => v,
-        Err(err) => {
-            log::error!(
-                "Unable to obtain default branch name for repository '{}': {}",
-                repo.name,
-                err
-            );
-            return Err(ReleaseError::UnknownError);
-        }
-    };
-    let gh_config = match &repo.config.github {
-        None => {
-            log::error!("GitHub repository not configured, can't open pull request!");
-            return Err(ReleaseError::UnknownError);
-        }
-        Some(c) => c,
-    };
-    let user_config = &ws.config.user;
-    if user_config.github_token.is_empty() {
-        log::error!("GitHub token not configured, can't open pull request!");
-        return Err(ReleaseError::UnknownError);
-    }
-
-    let api_url = format!(
-        "https://api.github.com/repos/{}/{}/pulls",
-        gh_config.org, gh_config.repo
-    );
-
-    let req = CreatePullRequestRequest {
-        title: format!("Release v{}", relver),
-        body: format!(
-            "Updates '{}' to reflect v{}\n\nSigned-off-by: {} \\<{}>",
-            default_branch, relver, user_config.name, user_config.email
-        ),
-        head: branch.clone(),
-        base: default_branch.clone(),
-    };
-
-    match serde_json::to_string(&req) {
-        Ok(v) => {
-            log::trace!("request body:\n{}", v);
-        }
-        Err(err) => {
-            log::error!("Unable to encode request body: {}", err);
-            return Err(ReleaseError::UnknownError);
-        }
-    };
-
-    let response = match reqwest::Client::new()
-        .post(&api_url)
-        .bearer_auth(&user_config.github_token)
-        .header("Accept", "application/vnd.github+json")
-        .header("X-GitHub-Api-Version", "2022-11-28")
-        .header("User-Agent", "s3gw-arc-rs")
-        .json(&req)
-        .send()
-        .await
-    {
-        Ok(r) => r,
-        Err(err) => {
-            log::error!(
-                "Unable to open pull request for '{}' against '{}' on '{}/{}': {}",
-                branch,
-                default_branch,
-                gh_config.org,
-                gh_config.repo,
-                err
-            );
-            return Err(ReleaseError::UnknownError);
-        }
-    };
-
-    let res_body = match response.text().await {
-        Ok(v) => {
-            log::trace!("response body:\n{}", v);
-            v
-        }
-        Err(err) => {
-            log::error!("Error obtaining response body: {}", err);
-            return Err(ReleaseError::UnknownError);
-        }
-    };
-
-    let (url, number) = match serde_json::from_str::<CreatePullRequestResponse>(&res_body) {
-        Ok(r) => (r.html_url, r.number),
-        Err(err) => {
-            log::error!("Unable to obtain pull request URL and id: {}", err);
-            return Err(ReleaseError::UnknownError);
-        }
-    };
-    successln!("Opened Pull Request {} at {}", number, url);
-
-    Ok(())
-}
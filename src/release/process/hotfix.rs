@@ -0,0 +1,467 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+use crate::{
+    boomln, errorln, infoln,
+    release::{
+        changelog, common,
+        errors::{ReleaseError, ReleaseResult},
+        journal::{JournalStep, ReleaseJournal},
+        process::start,
+        sync, Release, ReleaseState,
+    },
+    successln,
+    version::Version,
+    warnln,
+    ws::{repository::Repository, workspace::Workspace},
+};
+
+/// A commit, or pull request, to cherry-pick onto a release branch, as
+/// provided by the user.
+///
+enum PickSpec {
+    /// commit, optionally on a given repository (i.e., '[REPO:]SHA1')
+    Commit { repo: Option<String>, rev: String },
+    /// pull request on a given repository (i.e., '[REPO#]NUMBER'), defaulting to
+    /// the main repository
+    PullRequest { repo: Option<String>, number: u64 },
+}
+
+impl PickSpec {
+    fn parse_commit(value: &String) -> PickSpec {
+        match value.split_once(':') {
+            Some((repo, rev)) => PickSpec::Commit {
+                repo: Some(repo.into()),
+                rev: rev.into(),
+            },
+            None => PickSpec::Commit {
+                repo: None,
+                rev: value.clone(),
+            },
+        }
+    }
+
+    fn parse_pull_request(value: &String) -> Result<PickSpec, ()> {
+        let (repo, number) = match value.split_once('#') {
+            Some((repo, number)) => (Some(repo.into()), number),
+            None => (None, value.as_str()),
+        };
+        match number.parse::<u64>() {
+            Ok(number) => Ok(PickSpec::PullRequest { repo, number }),
+            Err(_) => Err(()),
+        }
+    }
+}
+
+// ----
+// raw responses from GitHub for pull request commits
+// ----
+
+#[derive(serde::Deserialize)]
+struct GitHubPullRequestCommit {
+    sha: String,
+}
+
+// ----
+// end of raw responses from GitHub for pull request commits
+// ----
+
+/// Start hotfix release 'version' (e.g., '0.99.1'), by cherry-picking commits
+/// 'picks' and the commits of pull requests 'prs' onto the existing release
+/// branches, and then starting its first release candidate. Should any commit
+/// fail to apply, conflicts are reported per repository and the release
+/// branches are left untouched.
+///
+pub async fn hotfix(
+    release: &mut Release,
+    version: &Version,
+    notes: &Option<PathBuf>,
+    picks: &Vec<String>,
+    prs: &Vec<String>,
+) -> ReleaseResult<()> {
+    let ws = &release.ws;
+    infoln!("Refresh workspace...");
+    if let Err(()) = ws.sync() {
+        log::error!("Unable to synchronize workspace repositories!");
+        return Err(ReleaseError::SyncError);
+    }
+
+    start::check_can_start(&ws, &version)?;
+    check_release_branches(&ws, &version)?;
+
+    if let Err(()) = sync::sync(&release, &version) {
+        errorln!("Unable to synchronize release repositories!");
+        return Err(ReleaseError::SyncError);
+    }
+
+    let mut specs: Vec<PickSpec> = picks.iter().map(PickSpec::parse_commit).collect();
+    for pr in prs {
+        match PickSpec::parse_pull_request(&pr) {
+            Ok(spec) => specs.push(spec),
+            Err(()) => {
+                errorln!("Malformed pull request '{}', expected '[REPO#]NUMBER'", pr);
+                return Err(ReleaseError::UnknownError);
+            }
+        };
+    }
+    let to_pick = resolve_picks(&ws, &version, &specs).await?;
+    if to_pick.is_empty() {
+        warnln!("Nothing to cherry-pick onto the release branches.");
+    }
+
+    infoln!("Start hotfix release {}", version);
+
+    let mut first_rc = version.clone();
+    first_rc.set_rc(1);
+    let mut journal = ReleaseJournal::begin(&ws, &version, &first_rc, notes.as_ref())?;
+
+    if let Err(err) = cherry_pick(&version, &to_pick, &mut journal) {
+        if journal.entries.is_empty() {
+            journal.finish()?;
+        } else {
+            infoln!("Rolling back cherry-picks...");
+            journal.rollback(&ws)?;
+        }
+        return Err(err);
+    }
+
+    // bind this workspace to the release, as we do when starting a release.
+    release.state = Some(ReleaseState {
+        release_version: version.clone(),
+    });
+    if let Err(()) = release.write() {
+        boomln!("Unable to write release state file!");
+        return Err(ReleaseError::UnknownError);
+    }
+
+    // release notes are generated once the fixes are on the release branches.
+    let notes = match notes {
+        Some(n) => n.clone(),
+        None => changelog::write_draft(&release, &version)?,
+    };
+
+    let ws = &release.ws;
+    match start::start_release_candidate(&ws, &version, Some(&notes)) {
+        Ok(ver) => {
+            if ver.rc() != Some(1) {
+                boomln!("Release is corrupted. Expected '-rc1', got '{}'!", ver);
+                return Err(ReleaseError::CorruptedError);
+            }
+        }
+        Err(err) => {
+            errorln!("Unable to start v{}-rc1: {}", version, err);
+            return Err(err);
+        }
+    };
+
+    Ok(())
+}
+
+/// A hotfix is based on existing release branches, which must exist for all
+/// repositories.
+///
+fn check_release_branches(ws: &Workspace, version: &Version) -> ReleaseResult<()> {
+    let base_version = version.get_base_version();
+    let mut missing: Vec<String> = vec![];
+    for repo in ws.repos.as_vec() {
+        match repo.get_release_branches() {
+            Ok(branches) => {
+                if !branches.contains_key(&base_version) {
+                    missing.push(repo.name.clone());
+                }
+            }
+            Err(err) => {
+                log::error!("Unable to obtain release branches: {}", err);
+                return Err(ReleaseError::UnknownError);
+            }
+        };
+    }
+
+    if !missing.is_empty() {
+        errorln!(
+            "Release branches for v{} not found on repositories {}",
+            base_version,
+            missing.join(", ")
+        );
+        infoln!("Maybe you want to 'start' a release instead?");
+        return Err(ReleaseError::BranchNotFoundError);
+    }
+
+    Ok(())
+}
+
+/// Resolve 'specs' into the commits to cherry-pick per repository, in the
+/// order they have been provided. Commits not bound to a repository must be
+/// found in exactly one repository. Commits already on the release branch are
+/// skipped.
+///
+async fn resolve_picks<'a>(
+    ws: &'a Workspace,
+    version: &Version,
+    specs: &Vec<PickSpec>,
+) -> ReleaseResult<Vec<(&'a Repository, Vec<String>)>> {
+    let mut commits: Vec<(&Repository, String)> = vec![];
+
+    for spec in specs {
+        match spec {
+            PickSpec::Commit { repo, rev } => {
+                let found = find_commit(&ws, &repo, &rev)?;
+                commits.push(found);
+            }
+            PickSpec::PullRequest { repo, number } => {
+                let repo = match repo {
+                    None => ws.repos.main(),
+                    Some(name) => get_repo(&ws, &name)?,
+                };
+                for sha1 in get_pull_request_commits(&ws, &repo, *number).await? {
+                    commits.push((repo, sha1));
+                }
+            }
+        };
+    }
+
+    let mut picks: Vec<(&Repository, Vec<String>)> = ws
+        .repos
+        .as_vec()
+        .into_iter()
+        .map(|repo| (repo, vec![]))
+        .collect();
+    for (repo, sha1) in commits {
+        match repo.is_on_release_branch(&version, &sha1) {
+            Ok(true) => {
+                warnln!(
+                    "Commit {} already on release branch for '{}', skip.",
+                    sha1,
+                    repo.name
+                );
+                continue;
+            }
+            Ok(false) => {}
+            Err(err) => {
+                errorln!(
+                    "Unable to check commit {} on '{}' release branch: {}",
+                    sha1,
+                    repo.name,
+                    err
+                );
+                return Err(ReleaseError::UnknownError);
+            }
+        };
+        let (_, repo_picks) = picks.iter_mut().find(|(r, _)| r.name == repo.name).unwrap();
+        if !repo_picks.contains(&sha1) {
+            repo_picks.push(sha1);
+        }
+    }
+
+    Ok(picks.into_iter().filter(|(_, c)| !c.is_empty()).collect())
+}
+
+fn get_repo<'a>(ws: &'a Workspace, name: &String) -> ReleaseResult<&'a Repository> {
+    match ws.repos.get(&name) {
+        Some(r) => Ok(r),
+        None => {
+            errorln!("Unknown repository '{}'", name);
+            Err(ReleaseError::UnknownError)
+        }
+    }
+}
+
+/// Find commit 'rev' on repository 'repo' if provided, or on whichever
+/// repository has it otherwise.
+///
+fn find_commit<'a>(
+    ws: &'a Workspace,
+    repo: &Option<String>,
+    rev: &String,
+) -> ReleaseResult<(&'a Repository, String)> {
+    let candidates = match repo {
+        Some(name) => vec![get_repo(&ws, &name)?],
+        None => ws.repos.as_vec(),
+    };
+
+    let mut found: Vec<(&Repository, String)> = vec![];
+    for repo in candidates {
+        match repo.find_commit(&rev) {
+            Ok(Some(sha1)) => found.push((repo, sha1)),
+            Ok(None) => {}
+            Err(err) => {
+                errorln!(
+                    "Unable to look up commit '{}' on '{}': {}",
+                    rev,
+                    repo.name,
+                    err
+                );
+                return Err(ReleaseError::UnknownError);
+            }
+        };
+    }
+
+    match found.len() {
+        0 => {
+            errorln!("Commit '{}' not found", rev);
+            Err(ReleaseError::UnknownError)
+        }
+        1 => Ok(found.pop().unwrap()),
+        _ => {
+            errorln!(
+                "Commit '{}' found on multiple repositories ({}), use 'REPO:{}'",
+                rev,
+                found
+                    .iter()
+                    .map(|(r, _)| r.name.clone())
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                rev
+            );
+            Err(ReleaseError::UnknownError)
+        }
+    }
+}
+
+/// Obtain the commits of pull request 'number' on repository 'repo', in the
+/// order they have been committed, fetching them from the remote.
+///
+async fn get_pull_request_commits(
+    ws: &Workspace,
+    repo: &Repository,
+    number: u64,
+) -> ReleaseResult<Vec<String>> {
+    let gh_config = match &repo.config.github {
+        Some(c) if c.is_github() => c,
+        _ => {
+            errorln!(
+                "GitHub repository not configured for '{}', can't obtain pull request {}",
+                repo.name,
+                number
+            );
+            return Err(ReleaseError::UnknownError);
+        }
+    };
+    let github_token = common::get_forge_token(&ws, &repo)?;
+
+    let api_url = gh_config.get_api_url(&format!("pulls/{}/commits", number));
+    let client = crate::forge::GitHubClient::new(&github_token);
+    let commits = match client
+        .get_all_pages::<Vec<GitHubPullRequestCommit>>(&api_url, &[])
+        .await
+    {
+        Ok(Some(pages)) => pages
+            .into_iter()
+            .flatten()
+            .collect::<Vec<GitHubPullRequestCommit>>(),
+        Ok(None) => {
+            errorln!("Pull request {} not found on '{}'", number, repo.name);
+            return Err(ReleaseError::UnknownError);
+        }
+        Err(err) => {
+            errorln!(
+                "Unable to obtain pull request {} commits on '{}': {}",
+                number,
+                repo.name,
+                err
+            );
+            return Err(ReleaseError::UnknownError);
+        }
+    };
+
+    if let Err(err) = repo.fetch_pull_request(number) {
+        errorln!("Unable to fetch pull request {}: {}", number, err);
+        return Err(ReleaseError::UnknownError);
+    }
+
+    infoln!(
+        "Pull request {} on '{}' has {} commit(s)",
+        number,
+        repo.name,
+        commits.len()
+    );
+    Ok(commits.into_iter().map(|c| c.sha).collect())
+}
+
+/// Cherry-pick 'picks' onto each repository's release branch for 'version'.
+/// All repositories are attempted, so that conflicts are reported for all of
+/// them at once.
+///
+fn cherry_pick(
+    version: &Version,
+    picks: &Vec<(&Repository, Vec<String>)>,
+    journal: &mut ReleaseJournal,
+) -> ReleaseResult<()> {
+    let mut failed = false;
+
+    infoln!("Cherry-picking commits...");
+    for (repo, commits) in picks {
+        let branch = version.to_str_fmt(&repo.config.release_branch_format);
+        let previous = common::get_release_branch_head(&repo, &version)?;
+        let step = JournalStep::CherryPicked {
+            branch: branch.clone(),
+            previous,
+            commits: commits.clone(),
+        };
+        if journal.has(&repo, &step) {
+            continue;
+        }
+
+        match repo.cherry_pick(&commits) {
+            Ok(None) => {
+                successln!(
+                    "{}: cherry-picked {} commit(s) onto '{}'",
+                    repo.name,
+                    commits.len(),
+                    branch
+                );
+                journal.record(&repo, step)?;
+            }
+            Ok(Some(conflict)) => {
+                failed = true;
+                if conflict.paths.is_empty() {
+                    errorln!(
+                        "{}: commit {} is empty on '{}'",
+                        repo.name,
+                        conflict.commit,
+                        branch
+                    );
+                } else {
+                    errorln!(
+                        "{}: commit {} conflicts on '{}':",
+                        repo.name,
+                        conflict.commit,
+                        branch
+                    );
+                    for path in &conflict.paths {
+                        errorln!("    {}", path);
+                    }
+                }
+            }
+            Err(err) => {
+                failed = true;
+                errorln!(
+                    "{}: unable to cherry-pick onto '{}': {}",
+                    repo.name,
+                    branch,
+                    err
+                );
+            }
+        };
+    }
+
+    if failed {
+        errorln!("Unable to cherry-pick all commits onto the release branches.");
+        return Err(ReleaseError::CherryPickError);
+    }
+
+    Ok(())
+}
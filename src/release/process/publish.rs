@@ -0,0 +1,182 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    errorln,
+    forge::ReleaseRequest,
+    infoln,
+    release::{
+        common,
+        errors::{ReleaseError, ReleaseResult},
+        Release,
+    },
+    successln,
+    version::Version,
+    ws::{dryrun::DryRunAction, repository::Repository, workspace::Workspace},
+};
+
+/// Publish a release for 'version''s tag on the 's3gw' repository, and on
+/// repositories 'repos', on each repository's forge, with the release's notes
+/// as its body. Release candidates are published as pre-releases, on forges
+/// supporting those. Existing releases are updated, if needed, so publishing
+/// can be safely repeated.
+///
+pub async fn publish(
+    release: &Release,
+    version: &Version,
+    repos: &Vec<String>,
+) -> ReleaseResult<()> {
+    let ws = &release.ws;
+    infoln!("Refresh workspace...");
+    if let Err(()) = ws.sync() {
+        errorln!("Unable to synchronize workspace repositories!");
+        return Err(ReleaseError::SyncError);
+    }
+
+    let mut targets: Vec<&Repository> = vec![ws.repos.main()];
+    for name in repos {
+        match ws.repos.get(&name) {
+            Some(r) => {
+                if !targets.iter().any(|t| t.name == r.name) {
+                    targets.push(r);
+                }
+            }
+            None => {
+                errorln!("Unknown repository '{}'", name);
+                return Err(ReleaseError::UnknownError);
+            }
+        };
+    }
+
+    let notes = match common::get_release_notes(&ws, &version)? {
+        Some(v) => v,
+        None => {
+            errorln!("Release notes for v{} not found on 's3gw'", version);
+            return Err(ReleaseError::PublishError);
+        }
+    };
+
+    for repo in targets {
+        publish_repo(&ws, &repo, &version, &notes).await?;
+    }
+
+    Ok(())
+}
+
+/// Publish, or update, the release for 'version''s tag on repository 'repo''s
+/// forge.
+///
+async fn publish_repo(
+    ws: &Workspace,
+    repo: &Repository,
+    version: &Version,
+    notes: &String,
+) -> ReleaseResult<()> {
+    let kind = match &repo.config.github {
+        None => {
+            errorln!(
+                "Forge not configured for '{}', can't publish release!",
+                repo.name
+            );
+            return Err(ReleaseError::PublishError);
+        }
+        Some(c) => c.kind,
+    };
+
+    let tag = repo.get_version_tag_name(&version);
+    match repo.find_commit(&repo.get_version_tag_refspec(&version)) {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            errorln!("Tag '{}' not found on '{}'", tag, repo.name);
+            return Err(ReleaseError::PublishError);
+        }
+        Err(err) => {
+            errorln!(
+                "Unable to look up tag '{}' on '{}': {}",
+                tag,
+                repo.name,
+                err
+            );
+            return Err(ReleaseError::UnknownError);
+        }
+    };
+
+    let req = ReleaseRequest {
+        tag: tag.clone(),
+        name: format!("v{}", version),
+        body: notes.clone(),
+        prerelease: version.is_prerelease(),
+    };
+
+    let forge = common::get_forge(&ws, &repo)?;
+    let existing = match forge.get_release(&tag).await {
+        Ok(v) => v,
+        Err(err) => {
+            errorln!("Unable to obtain {} release for '{}': {}", kind, tag, err);
+            return Err(ReleaseError::UnknownError);
+        }
+    };
+    if let Some(r) = &existing {
+        if r.matches(&req) {
+            infoln!(
+                "{} release for '{}' on '{}' already published at {}",
+                kind,
+                tag,
+                repo.name,
+                r.url
+            );
+            return Ok(());
+        }
+    }
+
+    if repo.dry_run_record(DryRunAction::GitHubRelease {
+        tag: tag.clone(),
+        prerelease: req.prerelease,
+        update: existing.is_some(),
+    }) {
+        return Ok(());
+    }
+
+    let res = match &existing {
+        Some(r) => forge.update_release(&r, &req).await,
+        None => forge.create_release(&req).await,
+    };
+    match res {
+        Ok(url) => {
+            successln!(
+                "{} {} release for '{}' on '{}' at {}",
+                if existing.is_some() {
+                    "Updated"
+                } else {
+                    "Published"
+                },
+                kind,
+                tag,
+                repo.name,
+                url
+            );
+            Ok(())
+        }
+        Err(err) => {
+            errorln!(
+                "Unable to publish {} release for '{}' on '{}': {}",
+                kind,
+                tag,
+                repo.name,
+                err
+            );
+            Err(ReleaseError::PublishError)
+        }
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use crate::{
+    errorln,
+    forge::GitHubClient,
+    infoln,
+    release::{
+        common,
+        errors::{ReleaseError, ReleaseResult},
+        status, watch, Release,
+    },
+    successln,
+    version::Version,
+};
+
+/// Re-run the failed jobs of the release workflow for release 'version''s
+/// latest release candidate. If 'watch' is provided, then watch the new
+/// attempt until it completes, polling at the provided interval.
+///
+pub async fn rebuild(
+    release: &Release,
+    version: &Version,
+    watch: &Option<Duration>,
+) -> ReleaseResult<()> {
+    let ws = &release.ws;
+    infoln!("Refresh workspace...");
+    if let Err(()) = ws.sync() {
+        errorln!("Unable to synchronize workspace repositories!");
+        return Err(ReleaseError::SyncError);
+    }
+
+    let release_versions = common::get_release_versions(&ws, &version);
+    let latest = match release_versions.last_key_value() {
+        Some((_, v)) => v,
+        None => {
+            errorln!("Release version {} has not been started yet.", version);
+            return Err(ReleaseError::NotStartedError);
+        }
+    };
+    let tag = latest.to_rc_str_fmt(&ws.repos.main().config.tag_format);
+
+    let run = match status::get_release_status(&ws, &latest).await {
+        Ok(Some(run)) => run,
+        Ok(None) => {
+            errorln!("Release workflow for '{}' not found", tag);
+            return Err(ReleaseError::ReleaseBuildNotFoundError);
+        }
+        Err(()) => {
+            errorln!("Unable to obtain release workflow for '{}'", tag);
+            return Err(ReleaseError::UnknownError);
+        }
+    };
+    if run.is_waiting() {
+        errorln!("Release workflow for '{}' is still running.", tag);
+        return Err(ReleaseError::ReleaseBuildOnGoingError);
+    } else if run.success {
+        errorln!("Release workflow for '{}' has already succeeded.", tag);
+        return Err(ReleaseError::ReleaseBuildSucceededError);
+    }
+
+    let repo = ws.repos.main();
+    let github_config = match &repo.config.github {
+        Some(c) if c.is_github() => c,
+        _ => {
+            errorln!("Rebuilding releases is only available for repositories on GitHub");
+            return Err(ReleaseError::RebuildError);
+        }
+    };
+    let github_token = common::get_forge_token(&ws, &repo)?;
+
+    let api_url = github_config.get_api_url(&format!("actions/runs/{}/rerun-failed-jobs", run.id));
+    let client = GitHubClient::new(&github_token);
+    let response = match client
+        .send(client.request(reqwest::Method::POST, &api_url))
+        .await
+    {
+        Ok(r) => r,
+        Err(err) => {
+            errorln!("Unable to re-run release workflow for '{}': {}", tag, err);
+            return Err(ReleaseError::RebuildError);
+        }
+    };
+    if !response.status().is_success() {
+        errorln!(
+            "Unable to re-run release workflow for '{}': {}",
+            tag,
+            response.status()
+        );
+        return Err(ReleaseError::RebuildError);
+    }
+
+    let attempt = run.num_attempts.unwrap_or(1) + 1;
+    successln!(
+        "Re-running failed jobs of release workflow for '{}' (attempt {}).",
+        tag,
+        attempt
+    );
+
+    let poll_interval = match watch {
+        Some(v) => v,
+        None => {
+            if let Some(url) = &run.url {
+                infoln!("Check the workflow run at {}", url);
+            }
+            return Ok(());
+        }
+    };
+
+    let run =
+        watch::wait_for_release_workflow_attempt(&ws, &latest, &poll_interval, attempt).await?;
+    if run.is_failed() {
+        watch::report_failure(&run);
+        return Err(ReleaseError::ReleaseBuildFailedError);
+    }
+    successln!("Release workflow for '{}' succeeded.", run.tag);
+    Ok(())
+}
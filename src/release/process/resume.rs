@@ -0,0 +1,118 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+use crate::{
+    boomln, errorln, infoln,
+    release::{
+        errors::{ReleaseError, ReleaseResult},
+        journal::ReleaseJournal,
+        process::{finish, start},
+        sync, Release, ReleaseState,
+    },
+    successln,
+};
+
+/// Resume an unfinished release, as recorded in the workspace's release
+/// journal. Steps already performed are skipped.
+///
+pub async fn resume(release: &mut Release) -> ReleaseResult<()> {
+    let mut journal = match ReleaseJournal::load(&release.ws)? {
+        None => {
+            infoln!("No unfinished release found.");
+            return Ok(());
+        }
+        Some(j) => j,
+    };
+    let relver = journal.relver.clone();
+    let tagver = journal.tagver.clone();
+
+    infoln!("Resuming release '{}'", tagver);
+    if let Err(()) = release.ws.sync() {
+        errorln!("Unable to synchronize workspace repositories!");
+        return Err(ReleaseError::SyncError);
+    }
+
+    // the release branches may not have all been cut if we failed while
+    // starting the release, in which case the remaining are cut before the
+    // workspace is bound to the release.
+    if tagver.rc() == Some(1) {
+        if let Err(err) = start::create_release_branches(&release.ws, &relver, &mut journal, true) {
+            errorln!("Unable to create release branches: {}", err);
+            infoln!("Use 'arc rel rollback' to undo the release instead.");
+            return Err(err);
+        }
+    }
+
+    // the release state may not have been written yet if we failed while
+    // starting the release.
+    if release.state.is_none() {
+        release.state = Some(ReleaseState {
+            release_version: relver.clone(),
+        });
+        if let Err(()) = release.write() {
+            boomln!("Unable to write release state file!");
+            return Err(ReleaseError::UnknownError);
+        }
+    }
+
+    if let Err(()) = sync::sync(&release, &relver) {
+        errorln!("Unable to synchronize release repositories!");
+        return Err(ReleaseError::SyncError);
+    }
+
+    let ws = &release.ws;
+    if tagver.is_prerelease() {
+        let notes: Option<PathBuf> = journal.notes.clone();
+        start::perform_release(&ws, &relver, &tagver, &notes.as_ref(), &mut journal)?;
+    } else {
+        finish::finish_release(&ws, &tagver, &mut journal).await?;
+    }
+    journal.finish()?;
+
+    successln!("Resumed and completed release '{}'", tagver);
+    Ok(())
+}
+
+/// Roll back an unfinished release, as recorded in the workspace's release
+/// journal. If the release was being started, the release state is removed
+/// as well.
+///
+pub fn rollback(release: &mut Release) -> ReleaseResult<()> {
+    let journal = match ReleaseJournal::load(&release.ws)? {
+        None => {
+            infoln!("No unfinished release found.");
+            return Ok(());
+        }
+        Some(j) => j,
+    };
+    let first_rc = journal.tagver.rc() == Some(1);
+    let tagver = journal.tagver.clone();
+
+    journal.rollback(&release.ws)?;
+
+    if first_rc {
+        let path = release.confdir.join("release.json");
+        if path.exists() {
+            if let Err(err) = std::fs::remove_file(&path) {
+                errorln!("Unable to remove release state for '{}': {}", tagver, err);
+                return Err(ReleaseError::UnknownError);
+            }
+        }
+        release.state = None;
+    }
+
+    Ok(())
+}
@@ -14,10 +14,14 @@
 
 use std::path::PathBuf;
 
-use crate::release::common::{get_release_versions, get_release_versions_from_repo};
+use crate::release::changelog;
+use crate::release::common::{self, get_release_versions, get_release_versions_from_repo};
 use crate::release::errors::ReleaseResult;
-use crate::release::process::submodules::{get_submodules, update_submodules};
+use crate::release::journal::{JournalStep, ReleaseJournal};
+use crate::release::process::submodules::update_submodules;
 use crate::version::Version;
+use crate::ws::config::WSGitRepoRole;
+use crate::ws::dryrun::DryRunAction;
 use crate::ws::workspace::Workspace;
 use crate::{
     boomln, errorln, infoln,
@@ -28,7 +32,12 @@ use crate::{
 
 use crate::release::Release;
 
-pub fn start(release: &mut Release, version: &Version, notes: &PathBuf) -> ReleaseResult<()> {
+pub fn start(
+    release: &mut Release,
+    version: &Version,
+    notes: &Option<PathBuf>,
+    assume_yes: bool,
+) -> ReleaseResult<()> {
     // 1. sync rw repos to force authorized connect
     // 2. check all repos for existing versions
     // 2.1. make sure this version has not been started in any of the
@@ -45,43 +54,22 @@ pub fn start(release: &mut Release, version: &Version, notes: &PathBuf) -> Relea
         }
     };
 
-    let avail = get_release_versions(&ws, &version);
-
-    if avail.iter().any(|(_, ver)| ver == version) {
-        warnln!("Version {} has already been released.", version);
-        return Err(ReleaseError::ReleaseExistsError);
-    }
-
-    if avail.len() > 0 {
-        warnln!("Release version {} has already been started.", version);
-        return Err(ReleaseError::ReleaseStartedError);
-    }
-
-    // Check whether the release has been started across the various
-    // repositories. If it has been started in any one repository, yet not
-    // started in the 's3gw' repository (otherwise it would have been caught
-    // above), then we have a potentially corrupted release state.
+    check_can_start(&ws, &version)?;
 
-    let mut started_repos: Vec<String> = vec![];
-    for repo in ws.repos.as_vec() {
-        let versions = get_release_versions_from_repo(&repo, &version);
-        if versions.len() > 0 {
-            started_repos.push(repo.name.clone());
-        }
-    }
-    if started_repos.len() > 0 {
-        warnln!(
-            "Release version {} has been started in some repositories: {}",
-            version,
-            started_repos.join(", ")
-        );
-        errorln!("Release potentially corrupted!");
-        return Err(ReleaseError::CorruptedError);
-    }
+    let notes = match notes {
+        Some(n) => n.clone(),
+        None => changelog::write_draft(&release, &version)?,
+    };
 
     infoln!("Start releasing version {}", version);
 
-    match create_release_branches(&ws, &version) {
+    // journal the release steps for the first release candidate, starting
+    // with the branch cuts, so they can be rolled back if needed.
+    let mut first_rc = version.clone();
+    first_rc.set_rc(1);
+    let mut journal = ReleaseJournal::begin(&ws, &version, &first_rc, Some(&notes))?;
+
+    match create_release_branches(&ws, &version, &mut journal, assume_yes) {
         Ok(true) => {
             successln!("Created release branches.");
         }
@@ -90,6 +78,11 @@ pub fn start(release: &mut Release, version: &Version, notes: &PathBuf) -> Relea
         }
         Err(err) => {
             errorln!("Error creating release branches: {}", err);
+            if journal.entries.is_empty() {
+                journal.finish()?;
+            } else {
+                infoln!("Use 'arc rel rollback' to undo the branches created.");
+            }
             return Err(err);
         }
     };
@@ -121,7 +114,7 @@ pub fn start(release: &mut Release, version: &Version, notes: &PathBuf) -> Relea
     // start a new release version release candidate.
     match start_release_candidate(&ws, &version, Some(&notes)) {
         Ok(ver) => {
-            if let Some(rc) = ver.rc {
+            if let Some(rc) = ver.rc() {
                 if rc != 1 {
                     // somehow this is not an "-rc1", which is unexpected
                     // given we are just starting a new release. Consider
@@ -144,17 +137,66 @@ pub fn start(release: &mut Release, version: &Version, notes: &PathBuf) -> Relea
     Ok(())
 }
 
-/// Prepare release branches by creating them if necessary.
+/// Check whether release 'version' can be started: it must not have been
+/// released nor started in any of the workspace's repositories.
 ///
-fn create_release_branches(ws: &Workspace, version: &Version) -> ReleaseResult<bool> {
+pub fn check_can_start(ws: &Workspace, version: &Version) -> ReleaseResult<()> {
+    let avail = get_release_versions(&ws, &version);
+
+    if avail.iter().any(|(_, ver)| ver == version) {
+        warnln!("Version {} has already been released.", version);
+        return Err(ReleaseError::ReleaseExistsError);
+    }
+
+    if avail.len() > 0 {
+        warnln!("Release version {} has already been started.", version);
+        return Err(ReleaseError::ReleaseStartedError);
+    }
+
+    // Check whether the release has been started across the various
+    // repositories. If it has been started in any one repository, yet not
+    // started in the 's3gw' repository (otherwise it would have been caught
+    // above), then we have a potentially corrupted release state.
+
+    let mut started_repos: Vec<String> = vec![];
+    for repo in ws.repos.as_vec() {
+        let versions = get_release_versions_from_repo(&repo, &version);
+        if versions.len() > 0 {
+            started_repos.push(repo.name.clone());
+        }
+    }
+    if started_repos.len() > 0 {
+        warnln!(
+            "Release version {} has been started in some repositories: {}",
+            version,
+            started_repos.join(", ")
+        );
+        errorln!("Release potentially corrupted!");
+        return Err(ReleaseError::CorruptedError);
+    }
+
+    Ok(())
+}
+
+/// Prepare release branches by creating them if necessary. Unless
+/// 'assume_yes' is true, the user is asked to confirm cutting the branches.
+/// Branch cuts left unfinished by a failed release start, as recorded in
+/// 'journal', are completed.
+///
+pub fn create_release_branches(
+    ws: &Workspace,
+    version: &Version,
+    journal: &mut ReleaseJournal,
+    assume_yes: bool,
+) -> ReleaseResult<bool> {
     let mut res = false;
     // check whether we need to cut branches for each repository
-    match maybe_cut_branches(&ws, &version) {
+    match maybe_cut_branches(&ws, &version, &journal, assume_yes) {
         Ok(None) => {
             log::info!("Branches ready to start release!");
         }
         Ok(Some(repos)) => {
-            match cut_branches_for(&version, &repos) {
+            match cut_branches_for(&version, &repos, journal) {
                 Ok(()) => {
                     log::info!("Success cutting branches for v{}", version);
                     res = true;
@@ -175,17 +217,28 @@ fn create_release_branches(ws: &Workspace, version: &Version) -> ReleaseResult<b
 }
 
 /// Check whether we need to cut release branches, and, if so, for which repositories.
+/// Only some repositories may need cutting if the remaining have had their
+/// branches cut, as recorded in 'journal', by a release start that failed.
 ///
 fn maybe_cut_branches<'a>(
     ws: &'a Workspace,
     version: &Version,
+    journal: &ReleaseJournal,
+    assume_yes: bool,
 ) -> ReleaseResult<Option<Vec<&'a Repository>>> {
     let repos = ws.repos.as_vec();
     let base_version = version.get_base_version();
-    let base_version_id = base_version.get_version_id();
 
     let mut repos_to_cut: Vec<&Repository> = vec![];
+    let mut repos_cut = 0;
     for repo in &repos {
+        let cut = JournalStep::BranchCreated {
+            branch: version.to_str_fmt(&repo.config.release_branch_format),
+        };
+        if journal.has(&repo, &cut) {
+            repos_cut += 1;
+            continue;
+        }
         let branches = match repo.get_release_branches() {
             Ok(v) => v,
             Err(err) => {
@@ -193,17 +246,17 @@ fn maybe_cut_branches<'a>(
                 return Err(ReleaseError::UnknownError);
             }
         };
-        for (k, v) in &branches {
-            log::debug!("Found branch '{}' ({})", v, k);
+        for v in branches.values() {
+            log::debug!("Found branch '{}'", v);
         }
-        if !branches.contains_key(&base_version_id) {
+        if !branches.contains_key(&base_version) {
             repos_to_cut.push(repo);
         }
     }
 
     if repos_to_cut.len() == 0 {
         return Ok(None);
-    } else if repos_to_cut.len() != repos.len() {
+    } else if repos_to_cut.len() + repos_cut != repos.len() {
         return Err(ReleaseError::CorruptedError);
     }
 
@@ -216,6 +269,9 @@ fn maybe_cut_branches<'a>(
             .collect::<Vec<String>>()
             .join(", ")
     );
+    if assume_yes {
+        return Ok(Some(repos_to_cut));
+    }
     match inquire::Confirm::new("Cut required branches?")
         .with_default(true)
         .prompt()
@@ -235,20 +291,28 @@ fn maybe_cut_branches<'a>(
 }
 
 /// Cut release branches for the provided repositories, for the provided
-/// release version.
+/// release version. Each branch cut is recorded in 'journal'.
 ///
-fn cut_branches_for(version: &Version, repos: &Vec<&Repository>) -> ReleaseResult<()> {
+fn cut_branches_for(
+    version: &Version,
+    repos: &Vec<&Repository>,
+    journal: &mut ReleaseJournal,
+) -> ReleaseResult<()> {
     for repo in repos {
         log::info!("cut branch for repository {}", repo.name);
-        match repo.branch_version_from_default(&version) {
+        let branch = version.to_str_fmt(&repo.config.release_branch_format);
+        journal.run(&repo, JournalStep::BranchCreated { branch }, || match repo
+            .branch_version_from_default(&version)
+        {
             Ok(()) => {
                 log::info!("branched off!");
+                Ok(())
             }
             Err(err) => {
                 log::error!("error branching off: {}", err);
-                return Err(ReleaseError::UnknownError);
+                Err(ReleaseError::UnknownError)
             }
-        }
+        })?;
     }
 
     Ok(())
@@ -269,8 +333,11 @@ pub fn start_release_candidate(
     let next_rc = match avail_versions.last_key_value() {
         None => 1_u64,
         Some((_, v)) => {
-            if let Some(rc) = v.rc {
+            if let Some(rc) = v.rc() {
                 rc + 1
+            } else if v.is_prerelease() {
+                // release candidates follow any alpha or beta pre-releases.
+                1_u64
             } else {
                 log::error!("Highest version is not an RC. Maybe release? Found: {}", v);
                 return Err(ReleaseError::UnknownError);
@@ -279,101 +346,174 @@ pub fn start_release_candidate(
     };
 
     let mut next_ver = relver.clone();
-    next_ver.rc = Some(next_rc);
+    next_ver.set_rc(next_rc);
 
     infoln!("Start next release candidate '{}': {}", next_rc, next_ver);
 
-    match perform_release(&ws, &relver, &next_ver, &notes) {
+    let mut journal = ReleaseJournal::begin(&ws, &relver, &next_ver, notes)?;
+    match perform_release(&ws, &relver, &next_ver, &notes, &mut journal) {
         Ok(()) => {
             successln!("Started release ver '{}' tag '{}'", relver, next_ver);
-            Ok(next_ver)
         }
         Err(err) => {
             errorln!("Error performing release {}: {}", next_ver, err);
-            Err(err)
+            if ws.dry_run.is_none() {
+                infoln!("Use 'arc rel resume' to retry, or 'arc rel rollback' to undo.");
+            }
+            return Err(err);
         }
-    }
+    };
+    journal.finish()?;
+
+    Ok(next_ver)
 }
 
 /// Perform a release, by creating appropriate tags and ensuring the 's3gw' repo
 /// represents the correct state for said release.
 /// This is used to start a new release candidate, as well to finish a release.
+/// Each completed step is recorded in 'journal', and steps already recorded
+/// are skipped, so that a failed release can be resumed.
 ///
 pub fn perform_release(
     ws: &Workspace,
     relver: &Version,
     next_ver: &Version,
     notes: &Option<&PathBuf>,
+    journal: &mut ReleaseJournal,
 ) -> ReleaseResult<()> {
-    // start release candidate on the various repositories, except
-    // 's3gw.git'.
-    let mut submodules = get_submodules(&ws);
+    // start release candidate on the various repositories, except the main
+    // repository (i.e., 's3gw.git').
+    let repos: Vec<&Repository> = ws
+        .repos
+        .as_vec()
+        .into_iter()
+        .filter(|r| r.role != WSGitRepoRole::Main)
+        .collect();
 
     infoln!("Tagging repositories...");
-    for entry in &mut submodules {
-        log::debug!(
-            "Tagging repository '{}' with version '{}'",
-            entry.repo.name,
-            next_ver
-        );
-        match entry.repo.tag_release_branch(&relver, &next_ver) {
-            Ok((tag_name, tag_oid)) => {
-                log::debug!(
-                    "Tagged version '{}' with '{}' oid {} name {}",
-                    relver,
-                    next_ver,
-                    tag_oid,
-                    tag_name,
-                );
-            }
-            Err(err) => {
-                errorln!(
-                    "Error tagging version '{}' with '{}': {}",
-                    relver,
-                    next_ver,
-                    err
-                );
-                return Err(ReleaseError::TaggingError);
-            }
-        };
+    for repo in &repos {
+        let tag = next_ver.to_rc_str_fmt(&repo.config.tag_format);
+        journal.run(&repo, JournalStep::TagCreated { tag }, || {
+            tag_repository(&repo, &relver, &next_ver)
+        })?;
     }
 
     // repositories have been tagged -- push them out so we can update the
     // submodules on 's3gw.git'.
     infoln!("Pushing repositories...");
-    for entry in &submodules {
-        log::debug!("Pushing '{}' to repository '{}'", relver, entry.name);
-        match entry.repo.push_release_branch(&relver) {
+    for repo in &repos {
+        push_repository(&repo, &relver, &next_ver, journal)?;
+    }
+
+    let branch = relver.to_str_fmt(&ws.repos.main().config.release_branch_format);
+    let previous = common::get_release_branch_head(&ws.repos.main(), &relver)?;
+    journal.run(
+        &ws.repos.main(),
+        JournalStep::Committed { branch, previous },
+        || commit_release(&ws, &relver, &next_ver, &notes),
+    )?;
+
+    let tag = next_ver.to_rc_str_fmt(&ws.repos.main().config.tag_format);
+    journal.run(&ws.repos.main(), JournalStep::TagCreated { tag }, || {
+        tag_repository(&ws.repos.main(), &relver, &next_ver)
+    })?;
+
+    // finally, push the branch and the release tag.
+    push_repository(&ws.repos.main(), &relver, &next_ver, journal)?;
+
+    Ok(())
+}
+
+/// Tag repository 'repo' release branch for 'relver' with 'tagver'.
+///
+fn tag_repository(repo: &Repository, relver: &Version, tagver: &Version) -> ReleaseResult<()> {
+    log::debug!(
+        "Tagging repository '{}' with version '{}'",
+        repo.name,
+        tagver
+    );
+    match repo.tag_release_branch(&relver, &tagver) {
+        Ok((tag_name, tag_oid)) => {
+            log::debug!(
+                "Tagged version '{}' with '{}' oid {} name {}",
+                relver,
+                tagver,
+                tag_oid,
+                tag_name,
+            );
+            Ok(())
+        }
+        Err(err) => {
+            errorln!(
+                "Error tagging version '{}' with '{}' on repository '{}': {}",
+                relver,
+                tagver,
+                repo.name,
+                err
+            );
+            Err(ReleaseError::TaggingError)
+        }
+    }
+}
+
+/// Push repository 'repo' release branch for 'relver' and its 'tagver' tag,
+/// recording each push in 'journal'.
+///
+fn push_repository(
+    repo: &Repository,
+    relver: &Version,
+    tagver: &Version,
+    journal: &mut ReleaseJournal,
+) -> ReleaseResult<()> {
+    let branch = relver.to_str_fmt(&repo.config.release_branch_format);
+    journal.run(&repo, JournalStep::BranchPushed { branch }, || {
+        log::debug!("Pushing '{}' to repository '{}'", relver, repo.name);
+        match repo.push_release_branch(&relver) {
             Ok(()) => {
-                log::debug!("Pushed '{}' to repository '{}'", relver, entry.name);
+                log::debug!("Pushed '{}' to repository '{}'", relver, repo.name);
+                Ok(())
             }
             Err(err) => {
                 errorln!(
                     "Error pushing '{}' to repository '{}': {}",
                     relver,
-                    entry.name,
+                    repo.name,
                     err
                 );
-                return Err(ReleaseError::PushingError);
+                Err(ReleaseError::PushingError)
             }
-        };
+        }
+    })?;
 
-        match entry.repo.push_release_tag(&next_ver) {
+    let tag = tagver.to_rc_str_fmt(&repo.config.tag_format);
+    journal.run(&repo, JournalStep::TagPushed { tag }, || {
+        match repo.push_release_tag(&tagver) {
             Ok(()) => {
-                log::debug!("Pushed '{}' to repository '{}'!", next_ver, entry.name);
+                log::debug!("Pushed '{}' to repository '{}'!", tagver, repo.name);
+                Ok(())
             }
             Err(err) => {
                 errorln!(
                     "Error pushing '{}' to repository '{}': {}",
-                    next_ver,
-                    entry.name,
+                    tagver,
+                    repo.name,
                     err
                 );
-                return Err(ReleaseError::PushingError);
+                Err(ReleaseError::PushingError)
             }
-        };
-    }
+        }
+    })
+}
 
+/// Update the 's3gw' repository's submodules to 'next_ver', copy the release
+/// notes if provided, and commit the resulting release state.
+///
+fn commit_release(
+    ws: &Workspace,
+    relver: &Version,
+    next_ver: &Version,
+    notes: &Option<&PathBuf>,
+) -> ReleaseResult<()> {
     let mut paths_to_add: Vec<PathBuf> = vec![];
 
     // update submodules on 's3gw.git' to reflect the current state of each
@@ -398,32 +538,41 @@ pub fn perform_release(
         let release_notes_file =
             PathBuf::from(format!("s3gw-v{}.md", next_ver.get_release_version()));
         let release_notes_path = release_notes_dir.join(&release_notes_file);
-        let release_notes_path_abs = ws.repos.s3gw.path.join(&release_notes_path);
+        let release_notes_path_abs = ws.repos.main().path.join(&release_notes_path);
         let latest_path = release_notes_dir.join(PathBuf::from("latest"));
-        let latest_path_abs = ws.repos.s3gw.path.join(&latest_path);
-
-        match std::fs::copy(&notes_file, &release_notes_path_abs) {
-            Ok(_) => {}
-            Err(err) => {
-                boomln!(
-                    "Error copying notes file from '{}' to '{}': {}",
-                    notes_file.display(),
-                    release_notes_path_abs.display(),
-                    err
-                );
-                return Err(ReleaseError::UnknownError);
+        let latest_path_abs = ws.repos.main().path.join(&latest_path);
+
+        if ws.repos.main().is_dry_run() {
+            ws.repos.main().dry_run_record(DryRunAction::Write {
+                path: release_notes_path.clone(),
+            });
+            ws.repos.main().dry_run_record(DryRunAction::Write {
+                path: latest_path.clone(),
+            });
+        } else {
+            match std::fs::copy(&notes_file, &release_notes_path_abs) {
+                Ok(_) => {}
+                Err(err) => {
+                    boomln!(
+                        "Error copying notes file from '{}' to '{}': {}",
+                        notes_file.display(),
+                        release_notes_path_abs.display(),
+                        err
+                    );
+                    return Err(ReleaseError::UnknownError);
+                }
+            };
+            if latest_path_abs.is_symlink() {
+                std::fs::remove_file(&latest_path_abs).expect("Unable to remove 'latest' symlink!");
             }
-        };
-        if latest_path_abs.is_symlink() {
-            std::fs::remove_file(&latest_path_abs).expect("Unable to remove 'latest' symlink!");
+            match std::os::unix::fs::symlink(&release_notes_file, &latest_path_abs) {
+                Ok(_) => {}
+                Err(err) => {
+                    boomln!("Error updating 'latest' symlink: {}", err);
+                    return Err(ReleaseError::UnknownError);
+                }
+            };
         }
-        match std::os::unix::fs::symlink(&release_notes_file, &latest_path_abs) {
-            Ok(_) => {}
-            Err(err) => {
-                boomln!("Error updating 'latest' symlink: {}", err);
-                return Err(ReleaseError::UnknownError);
-            }
-        };
         paths_to_add.push(release_notes_path);
         paths_to_add.push(latest_path);
     }
@@ -431,7 +580,7 @@ pub fn perform_release(
     let mut force_empty_commit = false;
 
     if paths_to_add.len() > 0 {
-        match ws.repos.s3gw.stage_paths(&paths_to_add) {
+        match ws.repos.main().stage_paths(&paths_to_add) {
             Ok(()) => {
                 log::debug!(
                     "Staged paths:\n{}",
@@ -454,7 +603,7 @@ pub fn perform_release(
 
     match ws
         .repos
-        .s3gw
+        .main()
         .commit_release(&relver, &next_ver, force_empty_commit)
     {
         Ok(()) => {
@@ -471,39 +620,5 @@ pub fn perform_release(
         }
     };
 
-    // finally, push the branch and the release tag.
-    match ws.repos.s3gw.push_release_branch(&relver) {
-        Ok(()) => {
-            log::debug!("Pushed s3gw release branch for '{}'", relver);
-        }
-        Err(err) => {
-            errorln!(
-                "Error pushing s3gw release branch for '{}': {}",
-                relver,
-                err
-            );
-            return Err(ReleaseError::PushingError);
-        }
-    };
-
-    match ws.repos.s3gw.push_release_tag(&next_ver) {
-        Ok(()) => {
-            log::debug!(
-                "Pushed s3gw release tag '{}' for version '{}'",
-                next_ver,
-                relver
-            );
-        }
-        Err(err) => {
-            errorln!(
-                "Error pushing s3gw release tag '{}' for version '{}': {}",
-                next_ver,
-                relver,
-                err
-            );
-            return Err(ReleaseError::PushingError);
-        }
-    };
-
     Ok(())
 }
@@ -39,15 +39,15 @@ impl SubmoduleInfo<'_> {
     }
 }
 
-/// Obtain all known 's3gw' repository submodules
-///  note(joao): not all submodules, actually - we miss the COSI submodules, but
-///  we're not handling those right now.
-pub fn get_submodules<'b>(ws: &'b Workspace) -> Vec<SubmoduleInfo> {
-    vec![
-        SubmoduleInfo::new("ui", &ws.repos.ui),
-        SubmoduleInfo::new("charts", &ws.repos.charts),
-        SubmoduleInfo::new("ceph", &ws.repos.ceph),
-    ]
+/// Obtain all known 's3gw' repository submodules, as defined by the
+/// workspace's repositories config.
+///
+pub fn get_submodules<'b>(ws: &'b Workspace) -> Vec<SubmoduleInfo<'b>> {
+    ws.repos
+        .submodules()
+        .into_iter()
+        .map(|repo| SubmoduleInfo::new(repo.submodule.as_ref().unwrap(), &repo))
+        .collect()
 }
 
 /// Update a given 's3gw' repository's submodule to the specified tag version.
@@ -62,7 +62,7 @@ pub fn update_submodule(
 
     match ws
         .repos
-        .s3gw
+        .main()
         .set_submodule_head(&info.name, &tagver_str, true)
     {
         Ok(p) => {
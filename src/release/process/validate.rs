@@ -12,30 +12,35 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::Duration;
+
 use crate::{
     boomln, errorln, infoln,
     release::{
         errors::{ReleaseError, ReleaseResult},
-        status,
+        status, watch,
     },
     version::Version,
     warnln,
     ws::workspace::Workspace,
 };
 
-/// Check whether we can release a given version.
+/// Check whether we can release a given version. If 'wait' is provided, and
+/// the last release candidate is still being released, wait for its release
+/// workflow to complete, polling at the provided interval.
 ///
 pub async fn check_can_release(
     ws: &Workspace,
     version: &Version,
     force: bool,
+    wait: &Option<Duration>,
 ) -> ReleaseResult<()> {
     // 1. check whether release has been finished
     // 2. check whether release has been started
     // 3. check whether last release candidate has finished building
 
     let release_versions = crate::release::common::get_release_versions(&ws, &version);
-    if release_versions.contains_key(&version.get_version_id()) {
+    if release_versions.contains_key(&version) {
         errorln!("Release version {} already exists", version);
         return Err(ReleaseError::ReleaseExistsError);
     } else if release_versions.len() == 0 {
@@ -51,13 +56,26 @@ pub async fn check_can_release(
         Some((_, v)) => v,
     };
 
-    let release_status = match status::get_release_status(&ws, &last_rc).await {
+    let mut release_status = match status::get_release_status(&ws, &last_rc).await {
         Ok(v) => v,
         Err(()) => {
             boomln!("Unable to obtain latest release status!");
             return Err(ReleaseError::UnknownError);
         }
     };
+    if let Some(poll_interval) = wait {
+        let is_waiting = match &release_status {
+            None => true,
+            Some(s) => s.is_waiting(),
+        };
+        if is_waiting {
+            let run = watch::wait_for_release_workflow(&ws, &last_rc, &poll_interval).await?;
+            if run.is_failed() {
+                watch::report_failure(&run);
+            }
+            release_status = Some(run);
+        }
+    }
     match release_status {
         None => {
             errorln!(
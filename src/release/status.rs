@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashSet},
     fmt::Display,
 };
 
@@ -21,76 +21,32 @@ use colored::Colorize;
 
 use crate::{
     boomln,
+    common::OutputFormat,
     common::UpdateProgress,
     errorln,
     version::Version,
-    ws::{repository::Repository, workspace::Workspace},
+    ws::{
+        config::{WSGitRepoRole, WSWorkflowConfig},
+        repository::Repository,
+        workspace::Workspace,
+    },
 };
 
 use super::common;
+use crate::forge::{
+    graphql::{self, BatchedReleaseStatus},
+    PipelineRun, PipelineStatus,
+};
 
-// ----
-// raw responses from GitHub for workflow runs
-// ----
-
-#[derive(serde::Deserialize)]
-struct GitHubRunResult {
-    #[allow(dead_code)]
-    total_count: u64,
-    workflow_runs: Vec<GitHubWorkflowResult>,
-}
-
-#[derive(serde::Deserialize)]
-pub(crate) struct GitHubWorkflowResult {
-    name: Option<String>,
-
-    #[allow(dead_code)]
-    head_branch: Option<String>,
-    #[allow(dead_code)]
-    head_sha: String,
-
-    status: Option<String>,
-    conclusion: Option<String>,
-
-    #[allow(dead_code)]
-    display_title: String,
-    #[allow(dead_code)]
-    created_at: chrono::DateTime<chrono::Utc>,
-    #[allow(dead_code)]
-    updated_at: chrono::DateTime<chrono::Utc>,
-    run_started_at: chrono::DateTime<chrono::Utc>,
-    run_attempt: u64,
-    #[allow(dead_code)]
-    url: String,
-}
-
-/// ----
-/// end of raw responses from GitHub for workflow runs
-/// ----
-
-/// ----
-/// raw responses from Quay.io for repository tags
-/// ----
-
-#[derive(serde::Deserialize)]
-pub(crate) struct QuayRepositoryTagResult {
-    tags: HashMap<String, QuayRepositoryTagEntry>,
-}
-
-#[derive(serde::Deserialize)]
-pub(crate) struct QuayRepositoryTagEntry {
-    #[allow(dead_code)]
-    name: String,
-}
-
-/// ----
-/// end of raw responses from Quay.io for repository tags
-/// ----
-
+#[derive(serde::Serialize)]
 pub enum ReleaseWorkflowStatus {
+    #[serde(rename = "unknown")]
     UNKNOWN,
+    #[serde(rename = "queued")]
     QUEUED,
+    #[serde(rename = "in-progress")]
     INPROGRESS,
+    #[serde(rename = "completed")]
     COMPLETED,
 }
 
@@ -111,14 +67,26 @@ impl ReleaseWorkflowStatus {
     }
 }
 
+#[derive(serde::Serialize)]
 pub struct ReleaseWorkflowResult {
+    pub id: u64,
+    /// Repository the workflow run belongs to.
+    pub repo: String,
+    /// Name of the workflow.
+    pub workflow: String,
     pub tag: String,
     pub status: ReleaseWorkflowStatus,
     pub success: bool,
-    pub num_attempts: u64,
+    /// Attempt number of the run, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_attempts: Option<u64>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
     pub started_at: chrono::DateTime<chrono::Utc>,
+    pub url: Option<String>,
+    /// The run's jobs, if details were requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jobs: Option<Vec<ReleaseWorkflowJob>>,
 }
 
 impl ReleaseWorkflowResult {
@@ -164,51 +132,33 @@ impl ReleaseWorkflowResult {
         duration_str
     }
 
-    pub(crate) fn from_github_result(res: &GitHubWorkflowResult) -> ReleaseWorkflowResult {
-        let status = match &res.status {
-            None => ReleaseWorkflowStatus::UNKNOWN,
-            Some(v) => match v.as_str() {
-                "queued" => ReleaseWorkflowStatus::QUEUED,
-                "completed" => ReleaseWorkflowStatus::COMPLETED,
-                "in_progress" => ReleaseWorkflowStatus::INPROGRESS,
-                _ => {
-                    log::trace!("unknown release workflow status: {}", v);
-                    ReleaseWorkflowStatus::UNKNOWN
-                }
-            },
+    pub(crate) fn from_pipeline_run(repo: &str, run: &PipelineRun) -> ReleaseWorkflowResult {
+        let status = match run.status {
+            PipelineStatus::Queued => ReleaseWorkflowStatus::QUEUED,
+            PipelineStatus::InProgress => ReleaseWorkflowStatus::INPROGRESS,
+            PipelineStatus::Completed => ReleaseWorkflowStatus::COMPLETED,
+            PipelineStatus::Unknown => ReleaseWorkflowStatus::UNKNOWN,
         };
 
-        let success = match &res.conclusion {
-            None => {
-                log::trace!("no conclusion found!");
-                false
-            }
-            Some(v) => match v.as_str() {
-                "success" => true,
-                _ => {
-                    log::trace!("conclusing not success: {}", v);
-                    false
-                }
-            },
-        };
-
-        log::trace!("release workflow status: {}, success: {}", status, success);
-
-        let tag = match &res.head_branch {
-            Some(v) => v.clone(),
-            None => {
-                panic!("Expected head branch name on workflow result!");
-            }
-        };
+        log::trace!(
+            "release workflow status: {}, success: {}",
+            status,
+            run.success
+        );
 
         ReleaseWorkflowResult {
-            tag,
+            id: run.id,
+            repo: repo.into(),
+            workflow: run.name.clone(),
+            tag: run.tag.clone(),
             status,
-            success,
-            num_attempts: res.run_attempt,
-            created_at: res.created_at,
-            updated_at: res.updated_at,
-            started_at: res.run_started_at,
+            success: run.success,
+            num_attempts: run.attempt,
+            created_at: run.created_at,
+            updated_at: run.updated_at,
+            started_at: run.started_at,
+            url: run.url.clone(),
+            jobs: None,
         }
     }
 
@@ -227,208 +177,469 @@ impl ReleaseWorkflowResult {
     }
 }
 
-pub(crate) struct QuayStatus {
-    s3gw: HashMap<String, QuayRepositoryTagEntry>,
-    ui: HashMap<String, QuayRepositoryTagEntry>,
+/// A single job of a release workflow run.
+///
+#[derive(serde::Serialize)]
+pub struct ReleaseWorkflowJob {
+    pub name: String,
+    pub status: String,
+    pub conclusion: Option<String>,
+    pub url: Option<String>,
+    /// Names of the job's steps that did not succeed.
+    pub failed_steps: Vec<String>,
+    /// Last lines of the job's log, if requested and the job failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_tail: Option<Vec<String>>,
+}
+
+impl ReleaseWorkflowJob {
+    /// Whether the job concluded without succeeding. Skipped jobs are not
+    /// considered failures.
+    ///
+    pub fn is_failed(self: &Self) -> bool {
+        is_failed_conclusion(&self.conclusion)
+    }
+}
+
+/// Whether a job or step 'conclusion' from GitHub states a failure.
+///
+fn is_failed_conclusion(conclusion: &Option<String>) -> bool {
+    match conclusion {
+        None => false,
+        Some(c) => match c.as_str() {
+            "success" | "skipped" | "neutral" => false,
+            _ => true,
+        },
+    }
+}
+
+/// Which details to obtain on release workflow runs, beyond their status.
+///
+#[derive(Clone, Copy, Default)]
+pub struct WorkflowDetails {
+    /// Obtain the run's jobs and their failed steps.
+    pub jobs: bool,
+    /// Obtain the last lines of each failed job's log.
+    pub log_lines: Option<usize>,
+}
+
+/// Tags available on a given registry, by image name.
+///
+struct RegistryTags {
+    name: String,
+    images: BTreeMap<String, HashSet<String>>,
+}
+
+/// Machine-readable release status, as obtained from the local repositories,
+/// github, and the container registries.
+///
+#[derive(serde::Serialize)]
+pub struct ReleaseStatusReport {
+    pub version: String,
+    pub releases: Vec<ReleaseVersionStatus>,
+    pub repositories: Vec<RepositoryStatus>,
+}
+
+/// Status for a single release version tagged in the 's3gw' repository.
+///
+#[derive(serde::Serialize)]
+pub struct ReleaseVersionStatus {
+    pub version: String,
+    /// Commit distance from this version to its release branch's HEAD.
+    pub head: Option<CommitDiff>,
+    /// Latest run of each workflow tracked for this version's tags, by
+    /// repository.
+    pub workflows: Vec<ReleaseWorkflowResult>,
+    /// Whether an image tag for this version exists, by registry and image
+    /// name.
+    pub images: BTreeMap<String, BTreeMap<String, bool>>,
+    /// Release pull requests still open for this version, by repository.
+    pub pull_requests: Vec<ReleasePullRequest>,
+}
+
+/// An open pull request for a release version (e.g., with its release notes),
+/// as opened by 'arc rel finish'.
+///
+#[derive(serde::Serialize)]
+pub struct ReleasePullRequest {
+    pub repo: String,
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+    pub head: String,
+    pub base: String,
+}
+
+/// Status for a single repository in the workspace.
+///
+#[derive(serde::Serialize)]
+pub struct RepositoryStatus {
+    pub name: String,
+    pub role: WSGitRepoRole,
+    pub tags: Vec<String>,
+    pub latest: Option<String>,
+    /// Commit distance from the latest tag to its release branch's HEAD.
+    pub head: Option<CommitDiff>,
+    /// Whether the latest tag matches the 's3gw' repository's latest tag.
+    pub valid: bool,
+}
+
+#[derive(serde::Serialize)]
+pub struct CommitDiff {
+    pub ahead: usize,
+    pub behind: usize,
 }
 
 /// Print release status for each release version in the provided 'releases'
+/// tree, in the provided output 'format', including the workflow 'details'
+/// requested.
+///
+pub async fn status(
+    ws: &Workspace,
+    version: &Version,
+    releases: &BTreeMap<Version, Version>,
+    format: &OutputFormat,
+    details: &WorkflowDetails,
+) -> Result<(), ()> {
+    let report = get_status_report(&ws, &version, &releases, &details).await;
+    match format {
+        OutputFormat::Table => {
+            print_status(&report);
+            Ok(())
+        }
+        _ => crate::common::print_structured(format, &report),
+    }
+}
+
+/// Obtain release status for each release version in the provided 'releases'
 /// tree. This function will obtain information for each release from multiple
-/// sources, including the local repositories, github, and quay.
+/// sources, including the local repositories, github, and the container
+/// registries. Workflow runs' jobs, and failed jobs' logs, are obtained only if
+/// requested by 'details'.
 ///
-pub async fn status(ws: &Workspace, version: &Version, releases: &BTreeMap<u64, Version>) {
+pub async fn get_status_report(
+    ws: &Workspace,
+    version: &Version,
+    releases: &BTreeMap<Version, Version>,
+    details: &WorkflowDetails,
+) -> ReleaseStatusReport {
     let progress = UpdateProgress::new(&"gather information".into());
     progress.start();
 
-    let is_github_repo = match ws.repos.s3gw.config.github {
-        Some(_) => true,
-        None => false,
-    };
-    // github token must be something more than just 'ghp_'
-    let has_github_token = ws.config.user.github_token.len() > 4;
+    let tokens = get_forge_tokens(&ws);
+    let registries_tags = get_registries_tags(&ws).await;
+    let batched = get_batched_status(&ws, &releases, &tokens).await;
 
-    let quay_status = match get_quay_status(&ws).await {
-        Ok(res) => res,
-        Err(()) => None,
-    };
+    let mut release_entries = vec![];
+    for relver in releases.values() {
+        let head = match ws.repos.main().diff_head(&relver, true) {
+            Ok((ahead, behind)) => Some(CommitDiff { ahead, behind }),
+            Err(err) => {
+                errorln!("Unable to obtain commit diff for '{}': {}", relver, err);
+                None
+            }
+        };
+
+        // get github status
+        let mut workflows = vec![];
+        for repo in ws.repos.as_vec() {
+            if !tokens.contains_key(&repo.name) {
+                continue;
+            }
+            let res = match batched.get(&repo.name) {
+                Some(b) => Ok(get_batched_workflows_status(&repo, &relver, &b)),
+                None => get_repo_workflows_status(&ws, &repo, &relver).await,
+            };
+            let mut runs = match res {
+                Ok(v) => v,
+                Err(()) => {
+                    errorln!(
+                        "Unable to obtain latest workflows for version {} on '{}'",
+                        relver,
+                        repo.name
+                    );
+                    continue;
+                }
+            };
+            if details.jobs && repo.config.github.as_ref().is_some_and(|gh| gh.is_github()) {
+                for run in &mut runs {
+                    run.jobs = match get_workflow_jobs(&ws, &repo, run.id, &details.log_lines).await
+                    {
+                        Ok(jobs) => Some(jobs),
+                        Err(()) => {
+                            errorln!(
+                                "Unable to obtain jobs for workflow '{}' on '{}'",
+                                run.workflow,
+                                repo.name
+                            );
+                            None
+                        }
+                    };
+                }
+            }
+            workflows.extend(runs);
+        }
+
+        // get image tag status from registries
+        let images = get_images_status(&relver, &registries_tags);
+
+        release_entries.push(ReleaseVersionStatus {
+            version: relver.get_version_str(),
+            head,
+            workflows,
+            images,
+            pull_requests: get_release_pull_requests(&relver, &batched),
+        });
+    }
+
+    progress.finish();
 
+    ReleaseStatusReport {
+        version: version.get_version_str(),
+        releases: release_entries,
+        repositories: get_per_repo_status(&ws, &version),
+    }
+}
+
+/// Print a release status report in a human readable format.
+///
+fn print_status(report: &ReleaseStatusReport) {
     let mut table = crate::release::common::StatusTable::default();
-    for relver in releases.values() {
+    for entry in &report.releases {
+        let relver = match Version::from_str(&entry.version) {
+            Ok(v) => v,
+            Err(()) => {
+                boomln!("Unable to parse release version '{}'", entry.version);
+                return;
+            }
+        };
         let table_entry = table.new_entry(&relver);
 
-        let diff_str = get_commit_diff_status_str(&ws.repos.s3gw, &relver);
+        let diff_str = match &entry.head {
+            Some(diff) => get_human_readable_diff(diff.ahead, diff.behind, None, &"HEAD".into()),
+            None => "unknown commit distance to HEAD".into(),
+        };
         table_entry.add_record(&diff_str);
 
-        // get github status
-        if is_github_repo && has_github_token {
-            if let Some(s) = get_github_status_str(&ws, &relver).await {
-                table_entry.add_record(&s);
+        for run in &entry.workflows {
+            table_entry.add_record(&format!(
+                "{} '{}': {}",
+                run.repo,
+                run.workflow,
+                get_github_run_status_str(&run)
+            ));
+            if let Some(jobs) = &run.jobs {
+                if let Some(url) = &run.url {
+                    table_entry.add_record(&format!("build url: {}", url));
+                }
+                for job in jobs {
+                    table_entry.add_record(&get_job_status_str(&job));
+                }
             }
         }
-        // get image tag status from quay
-        if let Some(s) = &quay_status {
-            let status_str = get_quay_status_str(&relver, &s);
-            table_entry.add_record(&status_str);
+        for (registry, images) in &entry.images {
+            table_entry.add_record(&get_images_status_str(&registry, &images));
+        }
+        for pr in &entry.pull_requests {
+            table_entry.add_record(&format!(
+                "{} pull request #{} open: {}",
+                pr.repo, pr.number, pr.url
+            ));
         }
     }
-
-    progress.finish();
     println!("{}", table);
 
-    match show_per_repo_diff(&ws, &version) {
-        Ok(()) => {}
-        Err(()) => {
-            boomln!("Error obtaining per repository commit diffs");
-            return;
-        }
-    };
+    show_failed_jobs_logs(&report.releases);
 
-    show_per_repo_sanity(&ws, &version);
+    show_per_repo_diff(&report.repositories);
+    show_per_repo_sanity(&report.repositories);
 }
 
-/// Returns a prettified release workflow run status string for the specified
-/// release version, if any is available.
+/// Obtain the workflows tracked for repository 'repo''s release tags. The
+/// 's3gw' repository tracks its release workflow unless configured otherwise.
 ///
-async fn get_github_status_str(ws: &Workspace, relver: &Version) -> Option<String> {
-    let latest_run = match get_release_status(&ws, &relver).await {
-        Ok(v) => v,
-        Err(()) => {
-            errorln!("Unable to obtain latest workflow for version {}", relver);
-            return None;
-        }
+pub fn get_tracked_workflows(repo: &Repository) -> Vec<WSWorkflowConfig> {
+    let configured = match &repo.config.github {
+        Some(gh) => gh.workflows.clone(),
+        None => vec![],
     };
-    if latest_run.is_some() {
-        return Some(get_github_run_status_str(&latest_run.unwrap()));
+    if configured.is_empty() && repo.role == WSGitRepoRole::Main {
+        return vec![WSWorkflowConfig::default_release()];
     }
-    None
-}
-
-/// Obtain workflow runs from specified 'org' and 'repo', for the specified
-/// tag/branch 'tag'. Returns a vector of 'GitHubWorkflowResult', containing the
-/// raw response from github for each individual workflow run matching said
-/// 'tag'. Result needs to be handled by the caller to make it useful.
-///
-async fn github_get_workflows_status(
-    org: &String,
-    repo: &String,
-    token: &String,
-    tag: &String,
-) -> Result<Vec<GitHubWorkflowResult>, ()> {
-    let api_url = format!("https://api.github.com/repos/{}/{}/actions/runs", org, repo);
-
-    let response = match reqwest::Client::new()
-        .get(&api_url)
-        .bearer_auth(&token)
-        .query(&[("branch", tag)])
-        .header("Accept", "application/vnd.github+json")
-        .header("X-GitHub-Api-Version", "2022-11-28")
-        .header("User-Agent", "s3gw-arc-rs")
-        .send()
-        .await
-    {
-        Ok(r) => r,
-        Err(err) => {
-            errorln!("Unable to obtain github workflows for {}: {}", tag, err);
-            return Err(());
-        }
-    };
-
-    let runs = match response.json::<GitHubRunResult>().await {
-        Ok(r) => r.workflow_runs,
-        Err(err) => {
-            boomln!("Unable to obtain resulting runs: {}", err);
-            return Err(());
-        }
-    };
-    return Ok(runs);
+    configured
 }
 
-/// Obtain the latest release workflow for the specified tag or branch, 'tag',
-/// for the specified 'org' and 'repo'. This function works by first obtaining
-/// all workflow runs that match said 'tag', and then filtering them for the
-/// specific release workflow name, ignoring those that are yet to be populated
-/// (this should not happen by the way), and finally returning solely the latest
-/// one, if available.
+/// Obtain the token for each repository with tracked workflows on its forge, by
+/// repository name. Repositories without a token are left out, their workflows
+/// not being reported.
 ///
-pub async fn github_get_latest_release_workflow(
-    org: &String,
-    repo: &String,
-    token: &String,
-    tag: &String,
-) -> Result<Option<ReleaseWorkflowResult>, ()> {
-    let mut results = match github_get_workflows_status(&org, &repo, &token, &tag).await {
-        Ok(r) => r,
-        Err(()) => {
-            boomln!("Error obtaining workflow status from github!");
-            return Err(());
+fn get_forge_tokens(ws: &Workspace) -> BTreeMap<String, String> {
+    let mut tokens = BTreeMap::new();
+    for repo in ws.repos.as_vec() {
+        if repo.config.github.is_none() || get_tracked_workflows(&repo).is_empty() {
+            continue;
+        }
+        if let Ok(token) = common::get_forge_token(&ws, &repo) {
+            tokens.insert(repo.name.clone(), token);
         }
-    };
-
-    if results.is_empty() {
-        return Ok(None);
     }
+    tokens
+}
 
-    results.sort_by(|a, b| a.created_at.cmp(&b.created_at));
-
-    let filtered: Vec<&GitHubWorkflowResult> = results
-        .iter()
-        .filter(|e| {
-            if e.name.is_none() {
-                return false;
+/// Obtain the release status of every release version in 'releases', in a
+/// single batch per GitHub repository with tracked workflows, by repository
+/// name. Repositories whose status can't be batched are left out, and their
+/// workflow runs should be obtained per release version instead.
+///
+async fn get_batched_status(
+    ws: &Workspace,
+    releases: &BTreeMap<Version, Version>,
+    tokens: &BTreeMap<String, String>,
+) -> BTreeMap<String, BatchedReleaseStatus> {
+    let mut batched = BTreeMap::new();
+    for repo in ws.repos.as_vec() {
+        let cfg = match &repo.config.github {
+            Some(gh) if gh.is_github() => gh,
+            _ => continue,
+        };
+        // github token must be something more than just 'ghp_'
+        let token = match tokens.get(&repo.name) {
+            Some(v) if v.len() > 4 => v,
+            _ => continue,
+        };
+        let tags = releases
+            .values()
+            .map(|relver| relver.to_rc_str_fmt(&repo.config.tag_format))
+            .collect::<Vec<String>>();
+
+        match graphql::get_release_status(&cfg, &token, &tags).await {
+            Ok(v) => {
+                batched.insert(repo.name.clone(), v);
             }
-            let name = e.name.as_ref().unwrap();
-            if name.to_lowercase() != "release s3gw" {
-                return false;
+            Err(err) => {
+                log::warn!(
+                    "Unable to batch release status for '{}', falling back: {}",
+                    repo.name,
+                    err
+                );
             }
+        };
+    }
+    batched
+}
 
-            if e.conclusion.is_none() {
-                return true;
-            } else {
-                let c = e.conclusion.as_ref().unwrap();
-                return c != "cancelled";
+/// Obtain the latest run of each workflow tracked for repository 'repo', for
+/// the specified release version, from its 'batched' release status.
+///
+fn get_batched_workflows_status(
+    repo: &Repository,
+    relver: &Version,
+    batched: &BatchedReleaseStatus,
+) -> Vec<ReleaseWorkflowResult> {
+    let tag = relver.to_rc_str_fmt(&repo.config.tag_format);
+    batched
+        .get_pipeline_runs(&tag, &get_tracked_workflows(&repo))
+        .iter()
+        .map(|run| ReleaseWorkflowResult::from_pipeline_run(&repo.name, &run))
+        .collect()
+}
+
+/// Obtain the open pull requests for the specified release version, as
+/// opened by 'arc rel finish' from a 'release-vX.Y.Z-<timestamp>' branch, from
+/// each repository's 'batched' release status.
+///
+fn get_release_pull_requests(
+    relver: &Version,
+    batched: &BTreeMap<String, BatchedReleaseStatus>,
+) -> Vec<ReleasePullRequest> {
+    let prefix = format!("release-v{}-", relver.get_release_version());
+    let mut prs = vec![];
+    for (repo, status) in batched {
+        for pr in &status.pull_requests {
+            if !pr.head.starts_with(&prefix) {
+                continue;
             }
-        })
-        .collect();
+            prs.push(ReleasePullRequest {
+                repo: repo.clone(),
+                number: pr.number,
+                title: pr.title.clone(),
+                url: pr.url.clone(),
+                head: pr.head.clone(),
+                base: pr.base.clone(),
+            });
+        }
+    }
+    prs
+}
 
-    match filtered.first() {
-        None => Ok(None),
-        Some(v) => Ok(Some(ReleaseWorkflowResult::from_github_result(&v))),
+/// Obtain the latest run of each workflow tracked for repository 'repo', for
+/// the specified release version.
+///
+async fn get_repo_workflows_status(
+    ws: &Workspace,
+    repo: &Repository,
+    relver: &Version,
+) -> Result<Vec<ReleaseWorkflowResult>, ()> {
+    get_workflows_status(&ws, &repo, &relver, &get_tracked_workflows(&repo)).await
+}
+
+/// Obtain the latest run of each of 'workflows' on repository 'repo''s forge,
+/// for the specified release version, ignoring cancelled runs.
+///
+async fn get_workflows_status(
+    ws: &Workspace,
+    repo: &Repository,
+    relver: &Version,
+    workflows: &Vec<WSWorkflowConfig>,
+) -> Result<Vec<ReleaseWorkflowResult>, ()> {
+    let forge = match common::get_forge(&ws, &repo) {
+        Ok(v) => v,
+        Err(_) => return Err(()),
+    };
+    let tag = relver.to_rc_str_fmt(&repo.config.tag_format);
+
+    match forge.get_pipeline_runs(&tag, &workflows).await {
+        Ok(runs) => Ok(runs
+            .iter()
+            .map(|run| ReleaseWorkflowResult::from_pipeline_run(&repo.name, &run))
+            .collect()),
+        Err(err) => {
+            errorln!(
+                "Unable to obtain workflow runs for '{}' on '{}': {}",
+                tag,
+                repo.name,
+                err
+            );
+            Err(())
+        }
     }
 }
 
-/// Obtain release status from github, for the specified release version. This
-/// function is simply a helper to translate our github configuration into
-/// something that can be called against github. Returns the latest workflow run
-/// available for the provided release version, if any is available.
+/// Obtain release status from the forge, for the specified release version.
+/// Returns the latest run of the 's3gw' repository's release workflow (i.e.,
+/// its first tracked workflow) available for the provided release version, if
+/// any is available.
 ///
 pub async fn get_release_status(
     ws: &Workspace,
     relver: &Version,
 ) -> Result<Option<ReleaseWorkflowResult>, ()> {
-    let github_config = match &ws.repos.s3gw.config.github {
-        Some(c) => c,
+    let repo = ws.repos.main();
+    let workflow = match get_tracked_workflows(&repo).into_iter().next() {
+        Some(w) => w,
         None => {
-            errorln!("Expected github repository config, found none!");
+            errorln!("No release workflow configured for '{}'", repo.name);
             return Err(());
         }
     };
-    let github_token = &ws.config.user.github_token;
-    let tag = format!(
-        "{}{}",
-        relver.to_str_fmt(&ws.repos.s3gw.config.tag_format),
-        match relver.rc {
-            None => "".into(),
-            Some(v) => format!("-rc{}", v),
-        }
-    );
 
-    github_get_latest_release_workflow(&github_config.org, &github_config.repo, &github_token, &tag)
-        .await
+    let runs = get_workflows_status(&ws, &repo, &relver, &vec![workflow]).await?;
+    Ok(runs.into_iter().next())
 }
 
 /// Returns a status string for a given release workflow run, with pretty formatting.
 ///
-fn get_github_run_status_str(run: &ReleaseWorkflowResult) -> String {
+pub fn get_github_run_status_str(run: &ReleaseWorkflowResult) -> String {
     let status_str = &run.status.to_string();
     let status = match &run.status {
         ReleaseWorkflowStatus::COMPLETED => status_str.green(),
@@ -445,91 +656,208 @@ fn get_github_run_status_str(run: &ReleaseWorkflowResult) -> String {
         },
     };
 
+    let attempts = match run.num_attempts {
+        Some(n) => format!("  ({} attempt{})", n, if n == 1 { "" } else { "s" }),
+        None => String::new(),
+    };
     format!(
-        "build status: {}, conclusion: {}  {:12}  ({} attempt{})",
+        "build status: {}, conclusion: {}  {:12}{}",
         status,
         success,
         run.to_duration_str(),
-        run.num_attempts,
-        if run.num_attempts == 1 { "" } else { "s" }
+        attempts
     )
 }
 
-/// Obtain all tags from the specified repository 'repo' in the namespace 'ns',
-/// from quay.io. Returns a hash map of 'QuayRepositoryTagEntry', if
-/// successfull.
+/// Returns a status string for a given workflow job, listing its failed steps
+/// and linking to it if it failed.
 ///
-async fn quay_get_tags(repo: &String) -> Result<HashMap<String, QuayRepositoryTagEntry>, ()> {
-    let api_url = format!("https://quay.io/api/v1/repository/{}", repo);
+fn get_job_status_str(job: &ReleaseWorkflowJob) -> String {
+    let conclusion = match &job.conclusion {
+        None => job.status.yellow(),
+        Some(c) if job.is_failed() => c.red(),
+        Some(c) => c.green(),
+    };
+    let mut res = format!("job '{}': {}", job.name, conclusion);
+    if job.is_failed() {
+        if !job.failed_steps.is_empty() {
+            res.push_str(&format!(", failed steps: {}", job.failed_steps.join(", ")));
+        }
+        if let Some(url) = &job.url {
+            res.push_str(&format!("  ({})", url));
+        }
+    }
+    res
+}
 
-    let response = match reqwest::Client::new()
-        .get(&api_url)
-        .query(&[("includeTags", "true")])
-        .send()
-        .await
-    {
-        Ok(r) => r,
-        Err(err) => {
-            errorln!("Unable to obtain tags from quay for '{}': {}", repo, err);
-            return Err(());
+/// Print the obtained log tail of each failed job, for every release version.
+///
+fn show_failed_jobs_logs(releases: &Vec<ReleaseVersionStatus>) {
+    for entry in releases {
+        for run in &entry.workflows {
+            let jobs = match &run.jobs {
+                Some(jobs) => jobs,
+                None => continue,
+            };
+            for job in jobs {
+                let lines = match &job.log_tail {
+                    Some(lines) => lines,
+                    None => continue,
+                };
+                println!(
+                    "{} log for job '{}' of {} '{}' on {}, last {} lines:",
+                    "=>".red(),
+                    job.name.bold(),
+                    run.repo,
+                    run.workflow,
+                    entry.version,
+                    lines.len()
+                );
+                for line in lines {
+                    println!("  {}", line);
+                }
+                println!();
+            }
         }
+    }
+}
+
+/// Obtain the jobs for workflow run 'run_id' on repository 'repo', from its
+/// forge. If 'log_lines' is provided, the last lines of each failed job's log
+/// are obtained as well; failing to obtain a log is not an error.
+///
+pub async fn get_workflow_jobs(
+    ws: &Workspace,
+    repo: &Repository,
+    run_id: u64,
+    log_lines: &Option<usize>,
+) -> Result<Vec<ReleaseWorkflowJob>, ()> {
+    let forge = match common::get_forge(&ws, &repo) {
+        Ok(v) => v,
+        Err(_) => return Err(()),
     };
 
-    let tags = match response.json::<QuayRepositoryTagResult>().await {
-        Ok(r) => r.tags,
+    let jobs = match forge.get_pipeline_jobs(run_id).await {
+        Ok(v) => v,
         Err(err) => {
-            boomln!(
-                "Unable to obtain resulting tags from quay for '{}': {}",
-                repo,
-                err
-            );
+            errorln!("Unable to obtain jobs for workflow run {}: {}", run_id, err);
             return Err(());
         }
     };
-    Ok(tags)
+
+    let mut res = vec![];
+    for job in jobs {
+        let failed_steps = job
+            .steps
+            .iter()
+            .filter(|step| is_failed_conclusion(&step.conclusion))
+            .map(|step| step.name.clone())
+            .collect();
+
+        let log_tail = match log_lines {
+            Some(n) if is_failed_conclusion(&job.conclusion) => {
+                match forge.get_job_log(job.id).await {
+                    Ok(log) => Some(get_log_tail(&log, *n)),
+                    Err(err) => {
+                        errorln!("Unable to obtain log for job '{}': {}", job.name, err);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        res.push(ReleaseWorkflowJob {
+            name: job.name,
+            status: job.status,
+            conclusion: job.conclusion,
+            url: job.url,
+            failed_steps,
+            log_tail,
+        });
+    }
+    Ok(res)
 }
 
-/// Obtain status from quay for the various repositories we want.
+/// Obtain the last 'num' lines of 'log'.
 ///
-async fn get_quay_status(ws: &Workspace) -> Result<Option<QuayStatus>, ()> {
-    let cfg = match &ws.config.registry {
-        Some(c) => c,
-        None => return Ok(None),
-    };
-
-    let s3gw = if let Ok(res) = quay_get_tags(&cfg.s3gw).await {
-        res
-    } else {
-        return Err(());
-    };
-    let ui = if let Ok(res) = quay_get_tags(&cfg.ui).await {
-        res
-    } else {
-        return Err(());
-    };
+fn get_log_tail(log: &str, num: usize) -> Vec<String> {
+    let lines: Vec<&str> = log.lines().collect();
+    let start = lines.len().saturating_sub(num);
+    lines[start..].iter().map(|l| l.to_string()).collect()
+}
 
-    Ok(Some(QuayStatus { s3gw, ui }))
+/// Obtain the tags for each image on each of the workspace's registries.
+/// Images whose tags can't be obtained are left out.
+///
+async fn get_registries_tags(ws: &Workspace) -> Vec<RegistryTags> {
+    let mut res = vec![];
+    for cfg in &ws.config.registries {
+        let registry = crate::registry::from_config(&cfg);
+        let mut images = BTreeMap::new();
+        for (image, repo) in &cfg.images {
+            match registry.get_tags(&repo).await {
+                Ok(tags) => {
+                    images.insert(image.clone(), tags.into_iter().collect());
+                }
+                Err(err) => {
+                    errorln!(
+                        "Unable to obtain tags for '{}' from registry '{}': {}",
+                        image,
+                        cfg.name,
+                        err
+                    );
+                }
+            };
+        }
+        res.push(RegistryTags {
+            name: cfg.name.clone(),
+            images,
+        });
+    }
+    res
 }
 
-/// Obtain status string from quay for a specific release version.
+/// Obtain image tag presence on each registry for a specific release version,
+/// by registry and image name.
 ///
-fn get_quay_status_str(relver: &Version, quay_status: &QuayStatus) -> String {
+fn get_images_status(
+    relver: &Version,
+    registries_tags: &Vec<RegistryTags>,
+) -> BTreeMap<String, BTreeMap<String, bool>> {
     let relstr = format!("v{}", relver);
 
-    fn get_status_from_map(
-        map: &HashMap<String, QuayRepositoryTagEntry>,
-        relstr: &String,
-    ) -> String {
-        if let Some(_) = map.get(relstr) {
-            "found".green().to_string()
-        } else {
-            "not found".yellow().to_string()
-        }
-    }
+    registries_tags
+        .iter()
+        .map(|registry| {
+            let images = registry
+                .images
+                .iter()
+                .map(|(image, tags)| (image.clone(), tags.contains(&relstr)))
+                .collect();
+            (registry.name.clone(), images)
+        })
+        .collect()
+}
 
-    let s3gw_str = get_status_from_map(&quay_status.s3gw, &relstr);
-    let ui_str = get_status_from_map(&quay_status.ui, &relstr);
-    format!("images: s3gw = {}, s3gw-ui = {}", s3gw_str, ui_str)
+/// Obtain status string for image tag presence on a given registry.
+///
+fn get_images_status_str(registry: &String, images: &BTreeMap<String, bool>) -> String {
+    let status = images
+        .iter()
+        .map(|(name, found)| {
+            format!(
+                "{} = {}",
+                name,
+                if *found {
+                    "found".green()
+                } else {
+                    "not found".yellow()
+                }
+            )
+        })
+        .collect::<Vec<String>>();
+    format!("images on {}: {}", registry, status.join(", "))
 }
 
 /// Obtain a human readable string stating the commit difference for the
@@ -577,121 +905,124 @@ fn get_human_readable_diff(
     )
 }
 
-/// Obtain status string representing commit distance from 'relver' to its
-/// release branch's HEAD.
+/// Obtain per repository status for the provided version 'relver': the release
+/// tags available, and the commit difference between the latest available
+/// release and the HEAD of the release branch. Repositories' latest tags are
+/// checked against the 's3gw' repository; a mismatch is considered corruption.
 ///
-fn get_commit_diff_status_str(repo: &Repository, relver: &Version) -> String {
-    let (ahead, behind) = repo.diff_head(&relver, true).unwrap();
-    get_human_readable_diff(ahead, behind, None, &"HEAD".into())
-}
-
-/// Print per repository commit difference status, between latest available
-/// release for the provided version 'relver' and the HEAD of the release branch.
-///
-fn show_per_repo_diff(ws: &Workspace, relver: &Version) -> Result<(), ()> {
-    let repos = ws.repos.as_vec();
-
-    for repo in repos {
-        match show_repo_diff(&repo, &relver) {
-            Ok(()) => {}
-            Err(()) => {
-                errorln!("Unable to get repository '{}' commit diff", repo.name);
-            }
-        };
-    }
-    println!("");
+fn get_per_repo_status(ws: &Workspace, relver: &Version) -> Vec<RepositoryStatus> {
+    let main_releases = common::get_release_versions_from_repo(&ws.repos.main(), &relver);
+    let expected_latest = match main_releases.keys().max() {
+        None => None,
+        Some(v) => main_releases.get(v),
+    };
 
-    Ok(())
+    ws.repos
+        .as_vec()
+        .iter()
+        .map(|repo| get_repo_status(&repo, &relver, &expected_latest))
+        .collect()
 }
 
-/// Obtain the commit difference between a repository's release branch HEAD and
-/// its latest release tag.
+/// Obtain status for a single repository, checking whether its latest tag
+/// matches the expected release version.
 ///
-fn show_repo_diff(repo: &Repository, relver: &Version) -> Result<(), ()> {
-    let releases = common::get_release_versions_from_repo(&repo, relver);
+fn get_repo_status(
+    repo: &Repository,
+    relver: &Version,
+    expected_latest_ver: &Option<&Version>,
+) -> RepositoryStatus {
+    let releases = common::get_release_versions_from_repo(&repo, &relver);
     let latest_release = match releases.keys().max() {
-        Some(v) => releases.get(v).unwrap(),
-        None => {
-            errorln!(
-                "Release '{}' not found for repository '{}': possibly corrupted release!",
-                relver,
-                repo.name
-            );
-            return Err(());
-        }
+        Some(v) => releases.get(v),
+        None => None,
     };
 
-    let (ahead, behind) = match repo.diff_head(&latest_release, true) {
-        Ok(res) => res,
-        Err(err) => {
-            errorln!(
-                "Error obtaining commit diff for release '{}' in repository '{}': {}",
-                latest_release,
-                repo.name,
-                err
-            );
-            return Err(());
-        }
+    let head = match latest_release {
+        None => None,
+        Some(latest) => match repo.diff_head(&latest, true) {
+            Ok((ahead, behind)) => Some(CommitDiff { ahead, behind }),
+            Err(err) => {
+                errorln!(
+                    "Error obtaining commit diff for release '{}' in repository '{}': {}",
+                    latest,
+                    repo.name,
+                    err
+                );
+                None
+            }
+        },
     };
 
-    let release_str = latest_release.to_string();
-    let branch_str = latest_release.get_base_version_str();
+    let valid = match (expected_latest_ver, latest_release) {
+        (None, None) => true,
+        (Some(expected), Some(latest)) => expected == &latest,
+        _ => false,
+    };
 
-    let diff_str = get_human_readable_diff(ahead, behind, Some(&release_str), &branch_str);
-    println!("{:12}: {}", repo.name, diff_str);
-    Ok(())
+    RepositoryStatus {
+        name: repo.name.clone(),
+        role: repo.role,
+        tags: releases.values().map(|v| v.get_version_str()).collect(),
+        latest: latest_release.map(|v| v.get_version_str()),
+        head,
+        valid,
+    }
 }
 
-/// Check repositories sanity, compared against the 's3gw' repository. Mismatch
-/// of tags is considered as corruption.
+/// Print per repository commit difference status, between latest available
+/// release and the HEAD of the release branch.
 ///
-fn show_per_repo_sanity(ws: &Workspace, relver: &Version) {
-    let all_repos = ws.repos.as_vec();
-    let repos: Vec<&&Repository> = all_repos
-        .iter()
-        .filter(|e| e.name != ws.repos.s3gw.name)
-        .collect();
-
-    let releases = common::get_release_versions_from_repo(&ws.repos.s3gw, &relver);
-    let latest_release = match releases.keys().max() {
-        None => None,
-        Some(v) => releases.get(v),
-    };
-
+fn show_per_repo_diff(repos: &Vec<RepositoryStatus>) {
     for repo in repos {
-        show_repo_sanity(&repo, &relver, &latest_release);
+        let latest = match &repo.latest {
+            Some(v) => v,
+            None => {
+                errorln!(
+                    "Release not found for repository '{}': possibly corrupted release!",
+                    repo.name
+                );
+                continue;
+            }
+        };
+        let diff = match &repo.head {
+            Some(v) => v,
+            None => {
+                errorln!("Unable to get repository '{}' commit diff", repo.name);
+                continue;
+            }
+        };
+        let branch_str = match Version::from_str(&latest) {
+            Ok(v) => v.get_base_version_str(),
+            Err(()) => latest.clone(),
+        };
+        let diff_str = get_human_readable_diff(diff.ahead, diff.behind, Some(&latest), &branch_str);
+        println!("{:12}: {}", repo.name, diff_str);
     }
+    println!("");
 }
 
-/// Check a single repository's sanity, checks whether its latest tag matches
-/// the expected release version.
+/// Print repositories sanity, as compared against the 's3gw' repository.
 ///
-fn show_repo_sanity(repo: &Repository, relver: &Version, expected_latest_ver: &Option<&Version>) {
-    let releases = common::get_release_versions_from_repo(&repo, &relver);
-    let latest_release = match releases.keys().max() {
-        Some(v) => releases.get(v).unwrap(),
-        None => {
-            if let Some(expected) = expected_latest_ver {
-                show_repo_corruption(&repo.name, &expected.to_string(), &"None".into());
-            } else {
-                println!("{:12}: valid", repo.name);
-            }
-            return;
-        }
+fn show_per_repo_sanity(repos: &Vec<RepositoryStatus>) {
+    let expected = match repos.iter().find(|e| e.role == WSGitRepoRole::Main) {
+        Some(main) => main.latest.clone().unwrap_or("None".into()),
+        None => return,
     };
 
-    if let Some(expected) = expected_latest_ver {
-        if expected.get_version_id() != latest_release.get_version_id() {
+    for repo in repos {
+        if repo.role == WSGitRepoRole::Main {
+            continue;
+        }
+        if repo.valid {
+            println!("{:12}: valid", repo.name);
+        } else {
             show_repo_corruption(
                 &repo.name,
-                &expected.to_string(),
-                &latest_release.to_string(),
+                &expected,
+                &repo.latest.clone().unwrap_or("None".into()),
             );
-        } else {
-            println!("{:12}: valid", repo.name);
         }
-    } else {
-        show_repo_corruption(&repo.name, &"None".into(), &latest_release.to_string());
     }
 }
 
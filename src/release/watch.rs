@@ -0,0 +1,146 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+use crate::{
+    errorln, infoln,
+    release::{
+        errors::{ReleaseError, ReleaseResult},
+        status::{self, ReleaseWorkflowResult},
+    },
+    version::Version,
+    ws::workspace::Workspace,
+};
+
+/// Longest interval between polls, however long we have been waiting.
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How many times to poll for a release workflow that has not shown up yet,
+/// before giving up on it.
+const MAX_NOT_FOUND_POLLS: u32 = 10;
+
+/// Wait for the release workflow for 'relver' to complete, polling GitHub
+/// every 'poll_interval' at first, doubling the interval between polls up to
+/// 'MAX_POLL_INTERVAL'. Returns the completed workflow run, be it successful
+/// or not.
+///
+pub async fn wait_for_release_workflow(
+    ws: &Workspace,
+    relver: &Version,
+    poll_interval: &Duration,
+) -> ReleaseResult<ReleaseWorkflowResult> {
+    wait_for_release_workflow_attempt(&ws, &relver, &poll_interval, 1).await
+}
+
+/// Wait for attempt 'min_attempt', or a later one, of the release workflow for
+/// 'relver' to complete, as 'wait_for_release_workflow()' does. Earlier
+/// attempts are considered as not having started yet, which is useful when
+/// waiting on a workflow run that has just been re-run. Runs whose attempt is
+/// unknown are waited on regardless.
+///
+pub async fn wait_for_release_workflow_attempt(
+    ws: &Workspace,
+    relver: &Version,
+    poll_interval: &Duration,
+    min_attempt: u64,
+) -> ReleaseResult<ReleaseWorkflowResult> {
+    let tag = relver.to_rc_str_fmt(&ws.repos.main().config.tag_format);
+    infoln!("Waiting for release workflow for '{}'...", tag);
+
+    let progress = ProgressBar::new_spinner();
+    progress.enable_steady_tick(Duration::from_millis(200));
+    progress.set_style(
+        ProgressStyle::with_template("{spinner:.dim.bold} {prefix:.bold}: {msg}  [{elapsed}]")
+            .unwrap(),
+    );
+    progress.set_prefix(tag.clone());
+
+    let mut interval = *poll_interval;
+    let mut not_found = 0;
+    loop {
+        let run = match status::get_release_status(&ws, &relver).await {
+            Ok(v) => v,
+            Err(()) => {
+                progress.finish_with_message("error");
+                errorln!("Unable to obtain release workflow for '{}'", tag);
+                return Err(ReleaseError::UnknownError);
+            }
+        };
+        match run {
+            None => {
+                not_found += 1;
+                if not_found >= MAX_NOT_FOUND_POLLS {
+                    progress.finish_with_message("not found");
+                    errorln!("Release workflow for '{}' not found", tag);
+                    return Err(ReleaseError::ReleaseBuildNotFoundError);
+                }
+                progress.set_message(format!(
+                    "waiting for workflow to start, next check in {}s",
+                    interval.as_secs()
+                ));
+            }
+            Some(run) if run.num_attempts.is_some_and(|n| n < min_attempt) => {
+                not_found += 1;
+                if not_found >= MAX_NOT_FOUND_POLLS {
+                    progress.finish_with_message("not found");
+                    errorln!(
+                        "Attempt {} of release workflow for '{}' not found",
+                        min_attempt,
+                        tag
+                    );
+                    return Err(ReleaseError::ReleaseBuildNotFoundError);
+                }
+                progress.set_message(format!(
+                    "waiting for attempt {} to start, next check in {}s",
+                    min_attempt,
+                    interval.as_secs()
+                ));
+            }
+            Some(run) => {
+                if !run.is_waiting() {
+                    progress.finish_with_message(if run.success { "success" } else { "failed" });
+                    return Ok(run);
+                }
+                progress.set_message(format!(
+                    "{} for {}{}, next check in {}s",
+                    run.status,
+                    run.to_duration_str(),
+                    match run.num_attempts {
+                        Some(n) => format!(" (attempt {})", n),
+                        None => String::new(),
+                    },
+                    interval.as_secs()
+                ));
+            }
+        };
+        tokio::time::sleep(interval).await;
+        interval = (interval * 2).min(MAX_POLL_INTERVAL);
+    }
+}
+
+/// Report on a failed release workflow run 'run'.
+///
+pub fn report_failure(run: &ReleaseWorkflowResult) {
+    errorln!(
+        "Release workflow for '{}' failed: {}",
+        run.tag,
+        status::get_github_run_status_str(&run)
+    );
+    if let Some(url) = &run.url {
+        infoln!("Check the workflow run at {}", url);
+    }
+}
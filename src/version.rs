@@ -13,34 +13,121 @@
 // limitations under the License.
 
 use handlebars::Handlebars;
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::ops::Bound;
 use std::{collections::BTreeMap, fmt::Display};
 
-#[derive(Clone, serde::Serialize, serde::Deserialize)]
+/// A SemVer version, e.g. '0.17.0', '0.17.0-rc1', '0.18.0-alpha.1' or
+/// '0.18.0-beta.2+build.5'. Both the patch version and the pre-release are
+/// optional, for release branches are versioned as 'X.Y'. Versions are ordered
+/// by precedence, and may thus be used as keys for ordered collections. Two
+/// versions are only equal if they are written the same, though, so that
+/// neither '0.17.0-rc1' and '0.17.0-rc.1', nor '0.17.0+b1' and '0.17.0+b2',
+/// collapse into the same key.
+///
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "VersionFormat", into = "String")]
 pub struct Version {
     pub major: u64,
     pub minor: u64,
     pub patch: Option<u64>,
-    pub rc: Option<u64>,
+    /// pre-release identifiers, e.g. '["alpha", "1"]' for '-alpha.1'
+    pub pre: Vec<PreReleaseIdentifier>,
+    /// build metadata identifiers, ignored when determining precedence
+    pub build: Vec<String>,
+}
+
+/// A single, dot-separated, pre-release identifier.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PreReleaseIdentifier {
+    Numeric(u64),
+    AlphaNumeric(String),
+}
+
+/// On-disk formats for a version. Versions used to be kept as their individual
+/// components, with an optional release candidate number.
+///
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum VersionFormat {
+    Str(String),
+    Legacy {
+        major: u64,
+        minor: u64,
+        patch: Option<u64>,
+        rc: Option<u64>,
+    },
+}
+
+impl TryFrom<VersionFormat> for Version {
+    type Error = String;
+
+    fn try_from(value: VersionFormat) -> Result<Self, Self::Error> {
+        match value {
+            VersionFormat::Str(s) => match Version::from_str(&s) {
+                Ok(v) => Ok(v),
+                Err(()) => Err(format!("invalid version '{}'", s)),
+            },
+            VersionFormat::Legacy {
+                major,
+                minor,
+                patch,
+                rc,
+            } => {
+                let mut version = Version {
+                    major,
+                    minor,
+                    patch,
+                    pre: vec![],
+                    build: vec![],
+                };
+                if let Some(v) = rc {
+                    version.set_rc(v);
+                }
+                Ok(version)
+            }
+        }
+    }
+}
+
+impl From<Version> for String {
+    fn from(value: Version) -> Self {
+        value.to_string()
+    }
 }
 
 pub struct ReleaseEntry {
     pub release: Version,
-    pub versions: BTreeMap<u64, Version>,
+    pub versions: BTreeMap<Version, Version>,
     pub is_complete: bool,
 }
 
 pub struct BaseVersion {
     pub version: Version,
-    pub releases: BTreeMap<u64, ReleaseEntry>,
+    pub releases: BTreeMap<Version, ReleaseEntry>,
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
-impl PartialEq for Version {
-    fn eq(&self, other: &Self) -> bool {
-        self.get_version_id() == other.get_version_id()
+/// Versions are ordered by SemVer precedence. Versions of equal precedence are
+/// further ordered by how their pre-release and build metadata are written, to
+/// keep the order consistent with equality.
+///
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_precedence(other).then_with(|| {
+            let ours = self.pre.iter().map(|i| i.to_string());
+            let theirs = other.pre.iter().map(|i| i.to_string());
+            ours.cmp(theirs).then(self.build.cmp(&other.build))
+        })
     }
 }
-impl Eq for Version {}
 
 impl Display for Version {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -48,16 +135,107 @@ impl Display for Version {
         if let Some(v) = self.patch {
             write!(f, ".{}", v)?;
         }
-        if let Some(v) = self.rc {
-            write!(f, "-rc{}", v)?;
+        write!(f, "{}", self.get_suffix_str())
+    }
+}
+
+impl Display for PreReleaseIdentifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PreReleaseIdentifier::Numeric(n) => write!(f, "{}", n),
+            PreReleaseIdentifier::AlphaNumeric(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// Part of a pre-release identifier, for precedence purposes.
+///
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum PreReleaseKey<'a> {
+    Numeric(u64),
+    AlphaNumeric(&'a str),
+}
+
+impl PreReleaseIdentifier {
+    fn from_str(value: &str) -> Result<PreReleaseIdentifier, ()> {
+        if value.is_empty() {
+            return Err(());
+        }
+        if value.chars().all(|c| c.is_ascii_digit()) {
+            // SemVer forbids leading zeroes in numeric identifiers.
+            if value.len() > 1 && value.starts_with('0') {
+                return Err(());
+            }
+            return match value.parse::<u64>() {
+                Ok(n) => Ok(PreReleaseIdentifier::Numeric(n)),
+                Err(_) => Err(()),
+            };
+        }
+        if !value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(());
+        }
+        Ok(PreReleaseIdentifier::AlphaNumeric(value.into()))
+    }
+
+    /// Obtain the keys to compare this identifier by. Identifiers in the form
+    /// of a label followed by a number (e.g., 'rc10', as used by our release
+    /// candidates' tags) are compared as if they were two identifiers (i.e.,
+    /// 'rc.10'). Otherwise 'rc10' would come before 'rc2'.
+    ///
+    fn get_keys(self: &Self) -> Vec<PreReleaseKey<'_>> {
+        match self {
+            PreReleaseIdentifier::Numeric(n) => vec![PreReleaseKey::Numeric(*n)],
+            PreReleaseIdentifier::AlphaNumeric(s) => {
+                let label = s.trim_end_matches(|c: char| c.is_ascii_digit());
+                let num = &s[label.len()..];
+                if label.is_empty() || num.is_empty() || label.ends_with('-') {
+                    return vec![PreReleaseKey::AlphaNumeric(s)];
+                }
+                match num.parse::<u64>() {
+                    Ok(n) => vec![
+                        PreReleaseKey::AlphaNumeric(label),
+                        PreReleaseKey::Numeric(n),
+                    ],
+                    Err(_) => vec![PreReleaseKey::AlphaNumeric(s)],
+                }
+            }
         }
-        Ok(())
     }
 }
 
 impl Version {
+    /// Compare versions according to SemVer precedence. A missing patch
+    /// version is considered lower than any patch version; a pre-release is
+    /// lower than its associated release; build metadata is not considered.
+    ///
+    pub fn cmp_precedence(self: &Self, other: &Self) -> Ordering {
+        let ord = self
+            .major
+            .cmp(&other.major)
+            .then(self.minor.cmp(&other.minor))
+            .then(self.patch.cmp(&other.patch));
+        if ord != Ordering::Equal {
+            return ord;
+        }
+
+        match (self.pre.is_empty(), other.pre.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => {
+                let ours = self.pre.iter().flat_map(|i| i.get_keys());
+                let theirs = other.pre.iter().flat_map(|i| i.get_keys());
+                ours.cmp(theirs)
+            }
+        }
+    }
+
     pub fn from_str(value: &String) -> Result<Version, ()> {
-        let pattern = r"^v?((\d+)\.(\d+)(?:\.(\d+)(?:-rc(\d+))?)?)$";
+        let pattern = concat!(
+            r"^v?((\d+)\.(\d+)(?:\.(\d+)",
+            r"(?:-([0-9A-Za-z-]+(?:\.[0-9A-Za-z-]+)*))?",
+            r"(?:\+([0-9A-Za-z-]+(?:\.[0-9A-Za-z-]+)*))?)?)$"
+        );
         let re = match regex::Regex::new(&pattern) {
             Ok(v) => v,
             Err(e) => {
@@ -73,7 +251,7 @@ impl Version {
                 return Err(());
             }
         };
-        assert_eq!(m.len(), 6);
+        assert_eq!(m.len(), 7);
 
         log::trace!("m: len = {}", m.len());
 
@@ -100,23 +278,74 @@ impl Version {
             .parse()
             .unwrap();
         let mut patch: Option<u64> = None;
-        let mut rc: Option<u64> = None;
+        let mut pre: Vec<PreReleaseIdentifier> = vec![];
+        let mut build: Vec<String> = vec![];
 
         if let Some(v) = m.get(4) {
             patch = Some(v.as_str().parse::<u64>().unwrap());
         }
         if let Some(v) = m.get(5) {
-            rc = Some(v.as_str().parse::<u64>().unwrap());
+            for id in v.as_str().split('.') {
+                match PreReleaseIdentifier::from_str(id) {
+                    Ok(i) => pre.push(i),
+                    Err(()) => {
+                        log::debug!("Invalid pre-release identifier '{}' in '{}'", id, value);
+                        return Err(());
+                    }
+                };
+            }
+        }
+        if let Some(v) = m.get(6) {
+            build = v.as_str().split('.').map(String::from).collect();
         }
 
         Ok(Version {
             major,
             minor,
             patch,
-            rc,
+            pre,
+            build,
         })
     }
 
+    /// Release candidate number, if this version is a release candidate (e.g.,
+    /// '0.17.0-rc1' or '0.17.0-rc.1').
+    ///
+    pub fn rc(self: &Self) -> Option<u64> {
+        let keys: Vec<PreReleaseKey> = self.pre.iter().flat_map(|i| i.get_keys()).collect();
+        match keys[..] {
+            [PreReleaseKey::AlphaNumeric("rc"), PreReleaseKey::Numeric(n)] => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Make this version release candidate 'rc', in the '-rcN' form used by
+    /// our tags.
+    ///
+    pub fn set_rc(self: &mut Self, rc: u64) {
+        self.pre = vec![PreReleaseIdentifier::AlphaNumeric(format!("rc{}", rc))];
+        self.build.clear();
+    }
+
+    pub fn is_prerelease(self: &Self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    /// Returns the pre-release and build metadata suffix of this version, if
+    /// any (e.g., '-beta.2+build.5').
+    ///
+    pub fn get_suffix_str(self: &Self) -> String {
+        let mut suffix = String::new();
+        if !self.pre.is_empty() {
+            let pre: Vec<String> = self.pre.iter().map(|i| i.to_string()).collect();
+            suffix = format!("-{}", pre.join("."));
+        }
+        if !self.build.is_empty() {
+            suffix = format!("{}+{}", suffix, self.build.join("."));
+        }
+        suffix
+    }
+
     /// Returns a version string based on the provided format.
     ///
     pub fn to_str_fmt(self: &Self, fmt: &String) -> String {
@@ -124,39 +353,23 @@ impl Version {
         hb.register_template_string("version", &fmt).unwrap();
 
         let mut data = HashMap::new();
-        data.insert("major", self.major);
-        data.insert("minor", self.minor);
+        data.insert("major", self.major.to_string());
+        data.insert("minor", self.minor.to_string());
         if let Some(p) = self.patch {
-            data.insert("patch", p);
+            data.insert("patch", p.to_string());
         }
-        if let Some(rc) = self.rc {
-            data.insert("rc", rc);
+        if let Some(rc) = self.rc() {
+            data.insert("rc", rc.to_string());
         }
 
         hb.render("version", &data).unwrap()
     }
 
+    /// Returns a version string based on the provided format, including any
+    /// pre-release and build metadata (e.g., 'v0.17.0-rc1').
+    ///
     pub fn to_rc_str_fmt(self: &Self, fmt: &String) -> String {
-        let base = self.to_str_fmt(&fmt);
-        if let Some(rc) = self.rc {
-            format!("{}-rc{}", base, rc)
-        } else {
-            base
-        }
-    }
-
-    pub fn get_version_id(self: &Self) -> u64 {
-        let mut patch: u64 = 999;
-        let mut rc: u64 = 999;
-
-        if let Some(v) = self.patch {
-            patch = v;
-        }
-        if let Some(v) = self.rc {
-            rc = v;
-        }
-
-        self.major * 10_u64.pow(9) + self.minor * 10_u64.pow(6) + patch * 10_u64.pow(3) + rc
+        format!("{}{}", self.to_str_fmt(&fmt), self.get_suffix_str())
     }
 
     pub fn get_base_version_str(self: &Self) -> String {
@@ -170,24 +383,17 @@ impl Version {
             major: self.major,
             minor: self.minor,
             patch: None,
-            rc: None,
+            pre: vec![],
+            build: vec![],
         }
     }
 
     pub fn get_version_str(self: &Self) -> String {
-        let p = match self.patch {
-            Some(v) => format!(".{}", v),
-            None => String::new(),
-        };
-        let rc = match self.rc {
-            Some(v) => format!("-rc{}", v),
-            None => String::new(),
-        };
-        format!("{}.{}{}{}", self.major, self.minor, p, rc,)
+        self.to_string()
     }
 
-    /// Release version is the vX.Y.Z umbrella version for any number of release
-    /// candidate versions and a given final release version.
+    /// Release version is the vX.Y.Z umbrella version for any number of
+    /// pre-release versions and a given final release version.
     ///
     pub fn get_release_version(self: &Self) -> Version {
         assert!(self.patch.is_some());
@@ -195,29 +401,210 @@ impl Version {
             major: self.major,
             minor: self.minor,
             patch: self.patch,
-            rc: None,
+            pre: vec![],
+            build: vec![],
         }
     }
 
-    pub fn min(self: &Self) -> Version {
-        let mut v = self.clone();
-        if v.patch.is_none() {
-            v.patch = Some(0);
+    /// Obtain the bounds of all versions under this version's umbrella: all
+    /// 'X.Y.*' versions for a base version 'X.Y', and all 'X.Y.Z-*' versions
+    /// for a release version 'X.Y.Z'. A pre-release is only bound by itself.
+    ///
+    pub fn get_range(self: &Self) -> (Bound<Version>, Bound<Version>) {
+        if self.is_prerelease() {
+            return (Bound::Included(self.clone()), Bound::Included(self.clone()));
         }
-        if v.rc.is_none() {
-            v.rc = Some(0);
+
+        // the lowest possible pre-release for a given patch version is '-0'.
+        let mut lower = self.clone();
+        lower.patch = Some(self.patch.unwrap_or(0));
+        lower.pre = vec![PreReleaseIdentifier::Numeric(0)];
+        lower.build.clear();
+
+        // a release version's builds come after the release version itself, so
+        // bound it by the lowest pre-release of the next patch version instead.
+        let upper = match self.patch {
+            Some(p) => Bound::Excluded(Version {
+                major: self.major,
+                minor: self.minor,
+                patch: Some(p + 1),
+                pre: vec![PreReleaseIdentifier::Numeric(0)],
+                build: vec![],
+            }),
+            None => Bound::Excluded(Version {
+                major: self.major,
+                minor: self.minor + 1,
+                patch: None,
+                pre: vec![],
+                build: vec![],
+            }),
+        };
+
+        (Bound::Included(lower), upper)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Version;
+    use std::cmp::Ordering;
+    use std::collections::BTreeMap;
+
+    fn ver(s: &str) -> Version {
+        Version::from_str(&s.into()).unwrap()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        for s in [
+            "0.17",
+            "0.17.0",
+            "0.17.0-rc1",
+            "0.18.0-alpha.1",
+            "0.18.0-beta.2+build.5",
+            "0.18.0+20231001",
+            "1.0.0-x-y.7.z.92",
+        ] {
+            assert_eq!(ver(s).to_string(), s);
+        }
+        assert_eq!(ver("v0.17.0-rc1").to_string(), "0.17.0-rc1");
+
+        for s in [
+            "0",
+            "0.17.0-",
+            "0.17.0-rc..1",
+            "0.17.0-01",
+            "0.17.0+",
+            "a.b.c",
+        ] {
+            assert!(Version::from_str(&s.into()).is_err(), "'{}' parsed", s);
         }
-        v
     }
 
-    pub fn max(self: &Self) -> Version {
-        let mut v = self.clone();
-        if v.patch.is_none() {
-            v.patch = Some(999);
+    #[test]
+    fn precedence() {
+        let ordered = [
+            "0.17",
+            "0.17.0-0",
+            "0.17.0-alpha",
+            "0.17.0-alpha.1",
+            "0.17.0-alpha.2",
+            "0.17.0-alpha.beta",
+            "0.17.0-beta",
+            "0.17.0-beta.2",
+            "0.17.0-beta.11",
+            "0.17.0-rc1",
+            "0.17.0-rc2",
+            "0.17.0-rc10",
+            "0.17.0",
+            "0.17.1-alpha.1",
+            "0.17.1",
+            "0.18.0",
+        ];
+        for pair in ordered.windows(2) {
+            let (a, b) = (ver(pair[0]), ver(pair[1]));
+            assert!(a < b, "{} < {}", a, b);
+            assert!(b > a, "{} > {}", b, a);
         }
-        if v.rc.is_none() {
-            v.rc = Some(999);
+
+        let same_precedence = [
+            ("0.17.0-rc1", "0.17.0-rc.1"),
+            ("0.17.0+build.1", "0.17.0+build.2"),
+            ("0.17.0", "0.17.0+build.1"),
+        ];
+        for (a, b) in same_precedence {
+            let (a, b) = (ver(a), ver(b));
+            assert_eq!(a.cmp_precedence(&b), Ordering::Equal, "{} = {}", a, b);
+            assert_ne!(a, b);
+            assert_ne!(a.cmp(&b), Ordering::Equal, "{} <> {}", a, b);
+            assert_eq!(a.cmp(&b), b.cmp(&a).reverse());
         }
-        v
+
+        let keys: BTreeMap<Version, ()> = ["0.17.0+b1", "0.17.0+b2", "0.17.0-rc1", "0.17.0-rc.1"]
+            .iter()
+            .map(|s| (ver(s), ()))
+            .collect();
+        assert_eq!(keys.len(), 4);
+    }
+
+    #[test]
+    fn ranges() {
+        let versions: BTreeMap<Version, ()> = [
+            "0.16.1",
+            "0.17",
+            "0.17.0-alpha.1",
+            "0.17.0-rc1",
+            "0.17.0-rc1000",
+            "0.17.0",
+            "0.17.0+b1",
+            "0.17.1000-rc1",
+            "0.17.1000",
+            "0.18",
+            "0.18.0-rc1",
+        ]
+        .iter()
+        .map(|s| (ver(s), ()))
+        .collect();
+
+        let in_range = |relver: &str| -> Vec<String> {
+            versions
+                .range(ver(relver).get_range())
+                .map(|(v, _)| v.to_string())
+                .collect()
+        };
+
+        assert_eq!(
+            in_range("0.17.0"),
+            [
+                "0.17.0-alpha.1",
+                "0.17.0-rc1",
+                "0.17.0-rc1000",
+                "0.17.0",
+                "0.17.0+b1"
+            ]
+        );
+        assert_eq!(in_range("0.17.1000"), ["0.17.1000-rc1", "0.17.1000"]);
+        assert_eq!(in_range("0.17.0-rc1000"), ["0.17.0-rc1000"]);
+        assert_eq!(
+            in_range("0.17"),
+            [
+                "0.17.0-alpha.1",
+                "0.17.0-rc1",
+                "0.17.0-rc1000",
+                "0.17.0",
+                "0.17.0+b1",
+                "0.17.1000-rc1",
+                "0.17.1000"
+            ]
+        );
+    }
+
+    #[test]
+    fn release_candidates() {
+        assert_eq!(ver("0.17.0-rc3").rc(), Some(3));
+        assert_eq!(ver("0.17.0-rc.3").rc(), Some(3));
+        assert_eq!(ver("0.17.0-beta.3").rc(), None);
+        assert_eq!(ver("0.17.0").rc(), None);
+
+        let mut v = ver("0.17.0+build.1");
+        v.set_rc(2);
+        assert_eq!(v.to_string(), "0.17.0-rc2");
+        assert_eq!(
+            v.to_rc_str_fmt(&"v{{major}}.{{minor}}.{{patch}}".into()),
+            "v0.17.0-rc2"
+        );
+    }
+
+    #[test]
+    fn serde() {
+        let v: Version =
+            serde_json::from_str(r#"{"major": 0, "minor": 17, "patch": 0, "rc": 2}"#).unwrap();
+        assert_eq!(v.to_string(), "0.17.0-rc2");
+
+        let v: Version = serde_json::from_str(r#""0.18.0-alpha.1+b.3""#).unwrap();
+        assert_eq!(
+            serde_json::to_string(&v).unwrap(),
+            r#""0.18.0-alpha.1+b.3""#
+        );
     }
 }
@@ -14,8 +14,11 @@
 
 pub mod cmds;
 pub mod config;
+pub mod dryrun;
 pub mod errors;
 pub mod init;
 mod prompt;
 pub mod repository;
+pub mod token;
+pub mod validate;
 pub mod workspace;
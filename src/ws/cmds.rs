@@ -16,9 +16,20 @@ use std::path::PathBuf;
 
 use crate::{boomln, errorln, infoln, successln};
 
+use super::{init::InitValues, token::WSTokenSource};
+
 #[derive(clap::Subcommand)]
 pub enum Cmds {
+    /// Create a new workspace.
     Init(InitCommand),
+    /// Show the workspace's config.
+    Show,
+    /// Set a single workspace config value.
+    Set(SetCommand),
+    /// Validate the workspace's config.
+    Validate,
+    /// Synchronize the workspace's repositories.
+    Sync,
 }
 
 #[derive(clap::Args)]
@@ -26,6 +37,69 @@ pub struct InitCommand {
     /// Workspace Path
     #[arg(value_name = "PATH")]
     pub path: PathBuf,
+
+    /// Workspace config file (JSON or YAML), instead of prompting
+    #[arg(value_name = "FILE", short, long)]
+    pub config: Option<PathBuf>,
+
+    /// Don't prompt, using the default config and provided values instead
+    #[arg(long)]
+    pub non_interactive: bool,
+
+    /// User name
+    #[arg(value_name = "NAME", long, env = "ARC_USER_NAME")]
+    pub user_name: Option<String>,
+
+    /// User email address
+    #[arg(value_name = "EMAIL", long, env = "ARC_USER_EMAIL")]
+    pub user_email: Option<String>,
+
+    /// Signing key ID
+    #[arg(value_name = "KEY", long, env = "ARC_SIGNING_KEY")]
+    pub signing_key: Option<String>,
+
+    /// GitHub token, kept in clear text in the workspace config
+    #[arg(value_name = "TOKEN", long, group = "token")]
+    pub github_token: Option<String>,
+
+    /// Environment variable to obtain the GitHub token from
+    #[arg(value_name = "VAR", long, group = "token")]
+    pub github_token_env: Option<String>,
+
+    /// File to obtain the GitHub token from
+    #[arg(value_name = "FILE", long, group = "token")]
+    pub github_token_file: Option<PathBuf>,
+
+    /// Command whose output is the GitHub token
+    #[arg(value_name = "COMMAND", long, group = "token")]
+    pub github_token_command: Option<String>,
+}
+
+#[derive(clap::Args)]
+pub struct SetCommand {
+    /// Config key (e.g., 'user.email', 'git.repos.s3gw.readwrite')
+    #[arg(value_name = "KEY")]
+    pub key: String,
+
+    /// Value to set
+    #[arg(value_name = "VALUE")]
+    pub value: String,
+}
+
+/// Obtain the GitHub token source provided to the init command, if any.
+///
+fn get_token_source(init: &InitCommand) -> Option<WSTokenSource> {
+    if let Some(v) = &init.github_token {
+        Some(WSTokenSource::Plain(v.clone()))
+    } else if let Some(v) = &init.github_token_env {
+        Some(WSTokenSource::Env(v.clone()))
+    } else if let Some(v) = &init.github_token_file {
+        Some(WSTokenSource::File(v.clone()))
+    } else {
+        init.github_token_command
+            .as_ref()
+            .map(|v| WSTokenSource::Command(v.clone()))
+    }
 }
 
 /// Handles workspace-related commands.
@@ -34,7 +108,18 @@ pub fn handle_cmds(cmd: &Cmds) {
     match cmd {
         Cmds::Init(init) => {
             infoln!("Create workspace at {}", init.path.display());
-            match super::init::init(&init.path) {
+            let values = if init.config.is_some() || init.non_interactive {
+                Some(InitValues {
+                    config: init.config.clone(),
+                    user_name: init.user_name.clone(),
+                    user_email: init.user_email.clone(),
+                    signing_key: init.signing_key.clone(),
+                    github_token: get_token_source(&init),
+                })
+            } else {
+                None
+            };
+            match super::init::init(&init.path, values.as_ref()) {
                 Ok(_) => {
                     successln!("Success!");
                 }
@@ -44,7 +129,6 @@ pub fn handle_cmds(cmd: &Cmds) {
             };
             return;
         }
-        #[allow(unreachable_patterns)]
         _ => {}
     }
 
@@ -55,7 +139,7 @@ pub fn handle_cmds(cmd: &Cmds) {
             return;
         }
     };
-    let _ws = match super::init::open(&path) {
+    let mut ws = match super::init::open(&path) {
         Ok(v) => v,
         Err(err) => {
             errorln!("Unable to open workspace at {}: {}", path.display(), err);
@@ -64,6 +148,45 @@ pub fn handle_cmds(cmd: &Cmds) {
     };
 
     match cmd {
+        Cmds::Show => {
+            match serde_json::to_string_pretty(&ws.config.redacted()) {
+                Ok(v) => println!("{}", v),
+                Err(err) => {
+                    boomln!("Unable to show workspace config: {}", err);
+                }
+            };
+        }
+        Cmds::Set(set) => {
+            if ws.config.set(&set.key, &set.value).is_err() {
+                errorln!("Unable to set '{}'", set.key);
+                return;
+            }
+            let cfgpath = ws.get_config_dir().join("config.json");
+            match ws.config.write(&cfgpath) {
+                Ok(()) => {
+                    successln!("Set '{}'", set.key);
+                    if set.key.ends_with(".readonly") || set.key.ends_with(".readwrite") {
+                        infoln!("Use 'arc ws sync' to update the repository's remotes.");
+                    }
+                }
+                Err(err) => {
+                    boomln!("Unable to write workspace config: {}", err);
+                }
+            };
+        }
+        Cmds::Validate => {
+            if super::validate::validate(&ws).is_err() {
+                errorln!("Workspace config is not valid!");
+            }
+        }
+        Cmds::Sync => match ws.sync() {
+            Ok(()) => {
+                successln!("Synchronized workspace.");
+            }
+            Err(()) => {
+                boomln!("Error synchronizing workspace!");
+            }
+        },
         Cmds::Init(_) => {
             boomln!("Should never reach this point!");
             return;
@@ -12,16 +12,165 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf};
 
-use crate::ws::errors::WorkspaceError;
+use crate::{errorln, ws::errors::WorkspaceError};
+
+use super::token::{deserialize_token_source, WSTokenSource};
 
 use super::errors::WorkspaceResult;
 
+/// Base URL for GitHub's API, used unless a repository specifies its own.
+pub const GITHUB_API_BASE: &str = "https://api.github.com";
+
+/// The kind of forge a repository is hosted on.
+///
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WSForgeKind {
+    #[default]
+    GitHub,
+    GitLab,
+    Gitea,
+}
+
+impl std::fmt::Display for WSForgeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            WSForgeKind::GitHub => "GitHub",
+            WSForgeKind::GitLab => "GitLab",
+            WSForgeKind::Gitea => "Gitea",
+        })
+    }
+}
+
+/// The forge hosting a repository. Named for GitHub, the default forge, as
+/// kept under 'github' in the workspace's config.
+///
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct WSGitHubConfig {
+    /// kind of forge, GitHub unless specified
+    #[serde(default)]
+    pub kind: WSForgeKind,
+    /// organization, user, or group owning the repository
     pub org: String,
     pub repo: String,
+    /// API base URL, for GitHub Enterprise or self-hosted forges (e.g.,
+    /// 'https://github.example.com/api/v3')
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// where to obtain the token to authenticate with, if not the user's
+    /// GitHub token
+    #[serde(default)]
+    pub token: Option<WSTokenSource>,
+    /// Workflows whose runs are tracked for this repository's release tags.
+    /// For the 's3gw' repository, the first one is the release workflow,
+    /// which must succeed before continuing a release.
+    #[serde(default)]
+    pub workflows: Vec<WSWorkflowConfig>,
+}
+
+/// How to identify a GitHub Actions workflow amongst a repository's workflow
+/// runs.
+///
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WSWorkflowConfig {
+    /// The workflow's name, compared case-insensitively (e.g., 'Release S3GW').
+    Name(String),
+    /// The workflow's file path (e.g., '.github/workflows/release.yaml').
+    Path(String),
+    /// The workflow's numeric id.
+    Id(u64),
+}
+
+impl std::fmt::Display for WSWorkflowConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WSWorkflowConfig::Name(name) => write!(f, "'{}'", name),
+            WSWorkflowConfig::Path(path) => write!(f, "at '{}'", path),
+            WSWorkflowConfig::Id(id) => write!(f, "with id {}", id),
+        }
+    }
+}
+
+impl WSWorkflowConfig {
+    /// The release workflow tracked on the 's3gw' repository, unless
+    /// configured otherwise.
+    ///
+    pub fn default_release() -> WSWorkflowConfig {
+        WSWorkflowConfig::Name("Release S3GW".into())
+    }
+
+    /// Whether a workflow run, with workflow 'name', file 'path', and
+    /// 'workflow_id', belongs to this workflow. Paths may be suffixed with
+    /// the ref the workflow was obtained from (e.g., '@refs/tags/v0.17.0').
+    ///
+    pub fn matches(
+        self: &Self,
+        name: Option<&String>,
+        path: Option<&String>,
+        workflow_id: Option<u64>,
+    ) -> bool {
+        match self {
+            WSWorkflowConfig::Name(expected) => match name {
+                Some(n) => n.to_lowercase() == expected.to_lowercase(),
+                None => false,
+            },
+            WSWorkflowConfig::Path(expected) => match path {
+                Some(p) => match p.split_once('@') {
+                    Some((p, _)) => p == expected,
+                    None => p == expected,
+                },
+                None => false,
+            },
+            WSWorkflowConfig::Id(expected) => workflow_id == Some(*expected),
+        }
+    }
+}
+
+impl WSGitHubConfig {
+    /// Whether the repository is hosted on GitHub.
+    ///
+    pub fn is_github(self: &Self) -> bool {
+        self.kind == WSForgeKind::GitHub
+    }
+
+    /// Check the forge config for repository 'repo' is sane. GitLab does not
+    /// report a pipeline's file nor workflow id, so its pipelines can only be
+    /// tracked by name.
+    ///
+    pub fn validate(self: &Self, repo: &String) -> WorkspaceResult<()> {
+        if self.kind != WSForgeKind::GitLab {
+            return Ok(());
+        }
+        let mut valid = true;
+        for workflow in &self.workflows {
+            if let WSWorkflowConfig::Name(_) = workflow {
+                continue;
+            }
+            errorln!(
+                "{}: GitLab pipelines can only be tracked by name, not by workflow {}.",
+                repo,
+                workflow
+            );
+            valid = false;
+        }
+        if !valid {
+            return Err(WorkspaceError::InvalidConfigError);
+        }
+        Ok(())
+    }
+
+    /// Obtain the GitHub API URL for 'path' on this repository (e.g., 'pulls').
+    ///
+    pub fn get_api_url(self: &Self, path: &str) -> String {
+        let base = match &self.api_base {
+            Some(v) => v.trim_end_matches('/'),
+            None => GITHUB_API_BASE,
+        };
+        format!("{}/repos/{}/{}/{}", base, self.org, self.repo, path)
+    }
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
@@ -35,20 +184,299 @@ pub struct WSGitRepoConfigValues {
     pub tag_format: String,
     pub release_branch_format: String,
     pub final_branch_format: Option<String>,
+    /// Dotted keys in the Helm chart's 'values.yaml' holding image tags, to be
+    /// updated to the released version. Only used for the charts repository.
+    #[serde(default)]
+    pub chart_image_tags: Vec<String>,
+}
+
+/// The role a repository plays in the release process.
+///
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WSGitRepoRole {
+    /// The repository tying all others together via submodules, and holding
+    /// the release notes (i.e., 's3gw').
+    Main,
+    /// The repository holding the Helm Chart (i.e., 's3gw-charts').
+    Charts,
+    /// Any other repository being released (e.g., 's3gw-ui', 'ceph').
+    Component,
+}
+
+/// A single repository tracked by the workspace.
+///
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct WSGitRepoConfig {
+    /// repository name, as shown to the user
+    pub name: String,
+    /// role of this repository in the release process
+    pub role: WSGitRepoRole,
+    /// directory name within the workspace
+    pub path: String,
+    /// path of this repository's submodule in the main repository, if any
+    pub submodule: Option<String>,
+    /// whether this repository's own submodules should be updated on sync
+    #[serde(default)]
+    pub update_submodules: bool,
+    #[serde(flatten)]
+    pub values: WSGitRepoConfigValues,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(from = "WSGitReposConfigFormat")]
 pub struct WSGitReposConfig {
-    pub s3gw: WSGitRepoConfigValues,
-    pub ceph: WSGitRepoConfigValues,
-    pub ui: WSGitRepoConfigValues,
-    pub charts: WSGitRepoConfigValues,
+    pub repos: Vec<WSGitRepoConfig>,
 }
 
+/// On-disk formats for the workspace's repositories config. Workspaces created
+/// before repositories were configurable have a fixed set of repositories,
+/// which are converted to their equivalent list.
+///
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum WSGitReposConfigFormat {
+    List { repos: Vec<WSGitRepoConfig> },
+    Legacy(Box<WSLegacyGitReposConfig>),
+}
+
+#[derive(serde::Deserialize)]
+struct WSLegacyGitReposConfig {
+    s3gw: WSGitRepoConfigValues,
+    ceph: WSGitRepoConfigValues,
+    ui: WSGitRepoConfigValues,
+    charts: WSGitRepoConfigValues,
+}
+
+impl From<WSGitReposConfigFormat> for WSGitReposConfig {
+    fn from(value: WSGitReposConfigFormat) -> Self {
+        match value {
+            WSGitReposConfigFormat::List { repos } => WSGitReposConfig { repos },
+            WSGitReposConfigFormat::Legacy(legacy) => {
+                let WSLegacyGitReposConfig {
+                    s3gw,
+                    ceph,
+                    ui,
+                    charts,
+                } = *legacy;
+                WSGitReposConfig::from_values(s3gw, ceph, ui, charts)
+            }
+        }
+    }
+}
+
+impl WSGitReposConfig {
+    /// Build the repository list for the s3gw project's repositories, from
+    /// each repository's values.
+    ///
+    fn from_values(
+        s3gw: WSGitRepoConfigValues,
+        ceph: WSGitRepoConfigValues,
+        ui: WSGitRepoConfigValues,
+        charts: WSGitRepoConfigValues,
+    ) -> WSGitReposConfig {
+        WSGitReposConfig {
+            repos: vec![
+                WSGitRepoConfig {
+                    name: "s3gw".into(),
+                    role: WSGitRepoRole::Main,
+                    path: "s3gw.git".into(),
+                    submodule: None,
+                    update_submodules: true,
+                    values: s3gw,
+                },
+                WSGitRepoConfig {
+                    name: "s3gw-ui".into(),
+                    role: WSGitRepoRole::Component,
+                    path: "s3gw-ui.git".into(),
+                    submodule: Some("ui".into()),
+                    update_submodules: false,
+                    values: ui,
+                },
+                WSGitRepoConfig {
+                    name: "s3gw-charts".into(),
+                    role: WSGitRepoRole::Charts,
+                    path: "charts.git".into(),
+                    submodule: Some("charts".into()),
+                    update_submodules: false,
+                    values: charts,
+                },
+                WSGitRepoConfig {
+                    name: "s3gw-ceph".into(),
+                    role: WSGitRepoRole::Component,
+                    path: "ceph.git".into(),
+                    submodule: Some("ceph".into()),
+                    update_submodules: false,
+                    values: ceph,
+                },
+            ],
+        }
+    }
+
+    /// Check the repository list is sane: names must be unique, and there must
+    /// be exactly one repository for each of the 'main' and 'charts' roles.
+    ///
+    pub fn validate(self: &Self) -> WorkspaceResult<()> {
+        for (idx, repo) in self.repos.iter().enumerate() {
+            if self.repos[..idx].iter().any(|r| r.name == repo.name) {
+                log::error!("Repository '{}' is defined more than once", repo.name);
+                return Err(WorkspaceError::ConfigError);
+            }
+        }
+        for role in [WSGitRepoRole::Main, WSGitRepoRole::Charts] {
+            let n = self.repos.iter().filter(|r| r.role == role).count();
+            if n != 1 {
+                log::error!(
+                    "Expected exactly one repository with role '{:?}', found {}",
+                    role,
+                    n
+                );
+                return Err(WorkspaceError::ConfigError);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for WSGitReposConfig {
+    fn default() -> Self {
+        WSGitReposConfig::from_values(
+            WSGitRepoConfigValues {
+                github: Some(WSGitHubConfig {
+                    kind: WSForgeKind::GitHub,
+                    org: "aquarist-labs".into(),
+                    repo: "s3gw".into(),
+                    api_base: None,
+                    token: None,
+                    workflows: vec![WSWorkflowConfig::default_release()],
+                }),
+                readonly: String::from("https://github.com/aquarist-labs/s3gw.git"),
+                readwrite: String::from("git@github.com:aquarist-labs/s3gw.git"),
+                tag_pattern: String::from(r"^v(\d+\.\d+\.\d+.*)$"),
+                release_branch_pattern: String::from(r"^s3gw-v(\d+\.\d+)$"),
+                final_branch_pattern: None,
+                tag_format: String::from("v{{major}}.{{minor}}.{{patch}}"),
+                release_branch_format: String::from("s3gw-v{{major}}.{{minor}}"),
+                final_branch_format: None,
+                chart_image_tags: vec![],
+            },
+            WSGitRepoConfigValues {
+                github: Some(WSGitHubConfig {
+                    kind: WSForgeKind::GitHub,
+                    org: "aquarist-labs".into(),
+                    repo: "ceph".into(),
+                    api_base: None,
+                    token: None,
+                    workflows: vec![],
+                }),
+                readonly: String::from("https://github.com/aquarist-labs/ceph.git"),
+                readwrite: String::from("git@github.com:aquarist-labs/ceph.git"),
+                tag_pattern: String::from(r"^s3gw-v(\d+\.\d+\.\d+.*)$"),
+                release_branch_pattern: String::from(r"^s3gw-v(\d+\.\d+)$"),
+                final_branch_pattern: None,
+                tag_format: String::from("s3gw-v{{major}}.{{minor}}.{{patch}}"),
+                release_branch_format: String::from("s3gw-v{{major}}.{{minor}}"),
+                final_branch_format: None,
+                chart_image_tags: vec![],
+            },
+            WSGitRepoConfigValues {
+                github: Some(WSGitHubConfig {
+                    kind: WSForgeKind::GitHub,
+                    org: "aquarist-labs".into(),
+                    repo: "s3gw-ui".into(),
+                    api_base: None,
+                    token: None,
+                    workflows: vec![],
+                }),
+                readonly: String::from("https://github.com/aquarist-labs/s3gw-ui.git"),
+                readwrite: String::from("git@github.com:aquarist-labs/s3gw-ui.git"),
+                tag_pattern: String::from(r"^s3gw-v(\d+\.\d+\.\d+.*)$"),
+                release_branch_pattern: String::from(r"^s3gw-v(\d+\.\d+)$"),
+                final_branch_format: None,
+                chart_image_tags: vec![],
+                tag_format: String::from("s3gw-v{{major}}.{{minor}}.{{patch}}"),
+                release_branch_format: String::from("s3gw-v{{major}}.{{minor}}"),
+                final_branch_pattern: None,
+            },
+            WSGitRepoConfigValues {
+                github: Some(WSGitHubConfig {
+                    kind: WSForgeKind::GitHub,
+                    org: "aquarist-labs".into(),
+                    repo: "s3gw-charts".into(),
+                    api_base: None,
+                    token: None,
+                    workflows: vec![],
+                }),
+                readonly: String::from("https://github.com/aquarist-labs/s3gw-charts.git"),
+                readwrite: String::from("git@github.com:aquarist-labs/s3gw-charts.git"),
+                tag_pattern: String::from(r"^s3gw-v(\d+\.\d+\.\d+.*)$"),
+                release_branch_pattern: String::from(r"^s3gw-v(\d+\.\d+)$"),
+                final_branch_pattern: Some(String::from(r"^v(\d+\.\d+)$")),
+                tag_format: String::from("s3gw-v{{major}}.{{minor}}.{{patch}}"),
+                release_branch_format: String::from("s3gw-v{{major}}.{{minor}}"),
+                final_branch_format: Some(String::from("v{{major}}.{{minor}}")),
+                chart_image_tags: vec![],
+            },
+        )
+    }
+}
+
+/// The kind of API a container registry provides.
+///
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WSRegistryKind {
+    /// Quay's own API (e.g., 'quay.io').
+    Quay,
+    /// Any registry implementing the OCI Distribution API (e.g., Harbor).
+    Oci,
+    /// GitHub's Container Registry.
+    Ghcr,
+}
+
+/// A container registry the release's images are pushed to.
+///
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct WSQuayRegistryConfig {
-    pub s3gw: String,
-    pub ui: String,
+pub struct WSRegistryConfig {
+    /// registry name, as shown to the user
+    pub name: String,
+    pub kind: WSRegistryKind,
+    /// registry's base URL, if not the default for its kind
+    #[serde(default)]
+    pub url: Option<String>,
+    /// repository for each image, by image name (e.g., 's3gw' at 's3gw/s3gw')
+    pub images: BTreeMap<String, String>,
+    /// user to authenticate as, if any
+    #[serde(default)]
+    pub username: Option<String>,
+    /// where to obtain the password or token to authenticate with, if any
+    #[serde(default)]
+    pub token: Option<WSTokenSource>,
+}
+
+/// Registry config for workspaces created before multiple registries were
+/// supported, with the s3gw and s3gw-ui images' repositories on quay.io.
+///
+#[derive(serde::Deserialize)]
+struct WSLegacyRegistryConfig {
+    s3gw: String,
+    ui: String,
+}
+
+impl WSRegistryConfig {
+    /// Build the config for the s3gw project's images on quay.io, at
+    /// repositories 's3gw' and 'ui'.
+    ///
+    pub fn quay(s3gw: String, ui: String) -> WSRegistryConfig {
+        WSRegistryConfig {
+            name: "quay".into(),
+            kind: WSRegistryKind::Quay,
+            url: None,
+            images: BTreeMap::from([("s3gw".into(), s3gw), ("s3gw-ui".into(), ui)]),
+            username: None,
+            token: None,
+        }
+    }
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
@@ -56,7 +484,96 @@ pub struct WSUserConfig {
     pub name: String,
     pub email: String,
     pub signing_key: String,
-    pub github_token: String,
+    #[serde(default, deserialize_with = "deserialize_token_source")]
+    pub github_token: WSTokenSource,
+
+    /// GitHub token, once obtained from its source
+    #[serde(skip)]
+    github_token_value: std::sync::OnceLock<String>,
+}
+
+/// Check whether 'value' looks like an email address.
+///
+pub fn is_valid_email(value: &str) -> bool {
+    let re = regex::Regex::new(r"^[\w_\-.]+@[\w\-_.]+$").unwrap();
+    re.is_match(value)
+}
+
+/// Check whether 'value' looks like a GitHub Personal Access Token.
+///
+pub fn is_valid_github_token(value: &str) -> bool {
+    let re = regex::Regex::new(r"^ghp_\w+$").unwrap();
+    re.is_match(value)
+}
+
+impl WSUserConfig {
+    /// Check user values, according to the same rules enforced when prompting
+    /// for them.
+    ///
+    pub fn validate(self: &Self) -> WorkspaceResult<()> {
+        let mut valid = true;
+        if self.name.is_empty() {
+            errorln!("User name must be provided.");
+            valid = false;
+        }
+        if !is_valid_email(&self.email) {
+            errorln!("User email '{}' is not an email address.", self.email);
+            valid = false;
+        }
+        if self.signing_key.is_empty() {
+            errorln!("Signing key must be provided.");
+            valid = false;
+        }
+        if !self.github_token.is_valid() {
+            errorln!("GitHub token source must be provided.");
+            valid = false;
+        } else if let WSTokenSource::Plain(token) = &self.github_token {
+            if !is_valid_github_token(&token) {
+                errorln!("GitHub token has the wrong format.");
+                valid = false;
+            }
+        }
+
+        if !valid {
+            return Err(WorkspaceError::InvalidConfigError);
+        }
+        Ok(())
+    }
+}
+
+impl WSUserConfig {
+    pub fn new(
+        name: String,
+        email: String,
+        signing_key: String,
+        github_token: WSTokenSource,
+    ) -> WSUserConfig {
+        WSUserConfig {
+            name,
+            email,
+            signing_key,
+            github_token,
+            github_token_value: std::sync::OnceLock::new(),
+        }
+    }
+
+    /// Obtain the GitHub token from its source. The token is obtained once,
+    /// and kept for subsequent calls.
+    ///
+    pub fn get_github_token(self: &Self) -> WorkspaceResult<String> {
+        if let Some(token) = self.github_token_value.get() {
+            return Ok(token.clone());
+        }
+        let token = match self.github_token.resolve() {
+            Ok(v) => v,
+            Err(err) => {
+                log::error!("Unable to obtain GitHub token from {}", self.github_token);
+                return Err(err);
+            }
+        };
+        let _ = self.github_token_value.set(token.clone());
+        Ok(token)
+    }
 }
 
 impl Default for WSUserConfig {
@@ -65,89 +582,221 @@ impl Default for WSUserConfig {
             name: String::new(),
             email: String::new(),
             signing_key: String::new(),
-            github_token: String::new(),
+            github_token: WSTokenSource::default(),
+            github_token_value: std::sync::OnceLock::new(),
         }
     }
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(from = "WSConfigFormat")]
 pub struct WSConfig {
     pub user: WSUserConfig,
     pub git: WSGitReposConfig,
-    pub registry: Option<WSQuayRegistryConfig>,
+    pub registries: Vec<WSRegistryConfig>,
+}
+
+/// On-disk format for the workspace's config. Workspaces created before
+/// multiple registries were supported have a single, optional, Quay registry.
+///
+#[derive(serde::Deserialize)]
+struct WSConfigFormat {
+    #[serde(default)]
+    user: WSUserConfig,
+    #[serde(default)]
+    git: WSGitReposConfig,
+    #[serde(default)]
+    registries: Vec<WSRegistryConfig>,
+    #[serde(default)]
+    registry: Option<WSLegacyRegistryConfig>,
+}
+
+impl From<WSConfigFormat> for WSConfig {
+    fn from(value: WSConfigFormat) -> Self {
+        let mut registries = value.registries;
+        if let Some(legacy) = value.registry {
+            registries.push(WSRegistryConfig::quay(legacy.s3gw, legacy.ui));
+        }
+        WSConfig {
+            user: value.user,
+            git: value.git,
+            registries,
+        }
+    }
 }
 
 impl Default for WSConfig {
     fn default() -> Self {
         WSConfig {
             user: WSUserConfig::default(),
-            git: WSGitReposConfig {
-                s3gw: WSGitRepoConfigValues {
-                    github: Some(WSGitHubConfig {
-                        org: "aquarist-labs".into(),
-                        repo: "s3gw".into(),
-                    }),
-                    readonly: String::from("https://github.com/aquarist-labs/s3gw.git"),
-                    readwrite: String::from("git@github.com:aquarist-labs/s3gw.git"),
-                    tag_pattern: String::from(r"^v(\d+\.\d+\.\d+.*)$"),
-                    release_branch_pattern: String::from(r"^s3gw-v(\d+\.\d+)$"),
-                    final_branch_pattern: None,
-                    tag_format: String::from("v{{major}}.{{minor}}.{{patch}}"),
-                    release_branch_format: String::from("s3gw-v{{major}}.{{minor}}"),
-                    final_branch_format: None,
-                },
-                ceph: WSGitRepoConfigValues {
-                    github: Some(WSGitHubConfig {
-                        org: "aquarist-labs".into(),
-                        repo: "ceph".into(),
-                    }),
-                    readonly: String::from("https://github.com/aquarist-labs/ceph.git"),
-                    readwrite: String::from("git@github.com:aquarist-labs/ceph.git"),
-                    tag_pattern: String::from(r"^s3gw-v(\d+\.\d+\.\d+.*)$"),
-                    release_branch_pattern: String::from(r"^s3gw-v(\d+\.\d+)$"),
-                    final_branch_pattern: None,
-                    tag_format: String::from("s3gw-v{{major}}.{{minor}}.{{patch}}"),
-                    release_branch_format: String::from("s3gw-v{{major}}.{{minor}}"),
-                    final_branch_format: None,
-                },
-                ui: WSGitRepoConfigValues {
-                    github: Some(WSGitHubConfig {
-                        org: "aquarist-labs".into(),
-                        repo: "s3gw-ui".into(),
-                    }),
-                    readonly: String::from("https://github.com/aquarist-labs/s3gw-ui.git"),
-                    readwrite: String::from("git@github.com:aquarist-labs/s3gw-ui.git"),
-                    tag_pattern: String::from(r"^s3gw-v(\d+\.\d+\.\d+.*)$"),
-                    release_branch_pattern: String::from(r"^s3gw-v(\d+\.\d+)$"),
-                    final_branch_format: None,
-                    tag_format: String::from("s3gw-v{{major}}.{{minor}}.{{patch}}"),
-                    release_branch_format: String::from("s3gw-v{{major}}.{{minor}}"),
-                    final_branch_pattern: None,
-                },
-                charts: WSGitRepoConfigValues {
-                    github: Some(WSGitHubConfig {
-                        org: "aquarist-labs".into(),
-                        repo: "s3gw-charts".into(),
-                    }),
-                    readonly: String::from("https://github.com/aquarist-labs/s3gw-charts.git"),
-                    readwrite: String::from("git@github.com:aquarist-labs/s3gw-charts.git"),
-                    tag_pattern: String::from(r"^s3gw-v(\d+\.\d+\.\d+.*)$"),
-                    release_branch_pattern: String::from(r"^s3gw-v(\d+\.\d+)$"),
-                    final_branch_pattern: Some(String::from(r"^v(\d+\.\d+)$")),
-                    tag_format: String::from("s3gw-v{{major}}.{{minor}}.{{patch}}"),
-                    release_branch_format: String::from("s3gw-v{{major}}.{{minor}}"),
-                    final_branch_format: Some(String::from("v{{major}}.{{minor}}")),
-                },
-            },
-            registry: Some(WSQuayRegistryConfig {
-                s3gw: "s3gw/s3gw".into(),
-                ui: "s3gw/s3gw-ui".into(),
-            }),
+            git: WSGitReposConfig::default(),
+            registries: vec![WSRegistryConfig::quay(
+                "s3gw/s3gw".into(),
+                "s3gw/s3gw-ui".into(),
+            )],
         }
     }
 }
 
 impl WSConfig {
+    /// Check this config is sane and complete.
+    ///
+    pub fn validate(self: &Self) -> WorkspaceResult<()> {
+        self.user.validate()?;
+        self.git.validate()?;
+        self.validate_forges()?;
+        self.validate_registries()
+    }
+
+    /// Check each repository's forge config, if any.
+    ///
+    fn validate_forges(self: &Self) -> WorkspaceResult<()> {
+        let mut valid = true;
+        for repo in &self.git.repos {
+            if let Some(github) = &repo.values.github {
+                valid &= github.validate(&repo.name).is_ok();
+            }
+        }
+        if !valid {
+            return Err(WorkspaceError::InvalidConfigError);
+        }
+        Ok(())
+    }
+
+    /// Check the registry list is sane: names must be unique, registries must
+    /// have images, and OCI registries must have an URL.
+    ///
+    fn validate_registries(self: &Self) -> WorkspaceResult<()> {
+        let mut valid = true;
+        for (idx, registry) in self.registries.iter().enumerate() {
+            if self.registries[..idx]
+                .iter()
+                .any(|r| r.name == registry.name)
+            {
+                errorln!("Registry '{}' is defined more than once.", registry.name);
+                valid = false;
+            }
+            if registry.images.is_empty() {
+                errorln!("Registry '{}' has no images.", registry.name);
+                valid = false;
+            }
+            if registry.kind == WSRegistryKind::Oci && registry.url.is_none() {
+                errorln!("OCI registry '{}' must have an URL.", registry.name);
+                valid = false;
+            }
+            if let Some(token) = &registry.token {
+                if !token.is_valid() {
+                    errorln!("Registry '{}' token source is not valid.", registry.name);
+                    valid = false;
+                }
+            }
+        }
+
+        if !valid {
+            return Err(WorkspaceError::InvalidConfigError);
+        }
+        Ok(())
+    }
+
+    /// Obtain a copy of this config, safe to be shown to the user, with
+    /// secrets redacted.
+    ///
+    pub fn redacted(self: &Self) -> WSConfig {
+        let mut cfg = self.clone();
+        if let WSTokenSource::Plain(_) = cfg.user.github_token {
+            cfg.user.github_token = WSTokenSource::Plain(String::from("<redacted>"));
+        }
+        for repo in &mut cfg.git.repos {
+            if let Some(github) = &mut repo.values.github {
+                if let Some(WSTokenSource::Plain(_)) = github.token {
+                    github.token = Some(WSTokenSource::Plain(String::from("<redacted>")));
+                }
+            }
+        }
+        for registry in &mut cfg.registries {
+            if let Some(WSTokenSource::Plain(_)) = registry.token {
+                registry.token = Some(WSTokenSource::Plain(String::from("<redacted>")));
+            }
+        }
+        cfg
+    }
+
+    /// Set the value at 'key' to 'value'. Keys are dot-separated paths into
+    /// the config (e.g., 'user.email'), where repositories are referred to by
+    /// name (e.g., 'git.repos.s3gw.readwrite'). The value is interpreted
+    /// according to the existing value's type, with JSON being expected for
+    /// non-scalar values, and the resulting config must be valid.
+    ///
+    pub fn set(self: &mut Self, key: &str, value: &str) -> WorkspaceResult<()> {
+        let mut root = match serde_json::to_value(&self) {
+            Ok(v) => v,
+            Err(err) => {
+                log::error!("Unable to serialize config: {}", err);
+                return Err(WorkspaceError::ConfigError);
+            }
+        };
+
+        let mut entry = &mut root;
+        for part in key.split('.') {
+            let next = match entry {
+                serde_json::Value::Object(map) => map.get_mut(part),
+                serde_json::Value::Array(vec) => match part.parse::<usize>() {
+                    Ok(idx) => vec.get_mut(idx),
+                    Err(_) => vec.iter_mut().find(|e| e["name"] == part),
+                },
+                _ => None,
+            };
+            entry = match next {
+                Some(v) => v,
+                None => {
+                    errorln!("Unknown config key '{}'", key);
+                    return Err(WorkspaceError::InvalidConfigError);
+                }
+            };
+        }
+
+        *entry = match entry {
+            serde_json::Value::Object(_) | serde_json::Value::Array(_) => {
+                match serde_json::from_str(value) {
+                    Ok(v) => v,
+                    Err(_) => {
+                        errorln!("Config key '{}' expects a JSON value", key);
+                        return Err(WorkspaceError::InvalidConfigError);
+                    }
+                }
+            }
+            serde_json::Value::String(_) => serde_json::Value::String(value.into()),
+            // unset values may be strings or objects; only the latter are JSON
+            serde_json::Value::Null => match serde_json::from_str(value) {
+                Ok(v @ serde_json::Value::Object(_)) | Ok(v @ serde_json::Value::Null) => v,
+                _ => serde_json::Value::String(value.into()),
+            },
+            _ => match serde_json::from_str(value) {
+                Ok(v) => v,
+                Err(_) => {
+                    errorln!("Invalid value '{}' for config key '{}'", value, key);
+                    return Err(WorkspaceError::InvalidConfigError);
+                }
+            },
+        };
+
+        let cfg: WSConfig = match serde_json::from_value(root) {
+            Ok(v) => v,
+            Err(err) => {
+                errorln!(
+                    "Invalid value '{}' for config key '{}': {}",
+                    value,
+                    key,
+                    err
+                );
+                return Err(WorkspaceError::InvalidConfigError);
+            }
+        };
+        cfg.validate()?;
+        *self = cfg;
+        Ok(())
+    }
+
     /// Write current config to 'path'. The file will be created if it does not exist.
     ///
     pub fn write(self: &Self, path: &PathBuf) -> WorkspaceResult<()> {
@@ -178,7 +827,8 @@ impl WSConfig {
         Ok(())
     }
 
-    /// Read config at 'path', returning a 'WSConfig' if it exists.
+    /// Read config at 'path', returning a 'WSConfig' if it exists. The config
+    /// is expected to be in JSON, unless the file has a YAML extension.
     ///
     pub fn read(path: &PathBuf) -> WorkspaceResult<WSConfig> {
         let f = match std::fs::File::open(path) {
@@ -188,13 +838,26 @@ impl WSConfig {
                 return Err(WorkspaceError::ConfigError);
             }
         };
-        let cfg: WSConfig = match serde_json::from_reader(f) {
+        let is_yaml = match path.extension() {
+            Some(ext) => ext == "yaml" || ext == "yml",
+            None => false,
+        };
+        let res: Result<WSConfig, String> = if is_yaml {
+            serde_yaml::from_reader(f).map_err(|e| e.to_string())
+        } else {
+            serde_json::from_reader(f).map_err(|e| e.to_string())
+        };
+        let cfg = match res {
             Ok(v) => v,
             Err(err) => {
                 log::error!("Error reading config from {}: {}", path.display(), err);
                 return Err(WorkspaceError::ConfigError);
             }
         };
+        if let Err(err) = cfg.git.validate() {
+            log::error!("Invalid config at {}: {}", path.display(), err);
+            return Err(err);
+        }
         Ok(cfg)
     }
 }
@@ -0,0 +1,161 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fmt::Display,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use colored::Colorize;
+
+/// An action that would have been performed against a repository, had we not
+/// been running in dry-run mode.
+///
+#[derive(Clone)]
+pub enum DryRunAction {
+    Branch {
+        name: String,
+        from: String,
+    },
+    Tag {
+        name: String,
+        target: String,
+    },
+    Commit {
+        message: String,
+    },
+    CherryPick {
+        commit: String,
+    },
+    Push {
+        refspec: String,
+    },
+    Submodule {
+        name: String,
+        refname: String,
+    },
+    Write {
+        path: PathBuf,
+    },
+    PullRequest {
+        title: String,
+        head: String,
+        base: String,
+    },
+    GitHubRelease {
+        tag: String,
+        prerelease: bool,
+        update: bool,
+    },
+}
+
+impl Display for DryRunAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DryRunAction::Branch { name, from } => {
+                write!(f, "create branch '{}' from '{}'", name, from)
+            }
+            DryRunAction::Tag { name, target } => {
+                write!(f, "create signed tag '{}' on '{}'", name, target)
+            }
+            DryRunAction::Commit { message } => write!(f, "commit '{}'", message),
+            DryRunAction::CherryPick { commit } => write!(f, "cherry-pick '{}'", commit),
+            DryRunAction::Push { refspec } => write!(f, "push '{}'", refspec),
+            DryRunAction::Submodule { name, refname } => {
+                write!(f, "bump submodule '{}' to '{}'", name, refname)
+            }
+            DryRunAction::Write { path } => write!(f, "write '{}'", path.display()),
+            DryRunAction::PullRequest { title, head, base } => {
+                write!(
+                    f,
+                    "open pull request '{}' from '{}' to '{}'",
+                    title, head, base
+                )
+            }
+            DryRunAction::GitHubRelease {
+                tag,
+                prerelease,
+                update,
+            } => {
+                write!(
+                    f,
+                    "{} GitHub {}release for '{}'",
+                    if *update { "update" } else { "create" },
+                    if *prerelease { "pre-" } else { "" },
+                    tag
+                )
+            }
+        }
+    }
+}
+
+/// Records actions per repository while in dry-run mode. It is shared between
+/// all the workspace's repositories, so the resulting plan keeps the order in
+/// which actions would have been performed.
+///
+#[derive(Clone, Default)]
+pub struct DryRunPlan {
+    entries: Arc<Mutex<Vec<(String, DryRunAction)>>>,
+}
+
+impl DryRunPlan {
+    pub fn new() -> DryRunPlan {
+        DryRunPlan::default()
+    }
+
+    /// Record an action for repository 'repo'.
+    ///
+    pub fn record(self: &Self, repo: &String, action: DryRunAction) {
+        log::debug!("dry-run: {}: {}", repo, action);
+        self.entries.lock().unwrap().push((repo.clone(), action));
+    }
+
+    /// Check whether a branch named 'name' would have been created on
+    /// repository 'repo'.
+    ///
+    pub fn has_branch(self: &Self, repo: &String, name: &String) -> bool {
+        self.entries.lock().unwrap().iter().any(|(r, a)| match a {
+            DryRunAction::Branch { name: n, .. } => r == repo && n == name,
+            _ => false,
+        })
+    }
+
+    /// Print the recorded plan, grouping actions by repository while keeping
+    /// the order in which repositories were first acted upon.
+    ///
+    pub fn print(self: &Self) {
+        let entries = self.entries.lock().unwrap();
+        if entries.is_empty() {
+            println!("Dry-run: no actions would have been performed.");
+            return;
+        }
+
+        let mut repos: Vec<&String> = vec![];
+        for (repo, _) in entries.iter() {
+            if !repos.contains(&repo) {
+                repos.push(repo);
+            }
+        }
+
+        println!("\n{}", "Dry-run plan:".bold());
+        for repo in repos {
+            println!("\n  {}", repo.cyan().bold());
+            for (n, (_, action)) in entries.iter().filter(|(r, _)| r == repo).enumerate() {
+                println!("    {:>3}. {}", n + 1, action);
+            }
+        }
+        println!();
+    }
+}
@@ -32,6 +32,9 @@ pub enum RepositoryError {
     RemoteUpdateError,
     SubmoduleUpdateError,
     BranchingError,
+    DeletingError,
+    ResettingError,
+    CherryPickError,
 
     UnknownError,
 }
@@ -55,6 +58,9 @@ impl Display for RepositoryError {
             RepositoryError::RemoteUpdateError => "error updating remote",
             RepositoryError::SubmoduleUpdateError => "error updating submodules",
             RepositoryError::BranchingError => "error branching",
+            RepositoryError::DeletingError => "error deleting reference",
+            RepositoryError::ResettingError => "error resetting branch",
+            RepositoryError::CherryPickError => "error cherry-picking",
 
             // unknown error
             RepositoryError::UnknownError => "unknown error",
@@ -69,8 +75,10 @@ pub enum WorkspaceError {
     DoesNotExistError,
     AlreadyExistsError,
     ConfigError,
+    InvalidConfigError,
     CreationError,
     SyncError,
+    TokenError,
 
     // create prompt errors
     UserAborted,
@@ -86,8 +94,10 @@ impl Display for WorkspaceError {
             WorkspaceError::DoesNotExistError => "workspace does not exist",
             WorkspaceError::AlreadyExistsError => "workspace already exists",
             WorkspaceError::ConfigError => "config error",
+            WorkspaceError::InvalidConfigError => "invalid config",
             WorkspaceError::CreationError => "error creating workspace",
             WorkspaceError::SyncError => "error synchronizing workspace",
+            WorkspaceError::TokenError => "unable to obtain token",
 
             // create prompt errors
             WorkspaceError::UserAborted => "aborted by user",
@@ -14,12 +14,62 @@
 
 use std::path::PathBuf;
 
-use crate::ws::errors::WorkspaceError;
+use crate::{errorln, ws::errors::WorkspaceError};
 
-use super::{config::WSConfig, errors::WorkspaceResult, prompt::init_prompt, workspace::Workspace};
+use super::{
+    config::WSConfig, errors::WorkspaceResult, prompt::init_prompt, token::WSTokenSource,
+    workspace::Workspace,
+};
 
-/// Create and initiate a new workspace at 'path'.
-pub fn init(path: &PathBuf) -> WorkspaceResult<Workspace> {
+/// Values used to create a new workspace without prompting the user.
+///
+pub struct InitValues {
+    /// config file to start from, instead of the default config
+    pub config: Option<PathBuf>,
+    pub user_name: Option<String>,
+    pub user_email: Option<String>,
+    pub signing_key: Option<String>,
+    pub github_token: Option<WSTokenSource>,
+}
+
+impl InitValues {
+    /// Obtain a workspace config from these values, starting from either the
+    /// provided config file or the default config, and overriding user values
+    /// if provided. The resulting config is validated.
+    ///
+    pub fn to_config(self: &Self) -> WorkspaceResult<WSConfig> {
+        let mut cfg = match &self.config {
+            None => WSConfig::default(),
+            Some(path) => match WSConfig::read(&path) {
+                Ok(v) => v,
+                Err(err) => {
+                    errorln!("Unable to read config from '{}': {}", path.display(), err);
+                    return Err(err);
+                }
+            },
+        };
+
+        if let Some(v) = &self.user_name {
+            cfg.user.name = v.clone();
+        }
+        if let Some(v) = &self.user_email {
+            cfg.user.email = v.clone();
+        }
+        if let Some(v) = &self.signing_key {
+            cfg.user.signing_key = v.clone();
+        }
+        if let Some(v) = &self.github_token {
+            cfg.user.github_token = v.clone();
+        }
+
+        cfg.validate()?;
+        Ok(cfg)
+    }
+}
+
+/// Create and initiate a new workspace at 'path'. If 'values' is provided, the
+/// workspace config is obtained from them instead of prompting the user.
+pub fn init(path: &PathBuf, values: Option<&InitValues>) -> WorkspaceResult<Workspace> {
     let arcpath = path.join(".arc");
     let cfgpath = arcpath.join("config.json");
 
@@ -27,7 +77,7 @@ pub fn init(path: &PathBuf) -> WorkspaceResult<Workspace> {
         log::error!("Workspace at {} already exists.", path.display());
         return Err(WorkspaceError::AlreadyExistsError);
     } else if !path.exists() || !arcpath.exists() || !cfgpath.exists() {
-        match create_workspace(path) {
+        match create_workspace(path, values) {
             Ok(()) => {}
             Err(err) => {
                 log::error!("Unable to create workspace at {}: {}", path.display(), err);
@@ -67,8 +117,20 @@ pub fn open(path: &PathBuf) -> WorkspaceResult<Workspace> {
 }
 
 /// Creates a new workspace, obtaining information required from the user (via
-/// prompts), and writes a workspace config file.
-fn create_workspace(path: &PathBuf) -> WorkspaceResult<()> {
+/// prompts) unless 'values' are provided, and writes a workspace config file.
+fn create_workspace(path: &PathBuf, values: Option<&InitValues>) -> WorkspaceResult<()> {
+    let cfg = match values {
+        None => init_prompt(&WSConfig::default()),
+        Some(v) => v.to_config(),
+    };
+    let cfg = match cfg {
+        Ok(v) => v,
+        Err(err) => {
+            log::error!("Unable to generate workspace config: {}", err);
+            return Err(WorkspaceError::ConfigError);
+        }
+    };
+
     let arcpath = path.join(".arc");
     if !arcpath.exists() {
         std::fs::create_dir_all(&arcpath).expect("Unable to create directories");
@@ -78,13 +140,6 @@ fn create_workspace(path: &PathBuf) -> WorkspaceResult<()> {
     let cfgpath = arcpath.join("config.json");
     assert!(!cfgpath.exists());
 
-    let cfg = match init_prompt(&WSConfig::default()) {
-        Ok(v) => v,
-        Err(err) => {
-            log::error!("Unable to generate workspace config: {}", err);
-            return Err(WorkspaceError::ConfigError);
-        }
-    };
     match cfg.write(&cfgpath) {
         Ok(_) => {}
         Err(_) => {
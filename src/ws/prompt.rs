@@ -12,16 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use inquire::{required, Confirm, Text};
+use inquire::{required, Confirm, Select, Text};
 
 use crate::ws::errors::WorkspaceError;
 
 use super::{
     config::{
-        WSConfig, WSGitHubConfig, WSGitRepoConfigValues, WSGitReposConfig, WSQuayRegistryConfig,
-        WSUserConfig,
+        is_valid_email, is_valid_github_token, WSConfig, WSForgeKind, WSGitHubConfig,
+        WSGitRepoConfigValues, WSGitReposConfig, WSRegistryConfig, WSUserConfig,
     },
     errors::WorkspaceResult,
+    token::{WSTokenSource, DEFAULT_TOKEN_ENV},
 };
 
 /// Prompt for a specific custom git repository. This is a helper function.
@@ -83,6 +84,7 @@ fn prompt_custom_git_repo_value(
         tag_format: default.tag_format.clone(),
         release_branch_format: default.release_branch_format.clone(),
         final_branch_format: default.final_branch_format.clone(),
+        chart_image_tags: default.chart_image_tags.clone(),
     }))
 }
 
@@ -116,8 +118,16 @@ fn prompt_custom_github_repo_value(
 
     Ok(WSGitRepoConfigValues {
         github: Some(WSGitHubConfig {
+            kind: WSForgeKind::GitHub,
             org: org.clone(),
             repo: gitless_repo.clone(),
+            api_base: None,
+            token: None,
+            workflows: default
+                .github
+                .as_ref()
+                .map(|gh| gh.workflows.clone())
+                .unwrap_or_default(),
         }),
         readonly: format!("https://github.com/{}/{}", org, repo),
         readwrite: format!("git@github.com:{}/{}", org, repo),
@@ -127,6 +137,7 @@ fn prompt_custom_github_repo_value(
         tag_format: default.tag_format.clone(),
         release_branch_format: default.release_branch_format.clone(),
         final_branch_format: default.final_branch_format.clone(),
+        chart_image_tags: default.chart_image_tags.clone(),
     })
 }
 
@@ -159,23 +170,14 @@ fn prompt_custom_git_repos(default: &WSGitReposConfig) -> WorkspaceResult<WSGitR
             }
         };
 
-        let repo_vec = vec![
-            ("s3gw", "s3gw.git", &default.s3gw, &mut cfg.s3gw),
-            ("s3gw-ui", "s3gw-ui.git", &default.ui, &mut cfg.ui),
-            (
-                "charts",
-                "s3gw-charts.git",
-                &default.charts,
-                &mut cfg.charts,
-            ),
-            ("ceph", "ceph.git", &default.ceph, &mut cfg.ceph),
-        ];
-
-        for entry in repo_vec {
-            match prompt_custom_github_repo_value(entry.0, &org, entry.1, &entry.2) {
+        for entry in &mut cfg.repos {
+            let default_name = match &entry.values.github {
+                Some(gh) => format!("{}.git", gh.repo),
+                None => format!("{}.git", entry.name),
+            };
+            match prompt_custom_github_repo_value(&entry.name, &org, &default_name, &entry.values) {
                 Ok(v) => {
-                    let tgt = entry.3;
-                    *tgt = v;
+                    entry.values = v;
                 }
                 Err(err) => return Err(err),
             };
@@ -185,19 +187,11 @@ fn prompt_custom_git_repos(default: &WSGitReposConfig) -> WorkspaceResult<WSGitR
         return Ok(cfg);
     }
 
-    let repo_vec = vec![
-        ("s3gw", &default.s3gw, &mut cfg.s3gw),
-        ("s3gw-ui", &default.ui, &mut cfg.ui),
-        ("charts", &default.charts, &mut cfg.charts),
-        ("ceph", &default.ceph, &mut cfg.ceph),
-    ];
-
-    for entry in repo_vec {
-        match prompt_custom_git_repo_value(entry.0, entry.1) {
+    for entry in &mut cfg.repos {
+        match prompt_custom_git_repo_value(&entry.name, &entry.values) {
             Ok(None) => {}
             Ok(Some(v)) => {
-                let tgt = entry.2;
-                *tgt = v;
+                entry.values = v;
             }
             Err(err) => return Err(err),
         };
@@ -206,19 +200,19 @@ fn prompt_custom_git_repos(default: &WSGitReposConfig) -> WorkspaceResult<WSGitR
     Ok(cfg)
 }
 
-/// Prompt for quay registries for deliverable artifacts.
+/// Prompt for the quay registry's repositories for deliverable artifacts.
+/// Additional registries can be configured later on.
 ///
-fn prompt_registries(default: &WSQuayRegistryConfig) -> WorkspaceResult<WSQuayRegistryConfig> {
-    let s3gw = match prompt_single_registry_repo(&"s3gw".into(), &default.s3gw) {
-        Ok(v) => v,
-        Err(err) => return Err(err),
-    };
-    let ui = match prompt_single_registry_repo(&"s3gw-ui".into(), &default.ui) {
-        Ok(v) => v,
-        Err(err) => return Err(err),
-    };
+fn prompt_registries(default: &WSRegistryConfig) -> WorkspaceResult<WSRegistryConfig> {
+    let mut registry = default.clone();
+    for (name, repo) in registry.images.iter_mut() {
+        *repo = match prompt_single_registry_repo(&name, &repo) {
+            Ok(v) => v,
+            Err(err) => return Err(err),
+        };
+    }
 
-    Ok(WSQuayRegistryConfig { s3gw, ui })
+    Ok(registry)
 }
 
 /// Prompt for a single repository's location (i.e., namespace/repository).
@@ -256,8 +250,7 @@ fn prompt_user() -> WorkspaceResult<WSUserConfig> {
     };
     let email = match Text::new("User email:")
         .with_validator(|v: &str| {
-            let re = regex::Regex::new(r"^[\w_\-.]+@[\w\-_.]+$").unwrap();
-            if re.is_match(&v) {
+            if is_valid_email(&v) {
                 return Ok(inquire::validator::Validation::Valid);
             }
             Ok(inquire::validator::Validation::Invalid(
@@ -288,18 +281,56 @@ fn prompt_user() -> WorkspaceResult<WSUserConfig> {
             });
         }
     };
-    let ghtoken = match Text::new("GitHub token:")
-        .with_validator(|v: &str| {
-            let re = regex::Regex::new(r"^ghp_\w+$").unwrap();
-            if re.is_match(v) {
+    let github_token = match prompt_github_token() {
+        Ok(v) => v,
+        Err(err) => return Err(err),
+    };
+
+    Ok(WSUserConfig::new(name, email, signing_key, github_token))
+}
+
+/// Prompt for where to obtain the GitHub token from. Only when explicitly
+/// chosen by the user will the token be kept in the workspace's config.
+///
+fn prompt_github_token() -> WorkspaceResult<WSTokenSource> {
+    let options = vec![
+        "environment variable",
+        "file",
+        "command",
+        "workspace config (clear text)",
+    ];
+    let choice = match Select::new("Obtain GitHub token from:", options).prompt() {
+        Ok(v) => v,
+        Err(err) => {
+            return Err(match err {
+                inquire::InquireError::OperationCanceled
+                | inquire::InquireError::OperationInterrupted => WorkspaceError::UserAborted,
+                _ => WorkspaceError::UnknownPromptError,
+            });
+        }
+    };
+
+    let (prompt, default) = match choice {
+        "environment variable" => ("Environment variable:", DEFAULT_TOKEN_ENV),
+        "file" => ("Token file path:", ""),
+        "command" => ("Token command:", "pass show github/token"),
+        _ => ("GitHub token:", ""),
+    };
+    let mut text = Text::new(prompt).with_validator(required!());
+    if !default.is_empty() {
+        text = text.with_default(default);
+    }
+    if choice == "workspace config (clear text)" {
+        text = text.with_validator(|v: &str| {
+            if is_valid_github_token(v) {
                 return Ok(inquire::validator::Validation::Valid);
             }
             Ok(inquire::validator::Validation::Invalid(
                 "wrong token format".into(),
             ))
-        })
-        .prompt()
-    {
+        });
+    }
+    let value = match text.prompt() {
         Ok(v) => v,
         Err(err) => {
             return Err(match err {
@@ -310,11 +341,11 @@ fn prompt_user() -> WorkspaceResult<WSUserConfig> {
         }
     };
 
-    Ok(WSUserConfig {
-        name,
-        email,
-        signing_key,
-        github_token: ghtoken,
+    Ok(match choice {
+        "environment variable" => WSTokenSource::Env(value),
+        "file" => WSTokenSource::File(value.into()),
+        "command" => WSTokenSource::Command(value),
+        _ => WSTokenSource::Plain(value),
     })
 }
 
@@ -358,12 +389,12 @@ pub fn init_prompt(default_config: &WSConfig) -> WorkspaceResult<WSConfig> {
         .prompt()
     {
         Ok(false) => {
-            cfg.registry = None;
+            cfg.registries = vec![];
         }
         Ok(true) => {
-            let default_registry = default_config.registry.as_ref().unwrap();
-            cfg.registry = match prompt_registries(&default_registry) {
-                Ok(v) => Some(v),
+            let default_registry = default_config.registries.first().unwrap();
+            cfg.registries = match prompt_registries(&default_registry) {
+                Ok(v) => vec![v],
                 Err(err) => return Err(err),
             }
         }
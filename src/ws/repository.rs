@@ -18,9 +18,12 @@ use crate::git;
 use crate::{boomln, version::Version};
 use crate::{errorln, successln};
 
+use super::dryrun::{DryRunAction, DryRunPlan};
 use super::errors::RepositoryResult;
 use super::{
-    config::{WSGitRepoConfigValues, WSGitReposConfig, WSUserConfig},
+    config::{
+        WSGitRepoConfig, WSGitRepoConfigValues, WSGitRepoRole, WSGitReposConfig, WSUserConfig,
+    },
     errors::RepositoryError,
 };
 
@@ -30,15 +33,27 @@ pub struct Repository {
     pub path: PathBuf,
     pub user_config: WSUserConfig,
     pub config: WSGitRepoConfigValues,
+    pub role: WSGitRepoRole,
+    pub submodule: Option<String>,
     pub update_submodules: bool,
+    pub dry_run: Option<DryRunPlan>,
 }
 
+/// A cherry-pick that could not be applied cleanly.
+///
+pub struct CherryPickConflict {
+    /// the commit failing to apply
+    pub commit: String,
+    /// paths with conflicts; empty if the commit would be empty once applied
+    pub paths: Vec<String>,
+}
+
+/// The workspace's repositories, as defined in the workspace config, in the
+/// order they have been defined.
+///
 #[derive(Clone)]
 pub struct Repos {
-    pub s3gw: Repository,
-    pub ui: Repository,
-    pub charts: Repository,
-    pub ceph: Repository,
+    entries: Vec<Repository>,
 }
 
 impl Repos {
@@ -47,78 +62,112 @@ impl Repos {
         user_config: &WSUserConfig,
         git_config: &WSGitReposConfig,
     ) -> Result<Repos, ()> {
-        let s3gw = match Repository::init(
-            &"s3gw".into(),
-            &base_path.join("s3gw.git"),
-            &user_config,
-            &git_config.s3gw,
-            true,
-        ) {
-            Ok(v) => v,
-            Err(_) => return Err(()),
-        };
-        let ui = match Repository::init(
-            &"s3gw-ui".into(),
-            &base_path.join("s3gw-ui.git"),
-            &user_config,
-            &git_config.ui,
-            false,
-        ) {
-            Ok(v) => v,
-            Err(_) => return Err(()),
-        };
-        let charts = match Repository::init(
-            &"s3gw-charts".into(),
-            &base_path.join("charts.git"),
-            &user_config,
-            &git_config.charts,
-            false,
-        ) {
-            Ok(v) => v,
-            Err(_) => return Err(()),
-        };
-        let ceph = match Repository::init(
-            &"s3gw-ceph".into(),
-            &base_path.join("ceph.git"),
-            &user_config,
-            &git_config.ceph,
-            false,
-        ) {
-            Ok(v) => v,
-            Err(_) => return Err(()),
-        };
+        if let Err(err) = git_config.validate() {
+            log::error!("Invalid repositories config: {}", err);
+            return Err(());
+        }
 
-        Ok(Repos {
-            s3gw,
-            ui,
-            charts,
-            ceph,
-        })
+        let mut entries: Vec<Repository> = vec![];
+        for entry in &git_config.repos {
+            match Repository::init(&base_path, &user_config, &entry) {
+                Ok(v) => entries.push(v),
+                Err(_) => return Err(()),
+            };
+        }
+
+        Ok(Repos { entries })
     }
 
     pub fn as_vec(self: &Self) -> Vec<&Repository> {
-        vec![&self.s3gw, &self.ui, &self.charts, &self.ceph]
+        self.entries.iter().collect()
+    }
+
+    /// Obtain repository named 'name', if it exists.
+    ///
+    pub fn get(self: &Self, name: &str) -> Option<&Repository> {
+        self.entries.iter().find(|r| r.name == name)
+    }
+
+    /// Obtain the first repository with role 'role'. There's always one
+    /// repository for each of the 'main' and 'charts' roles, as ensured when
+    /// the config is validated.
+    ///
+    fn get_by_role(self: &Self, role: WSGitRepoRole) -> &Repository {
+        self.entries
+            .iter()
+            .find(|r| r.role == role)
+            .expect("missing repository for role")
+    }
+
+    /// Obtain the main repository, i.e. 's3gw'.
+    ///
+    pub fn main(self: &Self) -> &Repository {
+        self.get_by_role(WSGitRepoRole::Main)
+    }
+
+    /// Obtain the Helm Chart repository, i.e. 's3gw-charts'.
+    ///
+    pub fn charts(self: &Self) -> &Repository {
+        self.get_by_role(WSGitRepoRole::Charts)
+    }
+
+    /// Obtain all repositories which are submodules of the main repository.
+    ///
+    pub fn submodules(self: &Self) -> Vec<&Repository> {
+        self.entries
+            .iter()
+            .filter(|r| r.submodule.is_some())
+            .collect()
+    }
+
+    /// Put all repositories in dry-run mode, recording actions to 'plan'
+    /// instead of performing them.
+    ///
+    pub fn set_dry_run(self: &mut Self, plan: &DryRunPlan) {
+        for repo in &mut self.entries {
+            repo.dry_run = Some(plan.clone());
+        }
     }
 }
 
 impl Repository {
     pub fn init(
-        name: &String,
-        path: &PathBuf,
+        base_path: &PathBuf,
         user_config: &WSUserConfig,
-        config: &WSGitRepoConfigValues,
-        update_submodules: bool,
+        config: &WSGitRepoConfig,
     ) -> Result<Repository, ()> {
         let repo = Repository {
-            name: name.clone(),
-            path: path.to_path_buf(),
+            name: config.name.clone(),
+            path: base_path.join(&config.path),
             user_config: user_config.clone(),
-            config: config.clone(),
-            update_submodules,
+            config: config.values.clone(),
+            role: config.role,
+            submodule: config.submodule.clone(),
+            update_submodules: config.update_submodules,
+            dry_run: None,
         };
         Ok(repo)
     }
 
+    /// Whether this repository is in dry-run mode.
+    ///
+    pub fn is_dry_run(self: &Self) -> bool {
+        self.dry_run.is_some()
+    }
+
+    /// Record 'action' in the dry-run plan, if in dry-run mode. Returns true if
+    /// the action has been recorded, in which case it must not be performed.
+    ///
+    pub fn dry_run_record(self: &Self, action: DryRunAction) -> bool {
+        match &self.dry_run {
+            None => false,
+            Some(plan) => {
+                plan.record(&self.name, action);
+                true
+            }
+        }
+    }
+
     fn version_to_str(self: &Self, ver: &Version, is_tag: bool) -> String {
         log::trace!(
             "version_to_str: repo name '{}' path '{}' format '{}'",
@@ -141,14 +190,50 @@ impl Repository {
             ver,
             is_tag
         );
-        if let Some(rc) = ver.rc {
+        if ver.is_prerelease() {
             assert!(is_tag);
-            format!("{}-rc{}", ver_base_str, rc)
+            format!("{}{}", ver_base_str, ver.get_suffix_str())
         } else {
             ver_base_str
         }
     }
 
+    /// Obtain the expected URL for each of the repository's remotes, as
+    /// configured.
+    ///
+    pub fn get_remote_urls(self: &Self) -> [(&'static str, &String); 2] {
+        [
+            ("ro", &self.config.readonly),
+            ("rw", &self.config.readwrite),
+        ]
+    }
+
+    /// Point the clone's remotes at the configured URLs, should they have been
+    /// changed since the repository was cloned.
+    ///
+    fn update_remote_urls(self: &Self, git: &git::repo::GitRepo) -> RepositoryResult<()> {
+        for (name, url) in self.get_remote_urls() {
+            let current = match git.get_remote_url(&name) {
+                Ok(v) => v,
+                Err(()) => return Err(RepositoryError::RemoteUpdateError),
+            };
+            if &current == url {
+                continue;
+            }
+            log::info!(
+                "{}: update remote '{}' from '{}' to '{}'",
+                self.name,
+                name,
+                current,
+                url
+            );
+            if let Err(()) = git.set_remote_url(&name, &url) {
+                return Err(RepositoryError::RemoteUpdateError);
+            }
+        }
+        Ok(())
+    }
+
     /// Synchronize local repository with its upstream. If the repository does
     /// not exist yet, it will be cloned.
     ///
@@ -176,6 +261,7 @@ impl Repository {
             Ok(v) => v,
             Err(()) => return Err(RepositoryError::UnableToOpenRepositoryError),
         };
+        self.update_remote_urls(&git)?;
         log::debug!("Updating remote for repo at {}", self.path.display());
         match git.remote_update(&self.name) {
             Ok(()) => {
@@ -210,7 +296,7 @@ impl Repository {
     ///
     pub fn get_releases(
         self: &Self,
-    ) -> RepositoryResult<BTreeMap<u64, crate::version::BaseVersion>> {
+    ) -> RepositoryResult<BTreeMap<Version, crate::version::BaseVersion>> {
         let branch_re = regex::Regex::new(&self.config.release_branch_pattern).expect(
             format!(
                 "potentially malformed branch pattern '{}'",
@@ -244,7 +330,7 @@ impl Repository {
             }
         };
 
-        let mut version_tree: BTreeMap<u64, crate::version::BaseVersion> = BTreeMap::new();
+        let mut version_tree: BTreeMap<Version, crate::version::BaseVersion> = BTreeMap::new();
         let branch_refs: Vec<&git::refs::GitRef> =
             refs.values().filter(|e| e.is_branch()).collect();
         let tag_refs: Vec<&git::refs::GitRef> = refs.values().filter(|e| e.is_tag()).collect();
@@ -262,10 +348,9 @@ impl Repository {
                     log::trace!("  not a match - skip.");
                     continue;
                 };
-                let verid = version.get_version_id();
-                if !version_tree.contains_key(&verid) {
+                if !version_tree.contains_key(&version) {
                     version_tree.insert(
-                        verid,
+                        version.clone(),
                         crate::version::BaseVersion {
                             version,
                             releases: BTreeMap::new(),
@@ -296,8 +381,7 @@ impl Repository {
                 };
 
                 let base_ver = version.get_base_version();
-                let base_ver_id = base_ver.get_version_id();
-                if !version_tree.contains_key(&base_ver_id) {
+                if !version_tree.contains_key(&base_ver) {
                     log::trace!(
                         "base version {} for {} not found - skip.",
                         base_ver,
@@ -306,12 +390,11 @@ impl Repository {
                     continue;
                 }
 
-                let base_version = version_tree.get_mut(&base_ver_id).unwrap();
+                let base_version = version_tree.get_mut(&base_ver).unwrap();
                 let release_ver = version.get_release_version();
-                let release_ver_id = release_ver.get_version_id();
-                if !base_version.releases.contains_key(&release_ver_id) {
+                if !base_version.releases.contains_key(&release_ver) {
                     base_version.releases.insert(
-                        release_ver_id,
+                        release_ver.clone(),
                         crate::version::ReleaseEntry {
                             release: release_ver.clone(),
                             versions: BTreeMap::new(),
@@ -320,12 +403,11 @@ impl Repository {
                     );
                 }
 
-                let version_entry_tree = base_version.releases.get_mut(&release_ver_id).unwrap();
-                let version_id = version.get_version_id();
-                if version.rc.is_none() {
+                let version_entry_tree = base_version.releases.get_mut(&release_ver).unwrap();
+                if !version.is_prerelease() {
                     version_entry_tree.is_complete = true;
                 }
-                version_entry_tree.versions.insert(version_id, version);
+                version_entry_tree.versions.insert(version.clone(), version);
             }
         }
 
@@ -339,7 +421,7 @@ impl Repository {
         self: &Self,
         refs: &Vec<&crate::git::refs::GitRef>,
         regex_pattern: &String,
-    ) -> RepositoryResult<BTreeMap<u64, Version>> {
+    ) -> RepositoryResult<BTreeMap<Version, Version>> {
         let regex = match regex::Regex::new(&regex_pattern) {
             Ok(r) => r,
             Err(e) => {
@@ -348,7 +430,7 @@ impl Repository {
             }
         };
 
-        let mut versions: BTreeMap<u64, Version> = BTreeMap::new();
+        let mut versions: BTreeMap<Version, Version> = BTreeMap::new();
         for entry in refs {
             log::trace!("get_versions_from_refs: handle '{}'", entry.name,);
             if let Some(m) = regex.captures(&entry.name) {
@@ -366,18 +448,17 @@ impl Repository {
                     log::trace!("  not a match - skip.");
                     continue;
                 };
-                let version_id = version.get_version_id();
                 log::trace!(
-                    "version id {} for ref {} ({})",
-                    version_id,
+                    "version {} for ref {} ({})",
+                    version,
                     entry.name,
                     match entry.has_remote {
                         true => "remote",
                         false => "local",
                     }
                 );
-                if !versions.contains_key(&version_id) {
-                    versions.insert(version_id, version);
+                if !versions.contains_key(&version) {
+                    versions.insert(version.clone(), version);
                 }
             }
         }
@@ -435,7 +516,7 @@ impl Repository {
 
     /// Obtain all versions, from tags, known to this repository.
     ///
-    pub fn get_versions(self: &Self) -> RepositoryResult<BTreeMap<u64, Version>> {
+    pub fn get_versions(self: &Self) -> RepositoryResult<BTreeMap<Version, Version>> {
         let refs = match self.get_git_refs() {
             Ok(v) => v,
             Err(err) => {
@@ -465,7 +546,7 @@ impl Repository {
 
     /// Obtain all release branches known to this repository, both local and remote.
     ///
-    pub fn get_release_branches(self: &Self) -> RepositoryResult<BTreeMap<u64, Version>> {
+    pub fn get_release_branches(self: &Self) -> RepositoryResult<BTreeMap<Version, Version>> {
         let refs = match self.get_git_refs() {
             Ok(v) => v,
             Err(err) => {
@@ -530,6 +611,14 @@ impl Repository {
             }
         };
         assert!(!dst.is_empty());
+        if self.is_dry_run() {
+            let (head_name, _) = git.get_default_branch();
+            self.dry_run_record(DryRunAction::Branch {
+                name: dst.clone(),
+                from: head_name,
+            });
+            return Ok(());
+        }
         match git.branch_from_default(&dst) {
             Ok(()) => {
                 log::info!("Success branching from default to '{}'!", dst);
@@ -566,6 +655,12 @@ impl Repository {
         let branch_str = self.version_to_str(&base_ver, false);
         let ref_entry = match heads.iter().find(|e| e.name == branch_str) {
             None => {
+                if let Some(plan) = &self.dry_run {
+                    if plan.has_branch(&self.name, &branch_str) {
+                        log::debug!("dry-run: branch '{}' would have been created", branch_str);
+                        return Ok(());
+                    }
+                }
                 errorln!("Unable to find branch '{}' local or remote", branch_str);
                 return Err(RepositoryError::UnknownBranchError);
             }
@@ -642,15 +737,25 @@ impl Repository {
             panic!("Expected patch version on relver '{}'", relver);
         };
         let tagver_str = format!("v{}.{}.{}", &relver.major, &relver.minor, &patchver);
-        let tag_msg = match tagver.rc {
+        let tag_msg = match tagver.rc() {
             Some(rc) => {
                 format!("release candidate {} for {}", rc, tagver_str)
             }
+            None if tagver.is_prerelease() => {
+                format!("pre-release {} for {}", tagver, tagver_str)
+            }
             None => {
                 format!("release {}", tagver_str)
             }
         };
 
+        if self.dry_run_record(DryRunAction::Tag {
+            name: tag_name.clone(),
+            target: branch_name.clone(),
+        }) {
+            return Ok((tag_name, String::from("dry-run")));
+        }
+
         // We use the 'git' command here because we have yet to find a library
         // that will allow us to do signed annotated tags. Also, we get the
         // additional benefit of having it dealing with the GPG key handling for us.
@@ -707,7 +812,10 @@ impl Repository {
 
     /// Obtain a given refspec's SHA1.
     ///
-    fn get_sha1_by_refspec(self: &Self, refspec: &String) -> RepositoryResult<(String, String)> {
+    pub fn get_sha1_by_refspec(
+        self: &Self,
+        refspec: &String,
+    ) -> RepositoryResult<(String, String)> {
         let git = match git::repo::GitRepo::open(&self.path) {
             Ok(r) => r,
             Err(()) => {
@@ -735,6 +843,11 @@ impl Repository {
     /// Push the given refspec to this repository's read-write remote.
     ///
     pub fn push(self: &Self, refspec: &String) -> RepositoryResult<()> {
+        if self.dry_run_record(DryRunAction::Push {
+            refspec: refspec.clone(),
+        }) {
+            return Ok(());
+        }
         let git = match git::repo::GitRepo::open(&self.path) {
             Ok(r) => r,
             Err(()) => {
@@ -800,6 +913,20 @@ impl Repository {
             if is_tag { "tags" } else { "heads" },
             name_spec
         );
+        if self.is_dry_run() {
+            let path = match git.get_git_repo().find_submodule(&name) {
+                Ok(s) => s.path().to_path_buf(),
+                Err(err) => {
+                    log::error!("Unable to find submodule '{}': {}", name, err);
+                    return Err(RepositoryError::SubmoduleHeadUpdateError);
+                }
+            };
+            self.dry_run_record(DryRunAction::Submodule {
+                name: name.clone(),
+                refname,
+            });
+            return Ok(Some(path));
+        }
         let path = match git.set_submodule_head(&name, &refname) {
             Ok(r) => match r {
                 Some(p) => {
@@ -823,6 +950,10 @@ impl Repository {
     /// Add paths in provided vector to this repository's index, for subsequent commit.
     ///
     pub fn stage_paths(self: &Self, paths: &Vec<PathBuf>) -> RepositoryResult<()> {
+        if self.is_dry_run() {
+            // nothing to record, staged paths are part of the subsequent commit.
+            return Ok(());
+        }
         let git = match git::repo::GitRepo::open(&self.path) {
             Ok(r) => r,
             Err(()) => {
@@ -851,6 +982,11 @@ impl Repository {
     }
 
     pub fn commit(self: &Self, commit_msg: &String, force_empty: bool) -> RepositoryResult<()> {
+        if self.dry_run_record(DryRunAction::Commit {
+            message: commit_msg.clone(),
+        }) {
+            return Ok(());
+        }
         let mut args = vec![
             "-C",
             self.path.to_str().unwrap(),
@@ -878,7 +1014,8 @@ impl Repository {
         }
     }
 
-    /// Commit a given release version, tagging it with the appropriate version.
+    /// Commit a given release version. Tagging the resulting commit is left to
+    /// the caller, via 'tag_release_branch()'.
     ///
     pub fn commit_release(
         self: &Self,
@@ -887,8 +1024,10 @@ impl Repository {
         force_empty: bool,
     ) -> RepositoryResult<()> {
         let relver_str = format!("v{}", relver);
-        let commit_msg = if let Some(rc) = &tagver.rc {
+        let commit_msg = if let Some(rc) = tagver.rc() {
             format!("release candidate {} for {}", rc, relver_str)
+        } else if tagver.is_prerelease() {
+            format!("pre-release {} for {}", tagver, relver_str)
         } else {
             format!("release {}", relver_str)
         };
@@ -899,19 +1038,307 @@ impl Repository {
             return Err(err);
         }
 
-        let tag_name = self.version_to_str(&tagver, true);
-        log::debug!("Tag release with '{}'", tag_name);
-        match self.tag_release_branch(&relver, &tagver) {
-            Ok(_) => {
-                log::debug!("Tagged release with '{}'", tag_name);
+        Ok(())
+    }
+
+    /// Find the commit for revision 'rev' (e.g., an abbreviated SHA1) in this
+    /// repository, returning its full SHA1 if it exists.
+    ///
+    pub fn find_commit(self: &Self, rev: &String) -> RepositoryResult<Option<String>> {
+        let git = match git::repo::GitRepo::open(&self.path) {
+            Ok(r) => r,
+            Err(()) => {
+                log::error!("Unable to open git repository at '{}'", self.path.display());
+                return Err(RepositoryError::UnableToOpenRepositoryError);
+            }
+        };
+        Ok(git.find_commit(&rev).map(|oid| oid.to_string()))
+    }
+
+    /// Check whether commit 'sha1' is already part of the release branch for
+    /// 'relver'.
+    ///
+    pub fn is_on_release_branch(
+        self: &Self,
+        relver: &Version,
+        sha1: &String,
+    ) -> RepositoryResult<bool> {
+        let git = match git::repo::GitRepo::open(&self.path) {
+            Ok(r) => r,
+            Err(()) => {
+                log::error!("Unable to open git repository at '{}'", self.path.display());
+                return Err(RepositoryError::UnableToOpenRepositoryError);
+            }
+        };
+        let oid = match git2::Oid::from_str(&sha1) {
+            Ok(v) => v,
+            Err(_) => return Err(RepositoryError::UnknownSHA1Error),
+        };
+        let branch_str = self.version_to_str(&relver.get_base_version(), false);
+        match git.is_ancestor(oid, &format!("refs/heads/{}", branch_str)) {
+            Ok(v) => Ok(v),
+            Err(()) => Err(RepositoryError::UnknownBranchError),
+        }
+    }
+
+    /// Fetch the commits of pull request 'number' from this repository's
+    /// read-only remote.
+    ///
+    pub fn fetch_pull_request(self: &Self, number: u64) -> RepositoryResult<()> {
+        let git = match git::repo::GitRepo::open(&self.path) {
+            Ok(r) => r,
+            Err(()) => {
+                log::error!("Unable to open git repository at '{}'", self.path.display());
+                return Err(RepositoryError::UnableToOpenRepositoryError);
+            }
+        };
+        let refspec = format!("+refs/pull/{}/head:refs/remotes/ro/pull/{}", number, number);
+        match git.fetch(&refspec) {
+            Ok(()) => Ok(()),
+            Err(()) => {
+                log::error!(
+                    "Unable to fetch pull request {} for '{}'",
+                    number,
+                    self.name
+                );
+                Err(RepositoryError::FetchingError)
+            }
+        }
+    }
+
+    /// Cherry-pick 'commits', in order, onto the currently checked out branch.
+    /// Should a commit fail to apply, the whole cherry-pick is aborted, leaving
+    /// the branch as it was, and the conflict is returned.
+    ///
+    pub fn cherry_pick(
+        self: &Self,
+        commits: &Vec<String>,
+    ) -> RepositoryResult<Option<CherryPickConflict>> {
+        if self.is_dry_run() {
+            for commit in commits {
+                self.dry_run_record(DryRunAction::CherryPick {
+                    commit: commit.clone(),
+                });
+            }
+            return Ok(None);
+        }
+
+        let path = self.path.to_str().unwrap();
+        let mut args = vec!["-C", path, "cherry-pick", "-x", "--gpg-sign", "--signoff"];
+        args.extend(commits.iter().map(|c| c.as_str()));
+
+        // We use the 'git' command for the same reasons we do when committing:
+        // commits must be signed.
+        let output = match std::process::Command::new("git").args(&args).output() {
+            Ok(v) => v,
+            Err(err) => {
+                log::error!("Unable to run 'git' command: {}", err);
+                return Err(RepositoryError::CherryPickError);
+            }
+        };
+        if output.status.success() {
+            return Ok(None);
+        }
+        log::debug!(
+            "cherry-pick on '{}' failed:\n{}",
+            self.name,
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let git_output = |args: &[&str]| -> Option<String> {
+            match std::process::Command::new("git")
+                .args(["-C", path])
+                .args(args)
+                .output()
+            {
+                Ok(o) if o.status.success() => Some(String::from_utf8_lossy(&o.stdout).into()),
+                _ => None,
+            }
+        };
+
+        let commit = match git_output(&["rev-parse", "--verify", "-q", "CHERRY_PICK_HEAD"]) {
+            Some(v) => v.trim().to_string(),
+            None => {
+                log::error!(
+                    "Unable to cherry-pick on '{}': {}",
+                    self.name,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+                return Err(RepositoryError::CherryPickError);
+            }
+        };
+        let paths = match git_output(&["diff", "--name-only", "--diff-filter=U"]) {
+            Some(v) => v.lines().map(String::from).collect(),
+            None => vec![],
+        };
+
+        if git_output(&["cherry-pick", "--abort"]).is_none() {
+            log::error!("Unable to abort cherry-pick on '{}'", self.name);
+            return Err(RepositoryError::CherryPickError);
+        }
+
+        Ok(Some(CherryPickConflict { commit, paths }))
+    }
+
+    /// Obtain the name of version 'ver''s tag.
+    ///
+    pub fn get_version_tag_name(self: &Self, ver: &Version) -> String {
+        self.version_to_str(&ver, true)
+    }
+
+    /// Obtain the refspec for version 'ver''s tag.
+    ///
+    pub fn get_version_tag_refspec(self: &Self, ver: &Version) -> String {
+        format!("refs/tags/{}", self.get_version_tag_name(&ver))
+    }
+
+    /// Obtain the refspec best representing version 'ver' on this repository:
+    /// its tag, if it has been released, its release branch, if it has been
+    /// started, or the default branch otherwise.
+    ///
+    pub fn get_version_refspec(self: &Self, ver: &Version) -> RepositoryResult<String> {
+        let git = match git::repo::GitRepo::open(&self.path) {
+            Ok(r) => r,
+            Err(()) => {
+                log::error!("Unable to open git repository at '{}'", self.path.display());
+                return Err(RepositoryError::UnableToOpenRepositoryError);
+            }
+        };
+        let branch_str = self.version_to_str(&ver.get_base_version(), false);
+        let candidates = vec![
+            self.get_version_tag_refspec(&ver),
+            format!("refs/heads/{}", branch_str),
+            format!("refs/remotes/ro/{}", branch_str),
+        ];
+        for refspec in candidates {
+            if git.find_commit(&refspec).is_some() {
+                return Ok(refspec);
+            }
+        }
+        let (head_name, _) = git.get_default_branch();
+        Ok(head_name)
+    }
+
+    /// Obtain the contents of the file at 'path' on 'refspec', if it exists.
+    ///
+    pub fn read_file(
+        self: &Self,
+        refspec: &String,
+        path: &PathBuf,
+    ) -> RepositoryResult<Option<String>> {
+        let git = match git::repo::GitRepo::open(&self.path) {
+            Ok(r) => r,
+            Err(()) => {
+                log::error!("Unable to open git repository at '{}'", self.path.display());
+                return Err(RepositoryError::UnableToOpenRepositoryError);
+            }
+        };
+        match git.read_file(&refspec, &path) {
+            Ok(v) => Ok(v.map(|c| String::from_utf8_lossy(&c).into())),
+            Err(()) => Err(RepositoryError::UnknownSHA1Error),
+        }
+    }
+
+    /// Obtain the commits on the first-parent history of 'refspec', newest
+    /// first, excluding those reachable from 'from', if provided.
+    ///
+    pub fn get_commits(
+        self: &Self,
+        from: Option<&String>,
+        refspec: &String,
+    ) -> RepositoryResult<Vec<git::repo::GitCommitInfo>> {
+        let git = match git::repo::GitRepo::open(&self.path) {
+            Ok(r) => r,
+            Err(()) => {
+                log::error!("Unable to open git repository at '{}'", self.path.display());
+                return Err(RepositoryError::UnableToOpenRepositoryError);
+            }
+        };
+        match git.log(from, &refspec) {
+            Ok(v) => Ok(v),
+            Err(()) => {
+                log::error!(
+                    "Unable to obtain commits for '{}' on '{}'",
+                    refspec,
+                    self.name
+                );
+                Err(RepositoryError::UnknownSHA1Error)
             }
+        }
+    }
+
+    /// Obtain the SHA1 of the commit at the HEAD of release branch for 'relver'.
+    ///
+    pub fn get_release_branch_head(self: &Self, relver: &Version) -> RepositoryResult<String> {
+        let branch_str = self.version_to_str(&relver.get_base_version(), false);
+        match self.get_sha1_by_refspec(&format!("refs/heads/{}", branch_str)) {
+            Ok((_, commit)) => Ok(commit),
             Err(err) => {
-                log::error!("Error tagging release with '{}': {}", tag_name, err);
-                return Err(err);
+                log::error!("Unable to obtain HEAD for branch '{}': {}", branch_str, err);
+                Err(err)
+            }
+        }
+    }
+
+    /// Delete local tag 'name'.
+    ///
+    pub fn delete_local_tag(self: &Self, name: &String) -> RepositoryResult<()> {
+        let git = match git::repo::GitRepo::open(&self.path) {
+            Ok(r) => r,
+            Err(()) => {
+                log::error!("Unable to open git repository at '{}'", self.path.display());
+                return Err(RepositoryError::UnableToOpenRepositoryError);
             }
         };
+        match git.delete_tag(&name) {
+            Ok(()) => Ok(()),
+            Err(()) => Err(RepositoryError::DeletingError),
+        }
+    }
 
-        Ok(())
+    /// Delete local branch 'name'.
+    ///
+    pub fn delete_local_branch(self: &Self, name: &String) -> RepositoryResult<()> {
+        let git = match git::repo::GitRepo::open(&self.path) {
+            Ok(r) => r,
+            Err(()) => {
+                log::error!("Unable to open git repository at '{}'", self.path.display());
+                return Err(RepositoryError::UnableToOpenRepositoryError);
+            }
+        };
+        match git.delete_branch(&name) {
+            Ok(()) => Ok(()),
+            Err(()) => Err(RepositoryError::DeletingError),
+        }
+    }
+
+    /// Delete reference 'refname' (e.g., 'refs/tags/foo') from this
+    /// repository's read-write remote.
+    ///
+    pub fn delete_remote_ref(self: &Self, refname: &String) -> RepositoryResult<()> {
+        match self.push(&format!(":{}", refname)) {
+            Ok(()) => Ok(()),
+            Err(err) => {
+                log::error!("Unable to delete remote ref '{}': {}", refname, err);
+                Err(RepositoryError::DeletingError)
+            }
+        }
+    }
+
+    /// Reset local branch 'name' to the commit with SHA1 'sha1'.
+    ///
+    pub fn reset_branch(self: &Self, name: &String, sha1: &String) -> RepositoryResult<()> {
+        let git = match git::repo::GitRepo::open(&self.path) {
+            Ok(r) => r,
+            Err(()) => {
+                log::error!("Unable to open git repository at '{}'", self.path.display());
+                return Err(RepositoryError::UnableToOpenRepositoryError);
+            }
+        };
+        match git.reset_branch(&name, &sha1) {
+            Ok(()) => Ok(()),
+            Err(()) => Err(RepositoryError::ResettingError),
+        }
     }
 
     /// Get how many commits ahead and behind a given version is compared to its
@@ -0,0 +1,172 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fmt::Display, path::PathBuf};
+
+use super::errors::{WorkspaceError, WorkspaceResult};
+
+/// Name of the environment variable the GitHub token is obtained from, by
+/// default.
+pub const DEFAULT_TOKEN_ENV: &str = "ARC_GITHUB_TOKEN";
+
+/// Where a secret token is obtained from. Only 'Plain' tokens are kept in the
+/// workspace's config; all others are obtained when needed.
+///
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WSTokenSource {
+    /// token kept in clear text in the config
+    Plain(String),
+    /// environment variable holding the token
+    Env(String),
+    /// file containing the token, which must only be accessible by its owner
+    File(PathBuf),
+    /// shell command whose output is the token (e.g., 'pass show github')
+    Command(String),
+}
+
+impl Default for WSTokenSource {
+    fn default() -> Self {
+        WSTokenSource::Env(DEFAULT_TOKEN_ENV.into())
+    }
+}
+
+impl Display for WSTokenSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WSTokenSource::Plain(_) => write!(f, "config"),
+            WSTokenSource::Env(var) => write!(f, "environment variable '{}'", var),
+            WSTokenSource::File(path) => write!(f, "file '{}'", path.display()),
+            WSTokenSource::Command(cmd) => write!(f, "command '{}'", cmd),
+        }
+    }
+}
+
+/// On-disk formats for a token. Workspaces created before token sources were
+/// supported keep the token itself.
+///
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum WSTokenSourceFormat {
+    Legacy(String),
+    Source(WSTokenSource),
+}
+
+/// Deserialize a token source, accepting a bare token as a 'Plain' source.
+///
+pub fn deserialize_token_source<'de, D>(deserializer: D) -> Result<WSTokenSource, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match serde::Deserialize::deserialize(deserializer)? {
+        WSTokenSourceFormat::Legacy(token) => Ok(WSTokenSource::Plain(token)),
+        WSTokenSourceFormat::Source(source) => Ok(source),
+    }
+}
+
+impl WSTokenSource {
+    /// Whether this source is sane, without obtaining the token.
+    ///
+    pub fn is_valid(self: &Self) -> bool {
+        match self {
+            WSTokenSource::Plain(v) | WSTokenSource::Env(v) | WSTokenSource::Command(v) => {
+                !v.is_empty()
+            }
+            WSTokenSource::File(path) => !path.as_os_str().is_empty(),
+        }
+    }
+
+    /// Obtain the token from this source.
+    ///
+    pub fn resolve(self: &Self) -> WorkspaceResult<String> {
+        let token = match self {
+            WSTokenSource::Plain(token) => token.clone(),
+            WSTokenSource::Env(var) => match std::env::var(&var) {
+                Ok(v) => v,
+                Err(err) => {
+                    log::error!("Unable to obtain token from '{}': {}", var, err);
+                    return Err(WorkspaceError::TokenError);
+                }
+            },
+            WSTokenSource::File(path) => read_token_file(&path)?,
+            WSTokenSource::Command(cmd) => run_token_command(&cmd)?,
+        };
+
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            log::error!("Empty token obtained from {}", self);
+            return Err(WorkspaceError::TokenError);
+        }
+        Ok(token)
+    }
+}
+
+/// Read a token from the file at 'path', ensuring it is not accessible by
+/// anyone other than its owner.
+///
+fn read_token_file(path: &PathBuf) -> WorkspaceResult<String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let meta = match std::fs::metadata(&path) {
+        Ok(m) => m,
+        Err(err) => {
+            log::error!("Unable to access token file '{}': {}", path.display(), err);
+            return Err(WorkspaceError::TokenError);
+        }
+    };
+    let mode = meta.permissions().mode();
+    if mode & 0o077 != 0 {
+        log::error!(
+            "Token file '{}' is accessible by others (mode {:o}), must be 0600 or stricter",
+            path.display(),
+            mode & 0o777
+        );
+        return Err(WorkspaceError::TokenError);
+    }
+
+    match std::fs::read_to_string(&path) {
+        Ok(v) => Ok(v),
+        Err(err) => {
+            log::error!("Unable to read token file '{}': {}", path.display(), err);
+            Err(WorkspaceError::TokenError)
+        }
+    }
+}
+
+/// Obtain a token from the output of shell command 'cmd'.
+///
+fn run_token_command(cmd: &String) -> WorkspaceResult<String> {
+    let output = match std::process::Command::new("sh")
+        .args(["-c", cmd.as_str()])
+        .stderr(std::process::Stdio::inherit())
+        .output()
+    {
+        Ok(v) => v,
+        Err(err) => {
+            log::error!("Unable to run token command '{}': {}", cmd, err);
+            return Err(WorkspaceError::TokenError);
+        }
+    };
+    if !output.status.success() {
+        log::error!("Token command '{}' failed: {}", cmd, output.status);
+        return Err(WorkspaceError::TokenError);
+    }
+    match String::from_utf8(output.stdout) {
+        Ok(v) => Ok(v),
+        Err(err) => {
+            log::error!("Token command '{}' output is not valid: {}", cmd, err);
+            Err(WorkspaceError::TokenError)
+        }
+    }
+}
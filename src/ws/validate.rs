@@ -0,0 +1,274 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use crate::{errorln, git::repo::GitRepo, infoln, successln, warnln};
+
+use super::{
+    config::{is_valid_github_token, WSConfig, WSGitRepoConfig, WSUserConfig},
+    errors::{WorkspaceError, WorkspaceResult},
+    repository::Repository,
+    token::WSTokenSource,
+    workspace::Workspace,
+};
+
+/// Validate the config of workspace 'ws'. Checks the config's values, whether
+/// each repository's tag and branch patterns and formats are consistent, whether
+/// each repository's remotes can be reached and match those of its clone,
+/// whether the signing key is available, and whether the GitHub token can be
+/// obtained.
+///
+pub fn validate(ws: &Workspace) -> WorkspaceResult<()> {
+    let cfg: &WSConfig = &ws.config;
+    let mut valid = true;
+
+    infoln!("Validating config values...");
+    if cfg.validate().is_err() {
+        valid = false;
+    }
+
+    infoln!("Validating repository patterns and formats...");
+    for repo in &cfg.git.repos {
+        if !check_repo_formats(&repo) {
+            valid = false;
+        }
+    }
+
+    infoln!("Validating repository remotes...");
+    for repo in &cfg.git.repos {
+        if !check_repo_remotes(&repo) {
+            valid = false;
+        }
+    }
+    for repo in ws.repos.as_vec() {
+        if !check_clone_remotes(&repo) {
+            valid = false;
+        }
+    }
+
+    infoln!("Validating signing key...");
+    if !check_signing_key(&cfg.user.signing_key) {
+        valid = false;
+    }
+
+    infoln!("Validating GitHub token...");
+    if !check_github_token(&cfg.user) {
+        valid = false;
+    }
+
+    if !valid {
+        return Err(WorkspaceError::InvalidConfigError);
+    }
+    successln!("Workspace config is valid.");
+    Ok(())
+}
+
+/// Check whether a format renders a version that is matched by the
+/// corresponding pattern. This is a helper function.
+///
+fn check_format(repo: &String, what: &str, pattern: &String, fmt: &String) -> bool {
+    let re = match regex::Regex::new(&pattern) {
+        Ok(v) => v,
+        Err(err) => {
+            errorln!("{}: invalid {} pattern '{}': {}", repo, what, pattern, err);
+            return false;
+        }
+    };
+
+    let mut hb = handlebars::Handlebars::new();
+    hb.set_strict_mode(true);
+    if let Err(err) = hb.register_template_string("version", &fmt) {
+        errorln!("{}: invalid {} format '{}': {}", repo, what, fmt, err);
+        return false;
+    }
+    let data = HashMap::from([("major", 1_u64), ("minor", 2_u64), ("patch", 3_u64)]);
+    let rendered = match hb.render("version", &data) {
+        Ok(v) => v,
+        Err(err) => {
+            errorln!(
+                "{}: unable to render {} format '{}': {}",
+                repo,
+                what,
+                fmt,
+                err
+            );
+            return false;
+        }
+    };
+
+    if !re.is_match(&rendered) {
+        errorln!(
+            "{}: {} format '{}' renders '{}', not matched by pattern '{}'",
+            repo,
+            what,
+            fmt,
+            rendered,
+            pattern
+        );
+        return false;
+    }
+    true
+}
+
+/// Check a repository's tag and branch patterns and formats.
+///
+fn check_repo_formats(repo: &WSGitRepoConfig) -> bool {
+    let values = &repo.values;
+    let mut valid = check_format(&repo.name, "tag", &values.tag_pattern, &values.tag_format);
+    valid &= check_format(
+        &repo.name,
+        "release branch",
+        &values.release_branch_pattern,
+        &values.release_branch_format,
+    );
+    match (&values.final_branch_pattern, &values.final_branch_format) {
+        (None, None) => {}
+        (Some(pattern), Some(fmt)) => {
+            valid &= check_format(&repo.name, "final branch", &pattern, &fmt);
+        }
+        _ => {
+            errorln!(
+                "{}: final branch pattern and format must be both set, or unset",
+                repo.name
+            );
+            valid = false;
+        }
+    };
+    valid
+}
+
+/// Check whether a repository's remotes can be reached. The read-write remote
+/// is checked with authentication, as required for pushing.
+///
+fn check_repo_remotes(repo: &WSGitRepoConfig) -> bool {
+    let mut valid = true;
+    for (url, with_auth) in [
+        (&repo.values.readonly, false),
+        (&repo.values.readwrite, true),
+    ] {
+        match GitRepo::check_remote_url(&url, with_auth) {
+            Ok(()) => {
+                log::debug!("{}: reached remote '{}'", repo.name, url);
+            }
+            Err(()) => {
+                errorln!("{}: unable to reach remote '{}'", repo.name, url);
+                valid = false;
+            }
+        };
+    }
+    valid
+}
+
+/// Check whether the remotes of a repository's clone in the workspace point at
+/// the configured URLs, as they may have been changed since it was cloned.
+///
+fn check_clone_remotes(repo: &Repository) -> bool {
+    if !repo.path.exists() {
+        log::debug!("{}: not cloned yet", repo.name);
+        return true;
+    }
+    let git = match GitRepo::open(&repo.path) {
+        Ok(v) => v,
+        Err(()) => {
+            errorln!(
+                "{}: unable to open clone at '{}'",
+                repo.name,
+                repo.path.display()
+            );
+            return false;
+        }
+    };
+    let mut valid = true;
+    for (name, url) in repo.get_remote_urls() {
+        match git.get_remote_url(&name) {
+            Ok(current) if &current == url => {}
+            Ok(current) => {
+                errorln!(
+                    "{}: remote '{}' is '{}', expected '{}'",
+                    repo.name,
+                    name,
+                    current,
+                    url
+                );
+                valid = false;
+            }
+            Err(()) => {
+                errorln!("{}: unable to obtain remote '{}'", repo.name, name);
+                valid = false;
+            }
+        };
+    }
+    if !valid {
+        infoln!(
+            "Use 'arc ws sync' to update the remotes of '{}'.",
+            repo.name
+        );
+    }
+    valid
+}
+
+/// Check whether the GitHub token can be obtained from its source, and looks
+/// like a GitHub token.
+///
+fn check_github_token(user: &WSUserConfig) -> bool {
+    if let WSTokenSource::Plain(_) = user.github_token {
+        warnln!("GitHub token is kept in clear text in the workspace config.");
+    }
+    let token = match user.get_github_token() {
+        Ok(v) => v,
+        Err(err) => {
+            errorln!(
+                "Unable to obtain GitHub token from {}: {}",
+                user.github_token,
+                err
+            );
+            return false;
+        }
+    };
+    if !is_valid_github_token(&token) {
+        errorln!(
+            "GitHub token from {} has the wrong format.",
+            user.github_token
+        );
+        return false;
+    }
+    true
+}
+
+/// Check whether the signing key is available to sign commits and tags.
+///
+fn check_signing_key(key: &String) -> bool {
+    if key.is_empty() {
+        return false;
+    }
+    match std::process::Command::new("gpg")
+        .args(["--list-secret-keys", key.as_str()])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+    {
+        Ok(status) => {
+            if !status.success() {
+                errorln!("Signing key '{}' not found.", key);
+                return false;
+            }
+            true
+        }
+        Err(err) => {
+            errorln!("Unable to run gpg to check signing key: {}", err);
+            false
+        }
+    }
+}
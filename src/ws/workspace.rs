@@ -16,13 +16,14 @@ use std::path::PathBuf;
 
 use crate::{infoln, ws::errors::WorkspaceError};
 
-use super::{config::WSConfig, errors::WorkspaceResult, repository::Repos};
+use super::{config::WSConfig, dryrun::DryRunPlan, errors::WorkspaceResult, repository::Repos};
 
 #[derive(Clone)]
 pub struct Workspace {
     path: PathBuf,
     pub config: WSConfig,
     pub repos: Repos,
+    pub dry_run: Option<DryRunPlan>,
 }
 
 impl Workspace {
@@ -58,9 +59,20 @@ impl Workspace {
             path: path.to_path_buf(),
             config: cfg,
             repos,
+            dry_run: None,
         })
     }
 
+    /// Put this workspace in dry-run mode. Actions that would modify
+    /// repositories, locally or remotely, are recorded instead of performed.
+    ///
+    pub fn set_dry_run(self: &mut Self) -> DryRunPlan {
+        let plan = DryRunPlan::new();
+        self.repos.set_dry_run(&plan);
+        self.dry_run = Some(plan.clone());
+        plan
+    }
+
     /// Obtain config directory for this workspace
     pub fn get_config_dir(self: &Self) -> PathBuf {
         self.path.clone().join(".arc")
@@ -0,0 +1,116 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! End-to-end tests for release announcements, rendered from templates.
+
+mod common;
+
+use common::Fixture;
+
+/// Release v0.16.0 on all upstream repositories, as created by the fixture.
+///
+fn release(fixture: &Fixture) {
+    for name in Fixture::repo_names() {
+        let tag = format!("{}0.16.0", Fixture::tag_prefix(name));
+        fixture.tag(name, &tag);
+    }
+}
+
+/// Reference to image 'repo' at v0.16.0 on the fixture's registry.
+///
+fn image_ref(fixture: &Fixture, repo: &str) -> String {
+    let host = fixture.forge.url().replace("http://", "");
+    format!("{}/{}:v0.16.0", host, repo)
+}
+
+#[test]
+fn announce_renders_default_templates() {
+    let fixture = Fixture::new();
+    release(&fixture);
+    fixture.init_workspace();
+
+    let output = fixture.arc(&["rel", "announce", "-v", "0.16.0", "--format", "github"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "v0.16.0\n\n\
+             ## Container images\n\n\
+             - `{}`\n\
+             - `{}`\n\n\
+             ## Helm Chart\n\n\
+             Version `0.16.0`, available at https://artifacthub.io/packages/helm/s3gw/s3gw/0.16.0\n",
+            image_ref(&fixture, "s3gw/s3gw"),
+            image_ref(&fixture, "s3gw/s3gw-ui"),
+        )
+    );
+
+    let outfile = fixture.path("announcement.txt");
+    let output = fixture.arc(&[
+        "rel",
+        "announce",
+        "-v",
+        "0.16.0",
+        "--outfile",
+        outfile.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let email = std::fs::read_to_string(&outfile).unwrap();
+    assert!(email.starts_with("Subject: [ANN] S3 Gateway v0.16.0 released\n"));
+    assert!(email.contains(&format!("\n    {}\n", image_ref(&fixture, "s3gw/s3gw"))));
+    assert!(email.contains("https://artifacthub.io/packages/helm/s3gw/s3gw/0.16.0"));
+}
+
+#[test]
+fn announce_renders_workspace_templates() {
+    let fixture = Fixture::new();
+    release(&fixture);
+    fixture.init_workspace();
+
+    let template = fixture
+        .workspace()
+        .join(".arc/templates/announce-slack.hbs");
+    std::fs::create_dir_all(template.parent().unwrap()).unwrap();
+    std::fs::write(
+        &template,
+        "v{{version}} chart {{chart_version}}\n\
+         {{#each images}}{{registry}}/{{name}}: {{reference}}\n{{/each}}\
+         {{notes}}\n",
+    )
+    .unwrap();
+    let notes = fixture.write_notes("notes.md", "Many things & more.\n");
+
+    let output = fixture.arc(&[
+        "rel",
+        "announce",
+        "-v",
+        "0.16.0",
+        "--format",
+        "slack",
+        "--changelog",
+        notes.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "v0.16.0 chart 0.16.0\n\
+             quay/s3gw: {}\n\
+             quay/s3gw-ui: {}\n\
+             Many things & more.\n",
+            image_ref(&fixture, "s3gw/s3gw"),
+            image_ref(&fixture, "s3gw/s3gw-ui"),
+        )
+    );
+}
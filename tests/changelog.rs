@@ -0,0 +1,131 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! End-to-end tests for release notes generated from the changes since the
+//! previous release.
+
+mod common;
+
+use common::Fixture;
+
+/// Release v0.16.0 on all upstream repositories, and then land a few changes
+/// on 's3gw' and 's3gw-ui'.
+///
+fn land_changes(fixture: &Fixture) {
+    for name in Fixture::repo_names() {
+        let tag = format!("{}0.16.0", Fixture::tag_prefix(name));
+        fixture.tag(name, &tag);
+    }
+
+    fixture.commit(
+        "s3gw",
+        "lock.txt",
+        "lock\n",
+        "feat(rgw): add object lock (#12)",
+    );
+    fixture.commit("s3gw", "crash.txt", "crash\n", "fix: crash on empty bucket");
+    fixture.commit("s3gw", "notes.txt", "notes\n", "Tidy up");
+    fixture.push("s3gw", "main");
+    fixture.commit("s3gw-ui", "page.txt", "page\n", "feat: bucket page (#3)");
+    fixture.push("s3gw-ui", "main");
+}
+
+#[test]
+fn changelog_groups_changes_by_commit_type() {
+    let fixture = Fixture::new();
+    land_changes(&fixture);
+    fixture.init_workspace();
+
+    let outfile = fixture.path("changelog.md");
+    let output = fixture.arc(&[
+        "rel",
+        "changelog",
+        "-v",
+        "0.17.0",
+        "-o",
+        outfile.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+
+    let s3gw_src = fixture.src_path("s3gw");
+    let short_sha1 = |rev: &str| {
+        let sha1 = fixture.git(&s3gw_src, &["rev-parse", "--short=7", rev]);
+        sha1.trim().to_string()
+    };
+    assert_eq!(
+        std::fs::read_to_string(&outfile).unwrap(),
+        format!(
+            "# Release Notes - v0.17.0\n\n\
+             ## Features\n\n\
+             - **rgw:** add object lock (s3gw#12)\n\
+             - bucket page (s3gw-ui#3)\n\n\
+             ## Bug Fixes\n\n\
+             - crash on empty bucket (s3gw@{})\n\n\
+             ## Other Changes\n\n\
+             - Tidy up (s3gw@{})\n",
+            short_sha1("HEAD~1"),
+            short_sha1("HEAD")
+        )
+    );
+}
+
+#[test]
+fn changelog_groups_changes_by_pull_request_labels() {
+    let mut fixture = Fixture::new();
+    land_changes(&fixture);
+    fixture.init_workspace();
+    // the repository's own token is used, rather than the user's.
+    fixture.arc(&[
+        "ws",
+        "set",
+        "git.repos.s3gw.github.token",
+        r#"{"plain": "ghp_repository"}"#,
+    ]);
+
+    let pull_request = fixture
+        .forge
+        .mock("GET", "/repos/aquarist-labs/s3gw/pulls/12")
+        .match_header("authorization", "Bearer ghp_repository")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"labels": [{"name": "area/rgw"}, {"name": "kind/bug"}]}"#)
+        .expect(1)
+        .create();
+
+    let output = fixture.arc(&["rel", "changelog", "-v", "0.17.0", "--group-by", "labels"]);
+    pull_request.assert();
+    assert!(output.status.success());
+    let changelog = String::from_utf8_lossy(&output.stdout);
+    let fixes = changelog.split("## Bug Fixes").nth(1).unwrap();
+    assert!(fixes.contains("- **rgw:** add object lock (s3gw#12)"));
+    assert!(fixes.contains("- crash on empty bucket"));
+    // 's3gw-ui' has no GitHub repository configured, keeping its commit type.
+    let features = changelog.split("## Features").nth(1).unwrap();
+    assert!(features.starts_with("\n\n- bucket page (s3gw-ui#3)\n\n"));
+}
+
+#[test]
+fn release_start_generates_release_notes() {
+    let fixture = Fixture::new();
+    land_changes(&fixture);
+    fixture.init_workspace();
+
+    fixture.arc(&["rel", "start", "--yes", "0.17.0"]);
+
+    let rc1 = fixture.tag_commit("s3gw", "v0.17.0-rc1");
+    let notes = fixture.file("s3gw", rc1, "docs/release-notes/s3gw-v0.17.0.md");
+    assert!(notes.starts_with("# Release Notes - v0.17.0\n"));
+    assert!(notes.contains("- **rgw:** add object lock (s3gw#12)"));
+    assert!(notes.contains("- bucket page (s3gw-ui#3)"));
+}
@@ -0,0 +1,548 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fixtures for end-to-end tests, driving the 'arc' binary against local bare
+//! repositories wired like the s3gw project's, and a local stand-in for the
+//! GitHub and Quay APIs.
+
+#![allow(dead_code)]
+
+use std::{
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use mockito::Matcher;
+
+/// Each repository in the fixture: name, workspace path, role, submodule path
+/// in the main repository, and tag prefix.
+const REPOS: [(&str, &str, &str, Option<&str>, &str); 4] = [
+    ("s3gw", "s3gw.git", "main", None, "v"),
+    ("s3gw-ui", "s3gw-ui.git", "component", Some("ui"), "s3gw-v"),
+    (
+        "s3gw-charts",
+        "charts.git",
+        "charts",
+        Some("charts"),
+        "s3gw-v",
+    ),
+    ("s3gw-ceph", "ceph.git", "component", Some("ceph"), "s3gw-v"),
+];
+
+const FAKE_GPG: &str = r#"#!/bin/sh
+# Stand-in for gpg, producing a bogus signature for whatever git asks.
+cat > /dev/null
+printf '\n[GNUPG:] SIG_CREATED D 1 8 00 0 0\n' >&2
+printf -- '-----BEGIN PGP SIGNATURE-----\n\nfake\n-----END PGP SIGNATURE-----\n'
+"#;
+
+const MKDOCS: &str = "site_name: s3gw
+nav:
+  - Home: index.md
+  - Release Notes:
+      - v0.16.0: release-notes/s3gw-v0.16.0.md
+";
+
+pub struct Fixture {
+    pub root: tempfile::TempDir,
+    pub forge: mockito::ServerGuard,
+}
+
+impl Fixture {
+    /// Create the upstream repositories, and the workspace's config pointing
+    /// at them. The workspace itself is created with 'init_workspace()'.
+    ///
+    pub fn new() -> Fixture {
+        let fixture = Fixture {
+            root: tempfile::tempdir().unwrap(),
+            forge: mockito::Server::new(),
+        };
+        fixture.setup_home();
+        for (name, _, _, submodule, _) in REPOS {
+            if submodule.is_some() {
+                fixture.create_upstream(name, |dir| {
+                    if name == "s3gw-charts" {
+                        write(&dir.join("charts/s3gw/Chart.yaml"), "version: 0.16.0\n");
+                    } else {
+                        write(&dir.join("README.md"), name);
+                    }
+                });
+            }
+        }
+        fixture.create_upstream("s3gw", |dir| {
+            write(&dir.join("mkdocs.yml"), MKDOCS);
+            write(&dir.join("docs/release-notes/s3gw-v0.16.0.md"), "v0.16.0");
+            for (name, _, _, submodule, _) in REPOS {
+                if let Some(path) = submodule {
+                    let url = fixture.upstream_path(name);
+                    fixture.git(&dir, &["submodule", "add", url.to_str().unwrap(), path]);
+                }
+            }
+        });
+        fixture.write_config();
+        fixture
+    }
+
+    pub fn path(&self, rel: &str) -> PathBuf {
+        self.root.path().join(rel)
+    }
+
+    pub fn workspace(&self) -> PathBuf {
+        self.path("ws")
+    }
+
+    pub fn upstream_path(&self, name: &str) -> PathBuf {
+        self.path(&format!("remotes/{}.git", name))
+    }
+
+    /// Path to the workspace's clone of repository 'name'.
+    ///
+    pub fn clone_path(&self, name: &str) -> PathBuf {
+        let (_, path, _, _, _) = REPOS.iter().find(|r| r.0 == name).unwrap();
+        self.workspace().join(path)
+    }
+
+    pub fn upstream(&self, name: &str) -> git2::Repository {
+        git2::Repository::open_bare(self.upstream_path(name)).unwrap()
+    }
+
+    /// Working copy for upstream repository 'name', with 'main' checked out.
+    ///
+    pub fn src_path(&self, name: &str) -> PathBuf {
+        self.path(&format!("src/{}", name))
+    }
+
+    /// Commit 'contents' to 'file' on the currently checked out branch of
+    /// upstream repository 'name''s working copy, returning the commit's SHA1.
+    /// The commit is not pushed.
+    ///
+    pub fn commit_file(self: &Self, name: &str, file: &str, contents: &str) -> String {
+        self.commit(name, file, contents, &format!("update {}", file))
+    }
+
+    /// Like 'commit_file()', with commit message 'message'.
+    ///
+    pub fn commit(self: &Self, name: &str, file: &str, contents: &str, message: &str) -> String {
+        let src = self.src_path(name);
+        write(&src.join(file), contents);
+        self.git(&src, &["add", file]);
+        self.git(&src, &["commit", "--quiet", "-m", message]);
+        self.git(&src, &["rev-parse", "HEAD"]).trim().to_string()
+    }
+
+    /// Push 'refspec' from upstream repository 'name''s working copy.
+    ///
+    pub fn push(self: &Self, name: &str, refspec: &str) {
+        let upstream = self.upstream_path(name);
+        self.git(
+            &self.src_path(name),
+            &[
+                "push",
+                "--quiet",
+                "--force",
+                upstream.to_str().unwrap(),
+                refspec,
+            ],
+        );
+    }
+
+    /// Tag the currently checked out commit of upstream repository 'name''s
+    /// working copy with 'tag', and push the tag.
+    ///
+    pub fn tag(self: &Self, name: &str, tag: &str) {
+        self.git(&self.src_path(name), &["tag", tag]);
+        self.push(name, &format!("refs/tags/{}", tag));
+    }
+
+    /// Tag prefix for repository 'name' (e.g., 'v' for 's3gw').
+    ///
+    pub fn tag_prefix(name: &str) -> &'static str {
+        REPOS.iter().find(|r| r.0 == name).unwrap().4
+    }
+
+    /// Names of all repositories in the fixture.
+    ///
+    pub fn repo_names() -> Vec<&'static str> {
+        REPOS.iter().map(|r| r.0).collect()
+    }
+
+    /// Submodules in the main repository, by path, and their repository name.
+    ///
+    pub fn submodules() -> Vec<(&'static str, &'static str)> {
+        REPOS
+            .iter()
+            .filter_map(|r| r.3.map(|path| (path, r.0)))
+            .collect()
+    }
+
+    /// Set up a home directory with a git config signing with a fake gpg, so
+    /// neither the user's git config nor keys are used.
+    ///
+    fn setup_home(self: &Self) {
+        let gpg = self.path("home/bin/gpg");
+        write(&gpg, FAKE_GPG);
+        std::fs::set_permissions(&gpg, std::fs::Permissions::from_mode(0o755)).unwrap();
+        write(
+            &self.path("home/.gitconfig"),
+            &format!(
+                "[user]\n\tname = Fixture\n\temail = fixture@example.com\n\
+                 [init]\n\tdefaultBranch = main\n\
+                 [gpg]\n\tprogram = {}\n\
+                 [protocol \"file\"]\n\tallow = always\n",
+                gpg.display()
+            ),
+        );
+    }
+
+    /// Create a bare upstream repository 'name', with a single commit on
+    /// 'main' whose contents are populated by 'populate'.
+    ///
+    fn create_upstream<F>(self: &Self, name: &str, populate: F)
+    where
+        F: FnOnce(&Path),
+    {
+        let src = self.src_path(name);
+        let upstream = self.upstream_path(name);
+        std::fs::create_dir_all(&src).unwrap();
+        self.git(&src, &["init", "--quiet"]);
+        populate(&src);
+        self.git(&src, &["add", "--all"]);
+        self.git(&src, &["commit", "--quiet", "-m", "initial commit"]);
+        self.git(
+            self.root.path(),
+            &["init", "--quiet", "--bare", upstream.to_str().unwrap()],
+        );
+        self.git(
+            &src,
+            &["push", "--quiet", upstream.to_str().unwrap(), "main"],
+        );
+    }
+
+    fn write_config(self: &Self) {
+        let repos: Vec<serde_json::Value> = REPOS
+            .iter()
+            .map(|(name, path, role, submodule, prefix)| {
+                let upstream = self.upstream_path(name);
+                let is_charts = *role == "charts";
+                serde_json::json!({
+                    "name": name,
+                    "role": role,
+                    "path": path,
+                    "submodule": submodule,
+                    "update_submodules": *role == "main",
+                    "github": if *role == "main" {
+                        serde_json::json!({
+                            "org": "aquarist-labs",
+                            "repo": "s3gw",
+                            "api_base": self.forge.url(),
+                        })
+                    } else {
+                        serde_json::Value::Null
+                    },
+                    "readonly": upstream,
+                    "readwrite": upstream,
+                    "tag_pattern": format!(r"^{}(\d+\.\d+\.\d+.*)$", prefix),
+                    "release_branch_pattern": r"^s3gw-v(\d+\.\d+)$",
+                    "final_branch_pattern": if is_charts { Some(r"^v(\d+\.\d+)$") } else { None },
+                    "tag_format": format!("{}{{{{major}}}}.{{{{minor}}}}.{{{{patch}}}}", prefix),
+                    "release_branch_format": "s3gw-v{{major}}.{{minor}}",
+                    "final_branch_format": if is_charts { Some("v{{major}}.{{minor}}") } else { None },
+                })
+            })
+            .collect();
+
+        let config = serde_json::json!({
+            "user": {
+                "name": "Release Manager",
+                "email": "release@example.com",
+                "signing_key": "FIXTUREKEY",
+                "github_token": { "plain": "ghp_fixture" },
+            },
+            "git": { "repos": repos },
+            "registries": [{
+                "name": "quay",
+                "kind": "quay",
+                "url": self.forge.url(),
+                "images": { "s3gw": "s3gw/s3gw", "s3gw-ui": "s3gw/s3gw-ui" },
+            }],
+        });
+        write(
+            &self.path("config.json"),
+            &serde_json::to_string_pretty(&config).unwrap(),
+        );
+    }
+
+    /// Run git with arguments 'args' in 'dir', panicking on failure.
+    ///
+    pub fn git(self: &Self, dir: &Path, args: &[&str]) -> String {
+        let output = self.command("git", dir).args(args).output().unwrap();
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into()
+    }
+
+    /// Run arc with arguments 'args' from within the workspace.
+    ///
+    pub fn arc(self: &Self, args: &[&str]) -> Output {
+        self.arc_in(&self.workspace(), args)
+    }
+
+    /// Run arc with arguments 'args' from within 'dir'.
+    ///
+    pub fn arc_in(self: &Self, dir: &Path, args: &[&str]) -> Output {
+        let output = self
+            .command(env!("CARGO_BIN_EXE_arc"), dir)
+            .args(args)
+            .output()
+            .unwrap();
+        println!(
+            "arc {:?}:\n{}\n{}",
+            args,
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        output
+    }
+
+    /// Create the workspace, from the fixture's config.
+    ///
+    pub fn init_workspace(self: &Self) {
+        let ws = self.workspace();
+        let config = self.path("config.json");
+        self.arc_in(
+            self.root.path(),
+            &[
+                "ws",
+                "init",
+                ws.to_str().unwrap(),
+                "--config",
+                config.to_str().unwrap(),
+            ],
+        );
+        assert!(ws.join(".arc/config.json").exists());
+    }
+
+    /// Write release notes file 'name', returning its path.
+    ///
+    pub fn write_notes(self: &Self, name: &str, contents: &str) -> PathBuf {
+        let path = self.path(name);
+        write(&path, contents);
+        path
+    }
+
+    /// Create the workspace, and start release v0.17.0, tagging its first
+    /// release candidate.
+    ///
+    pub fn start_release(self: &Self) {
+        self.init_workspace();
+        let notes = self.write_notes("notes.md", "# v0.17.0\n");
+        self.arc(&[
+            "rel",
+            "start",
+            "--yes",
+            "--notes",
+            notes.to_str().unwrap(),
+            "0.17.0",
+        ]);
+        self.tag_commit("s3gw", "v0.17.0-rc1");
+    }
+
+    fn command(self: &Self, program: &str, dir: &Path) -> Command {
+        let mut cmd = Command::new(program);
+        cmd.current_dir(dir)
+            .env("HOME", self.path("home"))
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env_remove("GIT_CONFIG_GLOBAL")
+            .env_remove("SSH_AUTH_SOCK")
+            .env_remove("ARC_GITHUB_TOKEN")
+            .env("ARC_DEBUG", "debug");
+        cmd
+    }
+
+    // ----
+    // mocked forge endpoints
+    // ----
+
+    /// Mock the workflow runs endpoint of repository 'repo', for tag or branch
+    /// 'tag', with runs 'runs', served 'hits' times if provided. Mocks are
+    /// served in the order they are created, until their hits are exhausted.
+    ///
+    pub fn mock_runs(
+        self: &mut Self,
+        repo: &str,
+        tag: &str,
+        runs: Vec<serde_json::Value>,
+        hits: Option<usize>,
+    ) -> mockito::Mock {
+        let body = serde_json::json!({
+            "total_count": runs.len(),
+            "workflow_runs": runs,
+        });
+        let mock = self
+            .forge
+            .mock(
+                "GET",
+                format!("/repos/aquarist-labs/{}/actions/runs", repo).as_str(),
+            )
+            .match_query(Matcher::UrlEncoded("branch".into(), tag.into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body.to_string());
+        match hits {
+            Some(n) => mock.expect(n).create(),
+            None => mock.create(),
+        }
+    }
+
+    /// Mock the workflow runs endpoint with a single "Release S3GW" run for
+    /// 'v0.17.0-rc1', at attempt 'attempt', with 'status' and 'conclusion',
+    /// served 'hits' times if provided.
+    ///
+    pub fn mock_run(
+        self: &mut Self,
+        attempt: u64,
+        status: &str,
+        conclusion: Option<&str>,
+        hits: Option<usize>,
+    ) -> mockito::Mock {
+        let tag = "v0.17.0-rc1";
+        let run = workflow_run(42, "Release S3GW", tag, status, conclusion, attempt);
+        self.mock_runs("s3gw", tag, vec![run], hits)
+    }
+
+    /// Mock the workflow runs endpoint, reporting a successful release
+    /// workflow run for whichever tag or branch is being asked for.
+    ///
+    pub fn mock_successful_runs(self: &mut Self) -> mockito::Mock {
+        self.forge
+            .mock("GET", "/repos/aquarist-labs/s3gw/actions/runs")
+            .match_query(Matcher::Regex("branch=".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body_from_request(|request| {
+                let query = request.path_and_query();
+                let tag = query
+                    .split("branch=")
+                    .nth(1)
+                    .unwrap_or_default()
+                    .split('&')
+                    .next()
+                    .unwrap_or_default();
+                let run = workflow_run(1, "Release S3GW", tag, "completed", Some("success"), 1);
+                serde_json::json!({
+                    "total_count": 1,
+                    "workflow_runs": [run],
+                })
+                .to_string()
+                .into_bytes()
+            })
+            .create()
+    }
+
+    // ----
+    // assertions on upstream repositories
+    // ----
+
+    /// Commit tag 'tag' points to on repository 'name'.
+    ///
+    pub fn tag_commit(self: &Self, name: &str, tag: &str) -> git2::Oid {
+        let repo = self.upstream(name);
+        let reference = repo
+            .find_reference(&format!("refs/tags/{}", tag))
+            .unwrap_or_else(|_| panic!("tag '{}' not found on '{}'", tag, name));
+        let id = reference.peel_to_commit().unwrap().id();
+        id
+    }
+
+    /// Commit at the head of branch 'branch' on repository 'name'.
+    ///
+    pub fn branch_commit(self: &Self, name: &str, branch: &str) -> git2::Oid {
+        let repo = self.upstream(name);
+        let reference = repo
+            .find_reference(&format!("refs/heads/{}", branch))
+            .unwrap_or_else(|_| panic!("branch '{}' not found on '{}'", branch, name));
+        let id = reference.peel_to_commit().unwrap().id();
+        id
+    }
+
+    /// Branches on repository 'name' starting with 'prefix'.
+    ///
+    pub fn branches(self: &Self, name: &str, prefix: &str) -> Vec<String> {
+        let repo = self.upstream(name);
+        let branches = repo.branches(Some(git2::BranchType::Local)).unwrap();
+        branches
+            .map(|b| b.unwrap().0.name().unwrap().unwrap().to_string())
+            .filter(|b| b.starts_with(prefix))
+            .collect()
+    }
+
+    /// Tree entry at 'path' for commit 'commit' on repository 'name'.
+    ///
+    pub fn entry(self: &Self, name: &str, commit: git2::Oid, path: &str) -> (git2::Oid, i32) {
+        let repo = self.upstream(name);
+        let tree = repo.find_commit(commit).unwrap().tree().unwrap();
+        let entry = tree
+            .get_path(Path::new(path))
+            .unwrap_or_else(|_| panic!("'{}' not found on '{}' at {}", path, name, commit));
+        (entry.id(), entry.filemode())
+    }
+
+    /// Contents of the file at 'path' for commit 'commit' on repository 'name'.
+    ///
+    pub fn file(self: &Self, name: &str, commit: git2::Oid, path: &str) -> String {
+        let (id, _) = self.entry(name, commit, path);
+        let repo = self.upstream(name);
+        let blob = repo.find_blob(id).unwrap();
+        String::from_utf8_lossy(blob.content()).into()
+    }
+}
+
+/// A run 'id' of workflow 'name' for tag or branch 'tag', at attempt 'attempt',
+/// with 'status' and 'conclusion', as reported by GitHub's workflow runs
+/// endpoint.
+///
+pub fn workflow_run(
+    id: u64,
+    name: &str,
+    tag: &str,
+    status: &str,
+    conclusion: Option<&str>,
+    attempt: u64,
+) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "name": name,
+        "head_branch": tag,
+        "head_sha": "0000000000000000000000000000000000000000",
+        "status": status,
+        "conclusion": conclusion,
+        "display_title": format!("{} {}", name, tag),
+        "created_at": "2023-10-01T00:00:00Z",
+        "updated_at": "2023-10-01T00:10:00Z",
+        "run_started_at": "2023-10-01T00:00:00Z",
+        "run_attempt": attempt,
+        "url": format!("https://example.com/api/runs/{}", id),
+        "html_url": format!("https://example.com/runs/{}", id),
+    })
+}
+
+fn write(path: &Path, contents: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, contents).unwrap();
+}
@@ -0,0 +1,344 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! End-to-end tests for repositories hosted on forges other than GitHub.
+
+mod common;
+
+use common::Fixture;
+use mockito::Matcher;
+
+/// Host the 's3gw' repository on a forge of kind 'kind', with its API at
+/// 'api_path' on the fixture's mock server, authenticating with 'token'.
+///
+fn set_forge(fixture: &Fixture, kind: &str, api_path: &str, token: &str) {
+    let github = serde_json::json!({
+        "kind": kind,
+        "org": "aquarist-labs",
+        "repo": "s3gw",
+        "api_base": format!("{}{}", fixture.forge.url(), api_path),
+        "token": { "plain": token },
+    });
+    fixture.arc(&["ws", "set", "git.repos.s3gw.github", &github.to_string()]);
+}
+
+#[test]
+fn publish_creates_gitlab_release() {
+    let mut fixture = Fixture::new();
+    fixture.tag("s3gw", "v0.16.0");
+    fixture.init_workspace();
+    set_forge(&fixture, "gitlab", "/api/v4", "glpat-fixture");
+
+    let lookup = fixture
+        .forge
+        .mock(
+            "GET",
+            "/api/v4/projects/aquarist-labs%2Fs3gw/releases/v0.16.0",
+        )
+        .with_status(404)
+        .expect(1)
+        .create();
+    let release = fixture
+        .forge
+        .mock("POST", "/api/v4/projects/aquarist-labs%2Fs3gw/releases")
+        .match_header("authorization", "Bearer glpat-fixture")
+        .match_body(Matcher::PartialJson(serde_json::json!({
+            "tag_name": "v0.16.0",
+            "name": "v0.16.0",
+            "description": "v0.16.0",
+        })))
+        .with_status(201)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"tag_name": "v0.16.0", "name": "v0.16.0", "description": "v0.16.0",
+                "_links": {"self": "https://example.com/-/releases/v0.16.0"}}"#,
+        )
+        .expect(1)
+        .create();
+
+    let output = fixture.arc(&["rel", "publish", "-v", "0.16.0"]);
+    lookup.assert();
+    release.assert();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Published GitLab release for 'v0.16.0'"));
+}
+
+#[test]
+fn publish_updates_gitea_release() {
+    let mut fixture = Fixture::new();
+    fixture.tag("s3gw", "v0.16.0-rc1");
+    fixture.init_workspace();
+    set_forge(&fixture, "gitea", "/api/v1", "gitea-fixture");
+
+    let _lookup = fixture
+        .forge
+        .mock(
+            "GET",
+            "/api/v1/repos/aquarist-labs/s3gw/releases/tags/v0.16.0-rc1",
+        )
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"id": 3, "html_url": "https://example.com/releases/3",
+                "name": "v0.16.0-rc1", "body": "outdated", "prerelease": true}"#,
+        )
+        .create();
+    let update = fixture
+        .forge
+        .mock("PATCH", "/api/v1/repos/aquarist-labs/s3gw/releases/3")
+        .match_header("authorization", "Bearer gitea-fixture")
+        .match_body(Matcher::PartialJson(serde_json::json!({
+            "body": "v0.16.0",
+            "prerelease": true,
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"id": 3, "html_url": "https://example.com/releases/3",
+                "name": "v0.16.0-rc1", "body": "v0.16.0", "prerelease": true}"#,
+        )
+        .expect(1)
+        .create();
+
+    fixture.arc(&["rel", "publish", "-v", "0.16.0-rc1"]);
+    update.assert();
+}
+
+#[test]
+fn status_reports_gitlab_pipelines() {
+    let mut fixture = Fixture::new();
+    fixture.init_workspace();
+    set_forge(&fixture, "gitlab", "/api/v4", "glpat-fixture");
+    let notes = fixture.write_notes("notes.md", "# v0.17.0\n");
+    fixture.arc(&[
+        "rel",
+        "start",
+        "--yes",
+        "--notes",
+        notes.to_str().unwrap(),
+        "0.17.0",
+    ]);
+
+    let _registry = fixture
+        .forge
+        .mock("GET", Matcher::Regex(r"^/api/v1/repository/s3gw/".into()))
+        .match_query(Matcher::Any)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"tags": {}}"#)
+        .create();
+    let pipelines = fixture
+        .forge
+        .mock("GET", "/api/v4/projects/aquarist-labs%2Fs3gw/pipelines")
+        .match_query(Matcher::UrlEncoded("ref".into(), "v0.17.0-rc1".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            serde_json::json!([{
+                "id": 9,
+                "name": "Other",
+                "ref": "v0.17.0-rc1",
+                "status": "success",
+                "web_url": "https://example.com/-/pipelines/9",
+                "created_at": "2023-10-01T00:00:00Z",
+                "updated_at": "2023-10-01T00:10:00Z",
+            }, {
+                "id": 8,
+                "name": "Release S3GW",
+                "ref": "v0.17.0-rc1",
+                "status": "failed",
+                "web_url": "https://example.com/-/pipelines/8",
+                "created_at": "2023-10-01T00:00:00Z",
+                "updated_at": "2023-10-01T00:10:00Z",
+            }])
+            .to_string(),
+        )
+        .create();
+
+    let output = fixture.arc(&["rel", "status", "-o", "json"]);
+    pipelines.assert();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let workflows = report["releases"][0]["workflows"].as_array().unwrap();
+    assert_eq!(workflows.len(), 1);
+    assert_eq!(workflows[0]["id"], 8);
+    assert_eq!(workflows[0]["status"], "completed");
+    assert_eq!(workflows[0]["success"], false);
+    assert_eq!(workflows[0]["url"], "https://example.com/-/pipelines/8");
+}
+
+#[test]
+fn status_reports_gitlab_pipelines_without_user_token() {
+    let mut fixture = Fixture::new();
+    fixture.init_workspace();
+    set_forge(&fixture, "gitlab", "/api/v4", "glpat-fixture");
+    let notes = fixture.write_notes("notes.md", "# v0.17.0\n");
+    fixture.arc(&[
+        "rel",
+        "start",
+        "--yes",
+        "--notes",
+        notes.to_str().unwrap(),
+        "0.17.0",
+    ]);
+    // the repository's own token suffices, with no GitHub token at hand.
+    fixture.arc(&[
+        "ws",
+        "set",
+        "user.github_token",
+        r#"{"env": "ARC_GITHUB_TOKEN"}"#,
+    ]);
+
+    let _registry = fixture
+        .forge
+        .mock("GET", Matcher::Regex(r"^/api/v1/repository/s3gw/".into()))
+        .match_query(Matcher::Any)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"tags": {}}"#)
+        .create();
+    let pipelines = fixture
+        .forge
+        .mock("GET", "/api/v4/projects/aquarist-labs%2Fs3gw/pipelines")
+        .match_query(Matcher::UrlEncoded("ref".into(), "v0.17.0-rc1".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            serde_json::json!([{
+                "id": 9,
+                "name": "Other",
+                "ref": "v0.17.0-rc1",
+                "status": "success",
+                "web_url": "https://example.com/-/pipelines/9",
+                "created_at": "2023-10-01T00:00:00Z",
+                "updated_at": "2023-10-01T00:10:00Z",
+            }, {
+                "id": 8,
+                "name": "Release S3GW",
+                "ref": "v0.17.0-rc1",
+                "status": "failed",
+                "web_url": "https://example.com/-/pipelines/8",
+                "created_at": "2023-10-01T00:00:00Z",
+                "updated_at": "2023-10-01T00:10:00Z",
+            }])
+            .to_string(),
+        )
+        .create();
+
+    let output = fixture.arc(&["rel", "status", "-o", "json"]);
+    pipelines.assert();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let workflows = report["releases"][0]["workflows"].as_array().unwrap();
+    assert_eq!(workflows.len(), 1);
+    assert_eq!(workflows[0]["id"], 8);
+    assert_eq!(workflows[0]["status"], "completed");
+    assert_eq!(workflows[0]["success"], false);
+    assert_eq!(workflows[0]["url"], "https://example.com/-/pipelines/8");
+}
+
+#[test]
+fn gitlab_workflows_must_be_named() {
+    let fixture = Fixture::new();
+    fixture.init_workspace();
+    set_forge(&fixture, "gitlab", "/api/v4", "glpat-fixture");
+
+    let output = fixture.arc(&[
+        "ws",
+        "set",
+        "git.repos.s3gw.github.workflows",
+        r#"[{"path": ".gitlab-ci.yml"}]"#,
+    ]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(
+        "s3gw: GitLab pipelines can only be tracked by name, not by workflow at '.gitlab-ci.yml'."
+    ));
+
+    let output = fixture.arc(&["ws", "show"]);
+    let config: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let repo = config["git"]["repos"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["name"] == "s3gw")
+        .unwrap();
+    // the config is left as it was.
+    assert_eq!(repo["github"]["workflows"], serde_json::json!([]));
+}
+
+#[test]
+fn status_retries_and_paginates_gitlab_pipelines() {
+    let mut fixture = Fixture::new();
+    fixture.start_release();
+    set_forge(&fixture, "gitlab", "/api/v4", "glpat-fixture");
+
+    let _registry = fixture
+        .forge
+        .mock("GET", Matcher::Regex(r"^/api/v1/repository/s3gw/".into()))
+        .match_query(Matcher::Any)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"tags": {}}"#)
+        .create();
+    let pipeline = |id: u64, name: &str| {
+        serde_json::json!({
+            "id": id,
+            "name": name,
+            "ref": "v0.17.0-rc1",
+            "status": "success",
+            "web_url": format!("https://example.com/-/pipelines/{}", id),
+            "created_at": "2023-10-01T00:00:00Z",
+            "updated_at": "2023-10-01T00:10:00Z",
+        })
+    };
+    let page2 = fixture
+        .forge
+        .mock("GET", "/api/v4/projects/aquarist-labs%2Fs3gw/pipelines")
+        .match_query(Matcher::UrlEncoded("page".into(), "2".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("x-next-page", "")
+        .with_body(serde_json::json!([pipeline(8, "Release S3GW")]).to_string())
+        .expect(1)
+        .create();
+    // a transient server error before page 1
+    let unavailable = fixture
+        .forge
+        .mock("GET", "/api/v4/projects/aquarist-labs%2Fs3gw/pipelines")
+        .match_query(Matcher::Any)
+        .with_status(502)
+        .expect(1)
+        .create();
+    let page1 = fixture
+        .forge
+        .mock("GET", "/api/v4/projects/aquarist-labs%2Fs3gw/pipelines")
+        .match_query(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("ref".into(), "v0.17.0-rc1".into()),
+            Matcher::UrlEncoded("per_page".into(), "100".into()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("x-next-page", "2")
+        .with_body(serde_json::json!([pipeline(9, "Other")]).to_string())
+        .expect(1)
+        .create();
+
+    let output = fixture.arc(&["rel", "status", "-o", "json"]);
+    unavailable.assert();
+    page1.assert();
+    page2.assert();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let workflows = report["releases"][0]["workflows"].as_array().unwrap();
+    assert_eq!(workflows.len(), 1);
+    assert_eq!(workflows[0]["id"], 8);
+    assert_eq!(workflows[0]["success"], true);
+}
@@ -0,0 +1,172 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! End-to-end tests for hotfix releases, cherry-picking commits onto existing
+//! release branches.
+
+mod common;
+
+use common::Fixture;
+
+/// Cut release branch 's3gw-v0.17' on all upstream repositories, from 'main'.
+///
+fn cut_release_branches(fixture: &Fixture) {
+    for name in Fixture::repo_names() {
+        fixture.push(name, "main:refs/heads/s3gw-v0.17");
+    }
+}
+
+/// Whether commit 'commit' on repository 'name' has been cherry-picked from
+/// 'origin'.
+///
+fn is_cherry_pick_of(fixture: &Fixture, name: &str, commit: git2::Oid, origin: &str) -> bool {
+    let repo = fixture.upstream(name);
+    let commit = repo.find_commit(commit).unwrap();
+    commit
+        .message()
+        .unwrap()
+        .contains(&format!("(cherry picked from commit {})", origin))
+}
+
+#[test]
+fn hotfix_cherry_picks_commits_and_pull_requests() {
+    let mut fixture = Fixture::new();
+    fixture.init_workspace();
+    cut_release_branches(&fixture);
+
+    // a fix on 's3gw-ui', and a pull request on 's3gw', both on 'main' only.
+    let ui_fix = fixture.commit_file("s3gw-ui", "fix.txt", "ui fix\n");
+    fixture.push("s3gw-ui", "main");
+
+    let s3gw_src = fixture.src_path("s3gw");
+    fixture.git(&s3gw_src, &["checkout", "--quiet", "-b", "fix"]);
+    let pr_first = fixture.commit_file("s3gw", "fix-1.txt", "first\n");
+    let pr_second = fixture.commit_file("s3gw", "fix-2.txt", "second\n");
+    fixture.push("s3gw", "fix:refs/pull/7/head");
+
+    let pr_commits = fixture
+        .forge
+        .mock("GET", "/repos/aquarist-labs/s3gw/pulls/7/commits")
+        .match_query(mockito::Matcher::Any)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(serde_json::json!([{ "sha": pr_first }, { "sha": pr_second }]).to_string())
+        .expect(1)
+        .create();
+
+    let notes = fixture.write_notes("notes.md", "# v0.17.1\n");
+    fixture.arc(&[
+        "rel",
+        "hotfix",
+        "--notes",
+        notes.to_str().unwrap(),
+        "--pick",
+        &ui_fix[..10],
+        "--pr",
+        "7",
+        "0.17.1",
+    ]);
+    pr_commits.assert();
+
+    let ui_rc1 = fixture.tag_commit("s3gw-ui", "s3gw-v0.17.1-rc1");
+    assert_eq!(fixture.branch_commit("s3gw-ui", "s3gw-v0.17"), ui_rc1);
+    assert!(is_cherry_pick_of(&fixture, "s3gw-ui", ui_rc1, &ui_fix));
+    assert_eq!(fixture.file("s3gw-ui", ui_rc1, "fix.txt"), "ui fix\n");
+
+    // repositories without fixes are tagged nonetheless.
+    let charts_rc1 = fixture.tag_commit("s3gw-charts", "s3gw-v0.17.1-rc1");
+    assert_eq!(charts_rc1, fixture.branch_commit("s3gw-charts", "main"));
+
+    let s3gw_rc1 = fixture.tag_commit("s3gw", "v0.17.1-rc1");
+    assert_eq!(fixture.file("s3gw", s3gw_rc1, "fix-1.txt"), "first\n");
+    assert_eq!(fixture.file("s3gw", s3gw_rc1, "fix-2.txt"), "second\n");
+    assert_eq!(
+        fixture.file("s3gw", s3gw_rc1, "docs/release-notes/s3gw-v0.17.1.md"),
+        "# v0.17.1\n"
+    );
+    let (ui_gitlink, _) = fixture.entry("s3gw", s3gw_rc1, "ui");
+    assert_eq!(ui_gitlink, ui_rc1);
+
+    // the release candidate commit follows both cherry-picks.
+    let repo = fixture.upstream("s3gw");
+    let rc1_commit = repo.find_commit(s3gw_rc1).unwrap();
+    let second = rc1_commit.parent(0).unwrap();
+    let first = second.parent(0).unwrap();
+    assert!(is_cherry_pick_of(&fixture, "s3gw", second.id(), &pr_second));
+    assert!(is_cherry_pick_of(&fixture, "s3gw", first.id(), &pr_first));
+}
+
+#[test]
+fn hotfix_reports_conflicts_and_leaves_branches_untouched() {
+    let fixture = Fixture::new();
+    fixture.init_workspace();
+    cut_release_branches(&fixture);
+
+    // 'README.md' diverges between 'main' and the release branch.
+    let ui_src = fixture.src_path("s3gw-ui");
+    fixture.git(&ui_src, &["checkout", "--quiet", "-b", "s3gw-v0.17"]);
+    fixture.commit_file("s3gw-ui", "README.md", "release branch\n");
+    fixture.push("s3gw-ui", "s3gw-v0.17");
+    fixture.git(&ui_src, &["checkout", "--quiet", "main"]);
+    let conflicting = fixture.commit_file("s3gw-ui", "README.md", "main\n");
+    let clean = fixture.commit_file("s3gw-ceph", "fix.txt", "fix\n");
+    fixture.push("s3gw-ui", "main");
+    fixture.push("s3gw-ceph", "main");
+
+    let before: Vec<git2::Oid> = Fixture::repo_names()
+        .iter()
+        .map(|name| fixture.branch_commit(name, "s3gw-v0.17"))
+        .collect();
+
+    let notes = fixture.write_notes("notes.md", "# v0.17.1\n");
+    let output = fixture.arc(&[
+        "rel",
+        "hotfix",
+        "--notes",
+        notes.to_str().unwrap(),
+        "--pick",
+        &clean,
+        "--pick",
+        &format!("s3gw-ui:{}", conflicting),
+        "0.17.1",
+    ]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!("s3gw-ui: commit {} conflicts", conflicting)));
+    assert!(stdout.contains("README.md"));
+
+    // nothing has been tagged nor pushed, and the workspace is not bound to
+    // the hotfix release.
+    for (name, head) in Fixture::repo_names().iter().zip(before) {
+        assert_eq!(fixture.branch_commit(name, "s3gw-v0.17"), head);
+        let prefix = format!("{}0.17.1", Fixture::tag_prefix(name));
+        let repo = fixture.upstream(name);
+        assert!(repo
+            .tag_names(Some(&format!("{}*", prefix)))
+            .unwrap()
+            .is_empty());
+    }
+    let arc_dir = fixture.workspace().join(".arc");
+    assert!(!arc_dir.join("release.json").exists());
+    assert!(!arc_dir.join("journal.json").exists());
+
+    // the clean cherry-pick on 's3gw-ceph' has been rolled back locally.
+    let ceph = git2::Repository::open(fixture.clone_path("s3gw-ceph")).unwrap();
+    let local_head = ceph
+        .find_reference("refs/heads/s3gw-v0.17")
+        .unwrap()
+        .peel_to_commit()
+        .unwrap()
+        .id();
+    assert_eq!(local_head, fixture.branch_commit("s3gw-ceph", "s3gw-v0.17"));
+}
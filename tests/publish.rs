@@ -0,0 +1,131 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! End-to-end tests for publishing GitHub releases for a release's tags.
+
+mod common;
+
+use common::Fixture;
+use mockito::Matcher;
+
+#[test]
+fn publish_creates_releases_and_pre_releases() {
+    let mut fixture = Fixture::new();
+    fixture.tag("s3gw", "v0.16.0-rc1");
+    fixture.tag("s3gw", "v0.16.0");
+    fixture.init_workspace();
+
+    let lookup = fixture
+        .forge
+        .mock(
+            "GET",
+            Matcher::Regex(r"^/repos/aquarist-labs/s3gw/releases/tags/v0\.16\.0(-rc1)?$".into()),
+        )
+        .with_status(404)
+        .expect(2)
+        .create();
+    let pre_release = fixture
+        .forge
+        .mock("POST", "/repos/aquarist-labs/s3gw/releases")
+        .match_header("authorization", "Bearer ghp_fixture")
+        .match_body(Matcher::PartialJson(serde_json::json!({
+            "tag_name": "v0.16.0-rc1",
+            "name": "v0.16.0-rc1",
+            "body": "v0.16.0",
+            "prerelease": true,
+        })))
+        .with_status(201)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"id": 1, "html_url": "https://example.com/releases/1",
+                "name": "v0.16.0-rc1", "body": "v0.16.0", "prerelease": true}"#,
+        )
+        .expect(1)
+        .create();
+    let final_release = fixture
+        .forge
+        .mock("POST", "/repos/aquarist-labs/s3gw/releases")
+        .match_body(Matcher::PartialJson(serde_json::json!({
+            "tag_name": "v0.16.0",
+            "prerelease": false,
+        })))
+        .with_status(201)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"id": 2, "html_url": "https://example.com/releases/2",
+                "name": "v0.16.0", "body": "v0.16.0", "prerelease": false}"#,
+        )
+        .expect(1)
+        .create();
+
+    for version in ["0.16.0-rc1", "0.16.0"] {
+        let output = fixture.arc(&["rel", "publish", "-v", version]);
+        assert!(output.status.success());
+    }
+    lookup.assert();
+    pre_release.assert();
+    final_release.assert();
+}
+
+#[test]
+fn publish_updates_existing_releases_only_if_needed() {
+    let mut fixture = Fixture::new();
+    fixture.tag("s3gw", "v0.16.0");
+    fixture.init_workspace();
+
+    let published = fixture
+        .forge
+        .mock("GET", "/repos/aquarist-labs/s3gw/releases/tags/v0.16.0")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"id": 7, "html_url": "https://example.com/releases/7",
+                "name": "v0.16.0", "body": "v0.16.0", "prerelease": false}"#,
+        )
+        .expect(1)
+        .create();
+    let create = fixture.forge.mock("POST", Matcher::Any).expect(0).create();
+    let update = fixture
+        .forge
+        .mock("PATCH", "/repos/aquarist-labs/s3gw/releases/7")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"id": 7, "html_url": "https://example.com/releases/7",
+                "name": "v0.16.0", "body": "v0.16.0", "prerelease": false}"#,
+        )
+        .expect(1)
+        .create();
+
+    // already published as is, nothing to do.
+    let output = fixture.arc(&["rel", "publish", "-v", "0.16.0"]);
+    assert!(output.status.success());
+    published.assert();
+
+    // published with outdated notes, update it.
+    fixture
+        .forge
+        .mock("GET", "/repos/aquarist-labs/s3gw/releases/tags/v0.16.0")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"id": 7, "html_url": "https://example.com/releases/7",
+                "name": "v0.16.0", "body": "outdated", "prerelease": false}"#,
+        )
+        .create();
+    let output = fixture.arc(&["rel", "publish", "-v", "0.16.0"]);
+    assert!(output.status.success());
+    update.assert();
+    create.assert();
+}
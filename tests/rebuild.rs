@@ -0,0 +1,112 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! End-to-end tests for re-running release workflows.
+
+mod common;
+
+use common::Fixture;
+
+/// Mock the endpoint re-running the failed jobs of workflow run 42.
+///
+fn mock_rerun(fixture: &mut Fixture) -> mockito::Mock {
+    fixture
+        .forge
+        .mock(
+            "POST",
+            "/repos/aquarist-labs/s3gw/actions/runs/42/rerun-failed-jobs",
+        )
+        .with_status(201)
+        .with_header("content-type", "application/json")
+        .with_body("{}")
+}
+
+#[test]
+fn rebuild_reruns_failed_workflow_and_watches_it() {
+    let mut fixture = Fixture::new();
+    fixture.start_release();
+
+    // once when looking up the failed run, and once more before the re-run
+    // shows up.
+    let failed = fixture.mock_run(1, "completed", Some("failure"), Some(2));
+    let queued = fixture.mock_run(2, "queued", None, Some(1));
+    let succeeded = fixture.mock_run(2, "completed", Some("success"), None);
+    let rerun = mock_rerun(&mut fixture).expect(1).create();
+
+    let output = fixture.arc(&["rel", "rebuild", "--watch", "--poll-interval", "0"]);
+    rerun.assert();
+    failed.assert();
+    queued.assert();
+    succeeded.assert();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Re-running failed jobs of release workflow for 'v0.17.0-rc1'"));
+    assert!(stdout.contains("Release workflow for 'v0.17.0-rc1' succeeded."));
+}
+
+#[test]
+fn rebuild_refuses_successful_workflow() {
+    let mut fixture = Fixture::new();
+    fixture.start_release();
+
+    let _succeeded = fixture.mock_run(1, "completed", Some("success"), None);
+    let rerun = mock_rerun(&mut fixture).expect(0).create();
+
+    let output = fixture.arc(&["rel", "rebuild"]);
+    rerun.assert();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("release build already succeeded"));
+}
+
+#[test]
+fn rebuild_does_not_resend_failed_rerun() {
+    let mut fixture = Fixture::new();
+    fixture.start_release();
+
+    let _failed = fixture.mock_run(1, "completed", Some("failure"), None);
+    // the re-run may have been requested even though the server errored, so
+    // it must not be requested again.
+    let rerun = mock_rerun(&mut fixture).with_status(502).expect(1).create();
+
+    let output = fixture.arc(&["rel", "rebuild"]);
+    rerun.assert();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Unable to re-run release workflow for 'v0.17.0-rc1'"));
+}
+
+#[test]
+fn rebuild_retries_rate_limited_rerun() {
+    let mut fixture = Fixture::new();
+    fixture.start_release();
+
+    let _failed = fixture.mock_run(1, "completed", Some("failure"), None);
+    // a rate limit rejects the request, so it is safely resent.
+    let rate_limited = fixture
+        .forge
+        .mock(
+            "POST",
+            "/repos/aquarist-labs/s3gw/actions/runs/42/rerun-failed-jobs",
+        )
+        .with_status(403)
+        .with_header("retry-after", "0")
+        .with_body(r#"{"message": "You have exceeded a secondary rate limit."}"#)
+        .expect(1)
+        .create();
+    let rerun = mock_rerun(&mut fixture).expect(1).create();
+
+    let output = fixture.arc(&["rel", "rebuild"]);
+    rate_limited.assert();
+    rerun.assert();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Re-running failed jobs of release workflow for 'v0.17.0-rc1'"));
+}
@@ -0,0 +1,421 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! End-to-end release tests, driving a full release ('rel start', 'rel
+//! continue', 'rel finish') against local repositories and a mock forge.
+
+mod common;
+
+use common::Fixture;
+use mockito::Matcher;
+
+/// Mock Quay's repository endpoint, with an empty set of tags for every image.
+///
+fn mock_registry(fixture: &mut Fixture) -> mockito::Mock {
+    fixture
+        .forge
+        .mock("GET", Matcher::Regex(r"^/api/v1/repository/s3gw/".into()))
+        .match_query(Matcher::UrlEncoded("includeTags".into(), "true".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"tags": {}}"#)
+        .create()
+}
+
+/// Assert all repositories have been tagged with 'version' (e.g.,
+/// '0.17.0-rc1'), and the main repository's tag references its submodules'
+/// tags.
+///
+fn assert_tagged(fixture: &Fixture, version: &str) {
+    let main_tag = format!("{}{}", Fixture::tag_prefix("s3gw"), version);
+    let main_commit = fixture.tag_commit("s3gw", &main_tag);
+
+    for (path, name) in Fixture::submodules() {
+        let tag = format!("{}{}", Fixture::tag_prefix(name), version);
+        let commit = fixture.tag_commit(name, &tag);
+        let (gitlink, mode) = fixture.entry("s3gw", main_commit, path);
+        assert_eq!(mode, 0o160000, "'{}' is not a submodule", path);
+        assert_eq!(
+            gitlink, commit,
+            "submodule '{}' on '{}' does not point to '{}'",
+            path, main_tag, tag
+        );
+    }
+}
+
+#[test]
+fn release_start_continue_finish() {
+    let mut fixture = Fixture::new();
+    // the release workflow is checked once before continuing, and once before
+    // finishing the release.
+    let runs = fixture.mock_successful_runs().expect(2);
+    let _registry = mock_registry(&mut fixture);
+    let pull_request = fixture
+        .forge
+        .mock("POST", "/repos/aquarist-labs/s3gw/pulls")
+        .match_header("authorization", "Bearer ghp_fixture")
+        .match_body(Matcher::PartialJson(serde_json::json!({
+            "title": "Release v0.17.0",
+            "base": "main",
+        })))
+        .with_status(201)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"html_url": "https://example.com/pull/1", "number": 1}"#)
+        .expect(1)
+        .create();
+    let charts_pull_request = fixture
+        .forge
+        .mock("POST", "/repos/aquarist-labs/s3gw-charts/pulls")
+        .match_body(Matcher::PartialJson(serde_json::json!({
+            "title": "Update charts to version 0.17.0",
+            "base": "main",
+        })))
+        .with_status(201)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"html_url": "https://example.com/pull/2", "number": 2}"#)
+        .expect(1)
+        .create();
+
+    fixture.init_workspace();
+    let charts_github = serde_json::json!({
+        "org": "aquarist-labs",
+        "repo": "s3gw-charts",
+        "api_base": fixture.forge.url(),
+    });
+    fixture.arc(&[
+        "ws",
+        "set",
+        "git.repos.s3gw-charts.github",
+        &charts_github.to_string(),
+    ]);
+
+    // start release, cutting release branches and tagging the first candidate
+    let notes = fixture.write_notes("notes-rc1.md", "# v0.17.0\n\nFirst candidate.\n");
+    fixture.arc(&[
+        "rel",
+        "start",
+        "--yes",
+        "--notes",
+        notes.to_str().unwrap(),
+        "0.17.0",
+    ]);
+
+    for name in Fixture::repo_names() {
+        assert_eq!(
+            fixture.branches(name, "s3gw-v"),
+            vec!["s3gw-v0.17".to_string()],
+            "release branch missing on '{}'",
+            name
+        );
+    }
+    assert_tagged(&fixture, "0.17.0-rc1");
+
+    let rc1 = fixture.tag_commit("s3gw", "v0.17.0-rc1");
+    assert_eq!(
+        fixture.file("s3gw", rc1, "docs/release-notes/s3gw-v0.17.0.md"),
+        "# v0.17.0\n\nFirst candidate.\n"
+    );
+    let (_, mode) = fixture.entry("s3gw", rc1, "docs/release-notes/latest");
+    assert_eq!(mode, 0o120000, "'latest' is not a symlink");
+    assert_eq!(
+        fixture.file("s3gw", rc1, "docs/release-notes/latest"),
+        "s3gw-v0.17.0.md"
+    );
+
+    // continue with a second candidate, with updated release notes
+    let notes = fixture.write_notes("notes-rc2.md", "# v0.17.0\n\nSecond candidate.\n");
+    fixture.arc(&["rel", "continue", "--notes", notes.to_str().unwrap()]);
+
+    assert_tagged(&fixture, "0.17.0-rc2");
+    let rc2 = fixture.tag_commit("s3gw", "v0.17.0-rc2");
+    assert_eq!(
+        fixture.file("s3gw", rc2, "docs/release-notes/s3gw-v0.17.0.md"),
+        "# v0.17.0\n\nSecond candidate.\n"
+    );
+
+    // finish the release, which must open a pull request against 'main'
+    fixture.arc(&["rel", "finish"]);
+
+    assert_tagged(&fixture, "0.17.0");
+    runs.assert();
+    pull_request.assert();
+
+    // charts are published by pushing to the final branch
+    let charts_final = fixture.branch_commit("s3gw-charts", "v0.17");
+    assert_eq!(
+        charts_final,
+        fixture.tag_commit("s3gw-charts", "s3gw-v0.17.0")
+    );
+    assert_eq!(
+        fixture.file("s3gw-charts", charts_final, "charts/s3gw/Chart.yaml"),
+        "version: 0.17.0\n"
+    );
+
+    // and the chart version update is proposed against 'main'
+    charts_pull_request.assert();
+    let charts_branches = fixture.branches("s3gw-charts", "release-v0.17.0-");
+    assert_eq!(
+        charts_branches.len(),
+        1,
+        "expected a chart pull request branch"
+    );
+    let charts_head = fixture.branch_commit("s3gw-charts", &charts_branches[0]);
+    let charts_upstream = fixture.upstream("s3gw-charts");
+    assert_eq!(
+        charts_upstream
+            .find_commit(charts_head)
+            .unwrap()
+            .parent_id(0)
+            .unwrap(),
+        fixture.branch_commit("s3gw-charts", "main")
+    );
+    assert_eq!(
+        fixture.file("s3gw-charts", charts_head, "charts/s3gw/Chart.yaml"),
+        "version: 0.17.0\n"
+    );
+
+    // the pull request's branch carries the release notes and docs update
+    let pr_branches = fixture.branches("s3gw", "release-v0.17.0-");
+    assert_eq!(
+        pr_branches.len(),
+        1,
+        "expected a single pull request branch"
+    );
+    let pr_head = fixture.branch_commit("s3gw", &pr_branches[0]);
+    assert_eq!(
+        fixture.file("s3gw", pr_head, "docs/release-notes/s3gw-v0.17.0.md"),
+        "# v0.17.0\n\nSecond candidate.\n"
+    );
+    let mkdocs: serde_yaml::Value =
+        serde_yaml::from_str(&fixture.file("s3gw", pr_head, "mkdocs.yml")).unwrap();
+    let release_notes = mkdocs["nav"]
+        .as_sequence()
+        .unwrap()
+        .iter()
+        .find_map(|e| e.get("Release Notes"))
+        .unwrap();
+    assert!(release_notes
+        .as_sequence()
+        .unwrap()
+        .iter()
+        .any(|e| e["v0.17.0"] == "release-notes/s3gw-v0.17.0.md"));
+
+    // the pull request updates submodules to the final release
+    let final_commit = fixture.tag_commit("s3gw", "v0.17.0");
+    for (path, _) in Fixture::submodules() {
+        assert_eq!(
+            fixture.entry("s3gw", pr_head, path),
+            fixture.entry("s3gw", final_commit, path),
+            "submodule '{}' not updated on pull request branch",
+            path
+        );
+    }
+}
+
+#[test]
+fn release_start_dry_run() {
+    let fixture = Fixture::new();
+    fixture.init_workspace();
+
+    let notes = fixture.write_notes("notes.md", "# v0.17.0\n");
+    let output = fixture.arc(&[
+        "rel",
+        "start",
+        "--yes",
+        "--dry-run",
+        "--notes",
+        notes.to_str().unwrap(),
+        "0.17.0",
+    ]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success());
+    assert!(stdout.contains("Release for version 0.17.0 successfully started!"));
+    assert!(!stdout.contains("Error"));
+    assert!(!stdout.contains("arc rel resume"));
+
+    // the plan lists what would have been done
+    assert!(stdout.contains("Dry-run plan:"));
+    assert!(stdout.contains("create branch 's3gw-v0.17' from 'refs/remotes/ro/main'"));
+    assert!(stdout.contains("create signed tag 's3gw-v0.17.0-rc1' on 's3gw-v0.17'"));
+    assert!(stdout.contains("commit 'release candidate 1 for v0.17.0'"));
+    assert!(stdout.contains("push 'refs/tags/v0.17.0-rc1'"));
+
+    // but nothing has been done, neither upstream nor in the workspace.
+    for name in Fixture::repo_names() {
+        let prefix = format!("{}0.17.0*", Fixture::tag_prefix(name));
+        assert!(fixture.branches(name, "s3gw-v").is_empty());
+        assert!(fixture
+            .upstream(name)
+            .tag_names(Some(&prefix))
+            .unwrap()
+            .is_empty());
+
+        let clone = git2::Repository::open(fixture.clone_path(name)).unwrap();
+        assert!(clone.find_reference("refs/heads/s3gw-v0.17").is_err());
+        assert!(clone.tag_names(Some(&prefix)).unwrap().is_empty());
+    }
+    let arc_dir = fixture.workspace().join(".arc");
+    assert!(!arc_dir.join("release.json").exists());
+    assert!(!arc_dir.join("journal.json").exists());
+}
+
+#[test]
+fn release_status_reports_candidates() {
+    let mut fixture = Fixture::new();
+    let _runs = fixture.mock_successful_runs();
+    let _registry = mock_registry(&mut fixture);
+
+    fixture.init_workspace();
+    let notes = fixture.write_notes("notes.md", "# v0.17.0\n");
+    fixture.arc(&[
+        "rel",
+        "start",
+        "--yes",
+        "--notes",
+        notes.to_str().unwrap(),
+        "0.17.0",
+    ]);
+
+    let output = fixture.arc(&["rel", "status", "-o", "json"]);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let releases = report["releases"].as_array().unwrap();
+    assert_eq!(releases.len(), 1);
+    assert_eq!(releases[0]["version"], "0.17.0-rc1");
+    let workflows = releases[0]["workflows"].as_array().unwrap();
+    assert_eq!(workflows.len(), 1);
+    assert_eq!(workflows[0]["repo"], "s3gw");
+    assert_eq!(workflows[0]["workflow"], "Release S3GW");
+    assert_eq!(workflows[0]["status"], "completed");
+    for repo in report["repositories"].as_array().unwrap() {
+        assert_eq!(repo["valid"], true, "repository {} not valid", repo["name"]);
+    }
+}
+
+#[test]
+fn finish_updates_chart_and_image_tags() {
+    let mut fixture = Fixture::new();
+    let _runs = fixture.mock_successful_runs();
+    let _registry = mock_registry(&mut fixture);
+    let _pull_request = fixture
+        .forge
+        .mock("POST", "/repos/aquarist-labs/s3gw/pulls")
+        .with_status(201)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"html_url": "https://example.com/pull/1", "number": 1}"#)
+        .create();
+
+    let chart = "\
+# s3gw Helm chart
+apiVersion: v2
+name: s3gw
+version: 0.16.0 # bumped on release
+appVersion: \"v0.16.0\"
+dependencies:
+  - name: common
+    version: 1.2.3
+";
+    let values = "\
+imageTag: v0.16.0
+ui:
+  # the UI's image
+  imageTag: 'v0.16.0'
+  imageName: s3gw/s3gw-ui
+";
+    fixture.commit_file("s3gw-charts", "charts/s3gw/Chart.yaml", chart);
+    fixture.commit_file("s3gw-charts", "charts/s3gw/values.yaml", values);
+    fixture.push("s3gw-charts", "main");
+
+    fixture.init_workspace();
+    fixture.arc(&[
+        "ws",
+        "set",
+        "git.repos.s3gw-charts.chart_image_tags",
+        r#"["imageTag", "ui.imageTag"]"#,
+    ]);
+    let notes = fixture.write_notes("notes.md", "# v0.17.0\n");
+    fixture.arc(&[
+        "rel",
+        "start",
+        "--yes",
+        "--notes",
+        notes.to_str().unwrap(),
+        "0.17.0",
+    ]);
+    fixture.arc(&["rel", "finish"]);
+
+    let charts_final = fixture.tag_commit("s3gw-charts", "s3gw-v0.17.0");
+    assert_eq!(
+        fixture.file("s3gw-charts", charts_final, "charts/s3gw/Chart.yaml"),
+        chart
+            .replace("version: 0.16.0 #", "version: 0.17.0 #")
+            .replace("\"v0.16.0\"", "\"v0.17.0\"")
+    );
+    assert_eq!(
+        fixture.file("s3gw-charts", charts_final, "charts/s3gw/values.yaml"),
+        values.replace("v0.16.0", "v0.17.0")
+    );
+}
+
+#[test]
+fn finish_falls_back_when_chart_update_conflicts() {
+    let mut fixture = Fixture::new();
+    let _runs = fixture.mock_successful_runs();
+    let _registry = mock_registry(&mut fixture);
+    let pull_request = fixture
+        .forge
+        .mock("POST", "/repos/aquarist-labs/s3gw/pulls")
+        .with_status(201)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"html_url": "https://example.com/pull/1", "number": 1}"#)
+        .expect(1)
+        .create();
+    let charts_pull_request = fixture
+        .forge
+        .mock("POST", "/repos/aquarist-labs/s3gw-charts/pulls")
+        .expect(0)
+        .create();
+
+    fixture.start_release();
+    let charts_github = serde_json::json!({
+        "org": "aquarist-labs",
+        "repo": "s3gw-charts",
+        "api_base": fixture.forge.url(),
+    });
+    fixture.arc(&[
+        "ws",
+        "set",
+        "git.repos.s3gw-charts.github",
+        &charts_github.to_string(),
+    ]);
+
+    // the chart changes on 'main' after the release branch has been cut, so
+    // the chart update no longer applies cleanly.
+    fixture.commit_file("s3gw-charts", "charts/s3gw/Chart.yaml", "version: 0.16.1\n");
+    fixture.push("s3gw-charts", "main");
+
+    let output = fixture.arc(&["rel", "finish"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("To finish the Helm Chart release, please do the following:"));
+    assert!(stdout.contains("charts/s3gw/Chart.yaml"));
+    assert!(stdout.contains("Version 0.17.0 released!"));
+
+    // the release is finished regardless, including the s3gw pull request.
+    assert_tagged(&fixture, "0.17.0");
+    pull_request.assert();
+    charts_pull_request.assert();
+    assert!(fixture
+        .branches("s3gw-charts", "release-v0.17.0-")
+        .is_empty());
+    assert!(!fixture.workspace().join(".arc/journal.json").exists());
+}
@@ -0,0 +1,194 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! End-to-end tests for resuming, and rolling back, a release that failed
+//! halfway through.
+
+mod common;
+
+use common::Fixture;
+
+/// Make pushing from the workspace's 's3gw-ceph' clone fail, while still
+/// allowing fetching from it.
+///
+fn break_push(fixture: &Fixture) {
+    let bogus = fixture.path("remotes/nonexistent.git");
+    fixture.git(
+        &fixture.clone_path("s3gw-ceph"),
+        &["remote", "set-url", "--push", "rw", bogus.to_str().unwrap()],
+    );
+}
+
+/// Start release 0.17.0, failing to push 's3gw-ceph'. Repositories before it
+/// have been tagged and pushed by then.
+///
+fn start_failing(fixture: &Fixture) {
+    fixture.init_workspace();
+    break_push(&fixture);
+    let notes = fixture.write_notes("notes.md", "# v0.17.0\n");
+    let output = fixture.arc(&[
+        "rel",
+        "start",
+        "--yes",
+        "--notes",
+        notes.to_str().unwrap(),
+        "0.17.0",
+    ]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Use 'arc rel resume' to retry, or 'arc rel rollback' to undo."));
+
+    assert!(fixture.workspace().join(".arc/journal.json").exists());
+    fixture.tag_commit("s3gw-ui", "s3gw-v0.17.0-rc1");
+    assert!(fixture.branches("s3gw-ceph", "s3gw-v").is_empty());
+    assert!(fixture
+        .upstream("s3gw")
+        .tag_names(Some("v0.17.0*"))
+        .unwrap()
+        .is_empty());
+}
+
+/// Make cutting the release branch on the workspace's 's3gw-ceph' clone fail,
+/// by holding a branch whose name is in the way.
+///
+fn block_branch_cut(fixture: &Fixture) {
+    fixture.git(
+        &fixture.clone_path("s3gw-ceph"),
+        &["branch", "s3gw-v0.17/blocker"],
+    );
+}
+
+/// Start release 0.17.0, failing to cut the release branch on 's3gw-ceph'.
+/// Repositories before it have had their branches cut by then.
+///
+fn start_failing_branch_cuts(fixture: &Fixture) {
+    fixture.init_workspace();
+    block_branch_cut(&fixture);
+    let notes = fixture.write_notes("notes.md", "# v0.17.0\n");
+    let output = fixture.arc(&[
+        "rel",
+        "start",
+        "--yes",
+        "--notes",
+        notes.to_str().unwrap(),
+        "0.17.0",
+    ]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Use 'arc rel rollback' to undo the branches created."));
+
+    let arc_dir = fixture.workspace().join(".arc");
+    assert!(arc_dir.join("journal.json").exists());
+    assert!(!arc_dir.join("release.json").exists());
+}
+
+#[test]
+fn resume_completes_partial_branch_cuts() {
+    let fixture = Fixture::new();
+    start_failing_branch_cuts(&fixture);
+
+    fixture.git(
+        &fixture.clone_path("s3gw-ceph"),
+        &["branch", "-D", "s3gw-v0.17/blocker"],
+    );
+    fixture.arc(&["rel", "resume"]);
+
+    let arc_dir = fixture.workspace().join(".arc");
+    assert!(!arc_dir.join("journal.json").exists());
+    assert!(arc_dir.join("release.json").exists());
+    for name in Fixture::repo_names() {
+        let tag = format!("{}0.17.0-rc1", Fixture::tag_prefix(name));
+        let commit = fixture.tag_commit(name, &tag);
+        assert_eq!(fixture.branch_commit(name, "s3gw-v0.17"), commit);
+    }
+}
+
+#[test]
+fn resume_refuses_unfinished_branch_cuts() {
+    let fixture = Fixture::new();
+    start_failing_branch_cuts(&fixture);
+
+    let output = fixture.arc(&["rel", "resume"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Use 'arc rel rollback' to undo the release instead."));
+
+    // the workspace is not bound to the release, which can still be rolled
+    // back.
+    let arc_dir = fixture.workspace().join(".arc");
+    assert!(arc_dir.join("journal.json").exists());
+    assert!(!arc_dir.join("release.json").exists());
+    fixture.arc(&["rel", "rollback"]);
+    assert!(!arc_dir.join("journal.json").exists());
+    let clone = git2::Repository::open(fixture.clone_path("s3gw")).unwrap();
+    assert!(clone.find_reference("refs/heads/s3gw-v0.17").is_err());
+}
+
+#[test]
+fn resume_completes_failed_release() {
+    let fixture = Fixture::new();
+    start_failing(&fixture);
+
+    fixture.git(
+        &fixture.clone_path("s3gw-ceph"),
+        &["config", "--unset", "remote.rw.pushurl"],
+    );
+    fixture.arc(&["rel", "resume"]);
+
+    assert!(!fixture.workspace().join(".arc/journal.json").exists());
+    for name in Fixture::repo_names() {
+        let tag = format!("{}0.17.0-rc1", Fixture::tag_prefix(name));
+        let commit = fixture.tag_commit(name, &tag);
+        assert_eq!(fixture.branch_commit(name, "s3gw-v0.17"), commit);
+    }
+    let rc1 = fixture.tag_commit("s3gw", "v0.17.0-rc1");
+    assert_eq!(
+        fixture.file("s3gw", rc1, "docs/release-notes/s3gw-v0.17.0.md"),
+        "# v0.17.0\n"
+    );
+}
+
+#[test]
+fn rollback_undoes_failed_release() {
+    let fixture = Fixture::new();
+    start_failing(&fixture);
+
+    fixture.arc(&["rel", "rollback"]);
+
+    let arc_dir = fixture.workspace().join(".arc");
+    assert!(!arc_dir.join("journal.json").exists());
+    assert!(!arc_dir.join("release.json").exists());
+    for name in Fixture::repo_names() {
+        assert!(
+            fixture.branches(name, "s3gw-v").is_empty(),
+            "release branch left on '{}'",
+            name
+        );
+        let prefix = Fixture::tag_prefix(name);
+        assert!(
+            fixture
+                .upstream(name)
+                .tag_names(Some(&format!("{}0.17.0*", prefix)))
+                .unwrap()
+                .is_empty(),
+            "release tag left on '{}'",
+            name
+        );
+
+        // and locally, too.
+        let clone = git2::Repository::open(fixture.clone_path(name)).unwrap();
+        assert!(clone.find_reference("refs/heads/s3gw-v0.17").is_err());
+        assert!(clone
+            .tag_names(Some(&format!("{}0.17.0*", prefix)))
+            .unwrap()
+            .is_empty());
+    }
+}
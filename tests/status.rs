@@ -0,0 +1,391 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! End-to-end tests for release status details.
+
+mod common;
+
+use common::{workflow_run, Fixture};
+use mockito::Matcher;
+
+#[test]
+fn status_details_show_failed_job_and_log() {
+    let mut fixture = Fixture::new();
+    fixture.init_workspace();
+    let notes = fixture.write_notes("notes.md", "# v0.17.0\n");
+    fixture.arc(&[
+        "rel",
+        "start",
+        "--yes",
+        "--notes",
+        notes.to_str().unwrap(),
+        "0.17.0",
+    ]);
+    fixture.tag_commit("s3gw", "v0.17.0-rc1");
+
+    let _registry = fixture
+        .forge
+        .mock("GET", Matcher::Regex(r"^/api/v1/repository/s3gw/".into()))
+        .match_query(Matcher::Any)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"tags": {}}"#)
+        .create();
+    let _runs = fixture.mock_run(1, "completed", Some("failure"), None);
+    let jobs_body = serde_json::json!({
+        "total_count": 2,
+        "jobs": [{
+            "id": 7,
+            "name": "build-radosgw",
+            "status": "completed",
+            "conclusion": "success",
+            "html_url": "https://example.com/runs/42/job/7",
+            "steps": [],
+        }, {
+            "id": 8,
+            "name": "build-ui",
+            "status": "completed",
+            "conclusion": "failure",
+            "html_url": "https://example.com/runs/42/job/8",
+            "steps": [{
+                "name": "Checkout",
+                "number": 1,
+                "status": "completed",
+                "conclusion": "success",
+            }, {
+                "name": "Build image",
+                "number": 2,
+                "status": "completed",
+                "conclusion": "failure",
+            }],
+        }],
+    });
+    let jobs = fixture
+        .forge
+        .mock("GET", "/repos/aquarist-labs/s3gw/actions/runs/42/jobs")
+        .match_query(Matcher::Any)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(jobs_body.to_string())
+        .create();
+    let log = fixture
+        .forge
+        .mock("GET", "/repos/aquarist-labs/s3gw/actions/jobs/8/logs")
+        .with_status(200)
+        .with_body("step 1 ok\nstep 2 building\nerror: npm install failed\n")
+        .create();
+
+    let output = fixture.arc(&["rel", "status", "--logs", "2"]);
+    jobs.assert();
+    log.assert();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("https://example.com/runs/42"));
+    assert!(stdout.contains("failed steps: Build image"));
+    assert!(stdout.contains("https://example.com/runs/42/job/8"));
+    assert!(!stdout.contains("https://example.com/runs/42/job/7"));
+    assert!(stdout.contains("error: npm install failed"));
+    assert!(stdout.contains("step 2 building"));
+    assert!(!stdout.contains("step 1 ok"));
+}
+
+#[test]
+fn status_tracks_configured_workflows() {
+    let mut fixture = Fixture::new();
+    fixture.init_workspace();
+    let github = serde_json::json!({
+        "org": "aquarist-labs",
+        "repo": "s3gw-charts",
+        "api_base": fixture.forge.url(),
+        "workflows": [{ "name": "Publish Chart" }],
+    });
+    fixture.arc(&[
+        "ws",
+        "set",
+        "git.repos.s3gw.github.workflows",
+        r#"[{"path": ".github/workflows/release.yaml"}]"#,
+    ]);
+    fixture.arc(&[
+        "ws",
+        "set",
+        "git.repos.s3gw-charts.github",
+        &github.to_string(),
+    ]);
+    let notes = fixture.write_notes("notes.md", "# v0.17.0\n");
+    fixture.arc(&[
+        "rel",
+        "start",
+        "--yes",
+        "--notes",
+        notes.to_str().unwrap(),
+        "0.17.0",
+    ]);
+
+    let _registry = fixture
+        .forge
+        .mock("GET", Matcher::Regex(r"^/api/v1/repository/s3gw/".into()))
+        .match_query(Matcher::Any)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"tags": {}}"#)
+        .create();
+    let run = |tag: &str, name: &str, path: &str| {
+        let mut run = workflow_run(1, name, tag, "completed", Some("success"), 1);
+        run["path"] = path.into();
+        run["workflow_id"] = 1000.into();
+        run
+    };
+    let s3gw_run = run(
+        "v0.17.0-rc1",
+        "Release Fork",
+        ".github/workflows/release.yaml@refs/tags/v0.17.0-rc1",
+    );
+    let s3gw = fixture.mock_runs("s3gw", "v0.17.0-rc1", vec![s3gw_run], None);
+    let charts_run = run(
+        "s3gw-v0.17.0-rc1",
+        "Publish Chart",
+        ".github/workflows/publish.yaml",
+    );
+    let charts = fixture.mock_runs("s3gw-charts", "s3gw-v0.17.0-rc1", vec![charts_run], None);
+
+    let output = fixture.arc(&["rel", "status", "-o", "json"]);
+    s3gw.assert();
+    charts.assert();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let workflows = report["releases"][0]["workflows"].as_array().unwrap();
+    assert_eq!(workflows.len(), 2);
+    assert_eq!(workflows[0]["repo"], "s3gw");
+    assert_eq!(workflows[0]["workflow"], "Release Fork");
+    assert_eq!(workflows[1]["repo"], "s3gw-charts");
+    assert_eq!(workflows[1]["workflow"], "Publish Chart");
+}
+
+#[test]
+fn status_batches_github_queries() {
+    let mut fixture = Fixture::new();
+    fixture.init_workspace();
+    let notes = fixture.write_notes("notes.md", "# v0.17.0\n");
+    fixture.arc(&[
+        "rel",
+        "start",
+        "--yes",
+        "--notes",
+        notes.to_str().unwrap(),
+        "0.17.0",
+    ]);
+    fixture.tag_commit("s3gw", "v0.17.0-rc1");
+
+    let _registry = fixture
+        .forge
+        .mock("GET", Matcher::Regex(r"^/api/v1/repository/s3gw/".into()))
+        .match_query(Matcher::Any)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"tags": {}}"#)
+        .create();
+    let suite = |conclusion: &str, created_at: &str, url: &str| {
+        serde_json::json!({
+            "status": "COMPLETED",
+            "conclusion": conclusion,
+            "createdAt": created_at,
+            "updatedAt": created_at,
+            "workflowRun": {
+                "databaseId": 42,
+                "createdAt": created_at,
+                "updatedAt": created_at,
+                "url": url,
+                "file": { "path": ".github/workflows/release.yaml" },
+                "workflow": { "databaseId": 1000, "name": "Release S3GW" },
+            },
+        })
+    };
+    let graphql_body = serde_json::json!({
+        "data": {
+            "repository": {
+                "refs": {
+                    "nodes": [{
+                        "name": "v0.17.0-rc1",
+                        "target": {
+                            "__typename": "Tag",
+                            "target": {
+                                "__typename": "Commit",
+                                "checkSuites": {
+                                    "nodes": [
+                                        suite("SUCCESS", "2023-10-01T00:00:00Z", "https://example.com/runs/42"),
+                                        suite("CANCELLED", "2023-10-02T00:00:00Z", "https://example.com/runs/43"),
+                                        { "status": "COMPLETED", "conclusion": "SUCCESS",
+                                          "createdAt": "2023-10-03T00:00:00Z",
+                                          "updatedAt": "2023-10-03T00:00:00Z",
+                                          "workflowRun": null },
+                                    ],
+                                },
+                            },
+                        },
+                    }],
+                    "pageInfo": { "hasNextPage": false, "endCursor": null },
+                },
+                "pullRequests": {
+                    "nodes": [{
+                        "number": 12,
+                        "title": "Release v0.17.0",
+                        "url": "https://example.com/pull/12",
+                        "headRefName": "release-v0.17.0-20231001-000000",
+                        "baseRefName": "main",
+                    }, {
+                        "number": 13,
+                        "title": "Unrelated",
+                        "url": "https://example.com/pull/13",
+                        "headRefName": "fix-something",
+                        "baseRefName": "main",
+                    }],
+                },
+            },
+        },
+    });
+    // queries change nothing, so they are resent despite being POSTs.
+    let unavailable = fixture
+        .forge
+        .mock("POST", "/graphql")
+        .with_status(502)
+        .expect(1)
+        .create();
+    let graphql = fixture
+        .forge
+        .mock("POST", "/graphql")
+        .match_body(Matcher::PartialJson(serde_json::json!({
+            "variables": { "owner": "aquarist-labs", "name": "s3gw", "tagQuery": "v0.17.0-rc1" },
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(graphql_body.to_string())
+        .expect(1)
+        .create();
+    let runs = fixture
+        .forge
+        .mock("GET", "/repos/aquarist-labs/s3gw/actions/runs")
+        .match_query(Matcher::Any)
+        .expect(0)
+        .create();
+
+    let output = fixture.arc(&["rel", "status", "-o", "json"]);
+    unavailable.assert();
+    graphql.assert();
+    runs.assert();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let release = &report["releases"][0];
+    let workflows = release["workflows"].as_array().unwrap();
+    assert_eq!(workflows.len(), 1);
+    assert_eq!(workflows[0]["id"], 42);
+    assert_eq!(workflows[0]["workflow"], "Release S3GW");
+    assert_eq!(workflows[0]["success"], true);
+    assert_eq!(workflows[0]["url"], "https://example.com/runs/42");
+    // attempts are not reported through GraphQL
+    assert!(workflows[0].get("num_attempts").is_none());
+    let prs = release["pull_requests"].as_array().unwrap();
+    assert_eq!(prs.len(), 1);
+    assert_eq!(prs[0]["repo"], "s3gw");
+    assert_eq!(prs[0]["number"], 12);
+
+    let output = fixture.arc(&["rel", "status"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("s3gw pull request #12 open: https://example.com/pull/12"));
+    assert!(stdout.contains("build status: completed"));
+    assert!(!stdout.contains("attempt"));
+}
+
+#[test]
+fn status_retries_and_paginates_workflow_runs() {
+    let mut fixture = Fixture::new();
+    fixture.init_workspace();
+    let notes = fixture.write_notes("notes.md", "# v0.17.0\n");
+    fixture.arc(&[
+        "rel",
+        "start",
+        "--yes",
+        "--notes",
+        notes.to_str().unwrap(),
+        "0.17.0",
+    ]);
+    fixture.tag_commit("s3gw", "v0.17.0-rc1");
+
+    let _registry = fixture
+        .forge
+        .mock("GET", Matcher::Regex(r"^/api/v1/repository/s3gw/".into()))
+        .match_query(Matcher::Any)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"tags": {}}"#)
+        .create();
+    let run = |id: u64, name: &str| {
+        workflow_run(id, name, "v0.17.0-rc1", "completed", Some("success"), 1)
+    };
+    let page2_body = serde_json::json!({
+        "total_count": 2,
+        "workflow_runs": [run(42, "Release S3GW")],
+    });
+    let page2 = fixture
+        .forge
+        .mock("GET", "/repos/aquarist-labs/s3gw/actions/runs")
+        .match_query(Matcher::UrlEncoded("page".into(), "2".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(page2_body.to_string())
+        .create();
+    // a secondary rate limit, then a transient server error, before page 1
+    let rate_limited = fixture
+        .forge
+        .mock("GET", "/repos/aquarist-labs/s3gw/actions/runs")
+        .match_query(Matcher::Any)
+        .with_status(403)
+        .with_header("retry-after", "0")
+        .with_body(r#"{"message": "You have exceeded a secondary rate limit."}"#)
+        .create();
+    let unavailable = fixture
+        .forge
+        .mock("GET", "/repos/aquarist-labs/s3gw/actions/runs")
+        .match_query(Matcher::Any)
+        .with_status(502)
+        .create();
+    let page1_body = serde_json::json!({
+        "total_count": 2,
+        "workflow_runs": [run(41, "Unit Tests")],
+    });
+    let page1 = fixture
+        .forge
+        .mock("GET", "/repos/aquarist-labs/s3gw/actions/runs")
+        .match_query(Matcher::UrlEncoded("per_page".into(), "100".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("x-ratelimit-limit", "5000")
+        .with_header("x-ratelimit-remaining", "4999")
+        .with_header(
+            "link",
+            &format!(
+                r#"<{}/repos/aquarist-labs/s3gw/actions/runs?branch=v0.17.0-rc1&per_page=100&page=2>; rel="next""#,
+                fixture.forge.url()
+            ),
+        )
+        .with_body(page1_body.to_string())
+        .create();
+
+    let output = fixture.arc(&["rel", "status", "-o", "json"]);
+    rate_limited.assert();
+    unavailable.assert();
+    page1.assert();
+    page2.assert();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let workflows = report["releases"][0]["workflows"].as_array().unwrap();
+    assert_eq!(workflows.len(), 1);
+    assert_eq!(workflows[0]["id"], 42);
+    assert_eq!(workflows[0]["success"], true);
+}
@@ -0,0 +1,79 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! End-to-end tests for waiting on, and watching, release workflows.
+
+mod common;
+
+use common::Fixture;
+use mockito::Matcher;
+
+#[test]
+fn continue_waits_for_release_workflow() {
+    let mut fixture = Fixture::new();
+    fixture.start_release();
+
+    // once when validating, and once more while waiting.
+    let in_progress = fixture.mock_run(1, "in_progress", None, Some(2));
+    let completed = fixture.mock_run(1, "completed", Some("success"), None);
+
+    fixture.arc(&["rel", "continue", "--wait", "--poll-interval", "0"]);
+    in_progress.assert();
+    completed.assert();
+    fixture.tag_commit("s3gw", "v0.17.0-rc2");
+}
+
+#[test]
+fn finish_aborts_once_release_workflow_fails() {
+    let mut fixture = Fixture::new();
+    fixture.start_release();
+
+    let in_progress = fixture.mock_run(1, "queued", None, Some(1));
+    let failed = fixture.mock_run(1, "completed", Some("failure"), None);
+
+    let output = fixture.arc(&["rel", "finish", "--wait", "--poll-interval", "0"]);
+    in_progress.assert();
+    failed.assert();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Release workflow for 'v0.17.0-rc1' failed"));
+    assert!(stdout.contains("https://example.com/runs/42"));
+    assert!(fixture
+        .upstream("s3gw")
+        .find_reference("refs/tags/v0.17.0")
+        .is_err());
+}
+
+#[test]
+fn status_watches_release_workflow() {
+    let mut fixture = Fixture::new();
+    fixture.start_release();
+    let _registry = fixture
+        .forge
+        .mock("GET", Matcher::Regex(r"^/api/v1/repository/s3gw/".into()))
+        .match_query(Matcher::Any)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"tags": {}}"#)
+        .create();
+
+    // once for the status report, and twice while watching.
+    let in_progress = fixture.mock_run(1, "in_progress", None, Some(3));
+    let completed = fixture.mock_run(1, "completed", Some("success"), None);
+
+    let output = fixture.arc(&["rel", "status", "--watch", "--poll-interval", "0"]);
+    in_progress.assert();
+    completed.assert();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Release workflow for 'v0.17.0-rc1' succeeded."));
+}
@@ -0,0 +1,66 @@
+// Copyright 2023 SUSE LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! End-to-end tests for workspace commands.
+
+mod common;
+
+use common::Fixture;
+
+#[test]
+fn sync_updates_changed_remotes() {
+    let fixture = Fixture::new();
+    fixture.init_workspace();
+
+    let old = fixture.upstream_path("s3gw-ui");
+    let new = fixture.path("remotes/s3gw-ui-fork.git");
+    fixture.git(
+        fixture.root.path(),
+        &[
+            "clone",
+            "--quiet",
+            "--bare",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+        ],
+    );
+    fixture.arc(&[
+        "ws",
+        "set",
+        "git.repos.s3gw-ui.readwrite",
+        new.to_str().unwrap(),
+    ]);
+
+    // the clone still points at the old remote.
+    let clone = fixture.clone_path("s3gw-ui");
+    let mismatch = format!(
+        "s3gw-ui: remote 'rw' is '{}', expected '{}'",
+        old.display(),
+        new.display()
+    );
+    let output = fixture.arc(&["ws", "validate"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains(&mismatch));
+
+    fixture.arc(&["ws", "sync"]);
+    assert_eq!(
+        fixture.git(&clone, &["remote", "get-url", "rw"]).trim(),
+        new.to_str().unwrap()
+    );
+    assert_eq!(
+        fixture.git(&clone, &["remote", "get-url", "ro"]).trim(),
+        old.to_str().unwrap()
+    );
+    let output = fixture.arc(&["ws", "validate"]);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("remote 'rw' is"));
+}